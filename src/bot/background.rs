@@ -0,0 +1,90 @@
+use std::sync::{Arc, OnceLock};
+use crate::utils::logger::error;
+use poise::serenity_prelude as serenity;
+use sqlx::SqlitePool;
+use tokio_util::sync::CancellationToken;
+use crate::bot::jobs::{announcement::AnnouncementJob, player_watch::{PlayerWatchJob, PLAYER_WATCH_INTERVAL}, reminder::ReminderJob};
+use crate::bot::jobs::clan_watch::{ClanWatchJob, CLAN_WATCH_INTERVAL};
+use crate::bot::jobs::sp_sampler::{SpSamplerJob, DEFAULT_SP_SAMPLE_BUDGET, SP_SAMPLE_INTERVAL};
+use crate::utils::scheduler::{JobOptions, JobRegistry, OverlapPolicy, Schedule, Scheduler};
+use crate::utils::time::Clock;
+
+/// Discord HTTP client for jobs that post messages. Jobs are registered before the serenity client exists,
+/// so the client is filled in once it has been built.
+#[derive(Clone, Default)]
+pub struct DiscordHandle(Arc<OnceLock<Arc<serenity::Http>>>);
+
+impl DiscordHandle {
+    pub fn set(&self, http: Arc<serenity::Http>) {
+        if self.0.set(http).is_err() {
+            error!("Discord HTTP client for jobs was already set");
+        }
+    }
+
+    pub fn http(&self) -> anyhow::Result<Arc<serenity::Http>> {
+        self.0.get().cloned().ok_or_else(|| anyhow::anyhow!("Discord client is not ready yet"))
+    }
+}
+
+/// Shared resources handed to jobs when they are registered.
+#[derive(Clone)]
+pub struct JobContext {
+    pub discord: DiscordHandle,
+    pub db_pool: SqlitePool,
+    pub wolvesville_client: Arc<reqwest::Client>,
+    /// The clock the scheduler runs on.
+    pub clock: Arc<dyn Clock>,
+}
+
+/// Registers every job the bot knows how to run. Rows in the `jobs` table refer to these by name,
+/// so a registered name must never change once jobs using it have been stored.
+pub fn register_jobs(registry: &mut JobRegistry, ctx: &JobContext) {
+    registry.register(ReminderJob::new(ctx.discord.clone()));
+    registry.register(AnnouncementJob::new(ctx.discord.clone()));
+    registry.register(PlayerWatchJob::new(ctx.clone()));
+    registry.register(ClanWatchJob::new(ctx.clone()));
+
+    let sp_sample_budget = std::env::var("WOV_SP_SAMPLE_BUDGET").ok().and_then(|v| v.parse().ok()).unwrap_or(DEFAULT_SP_SAMPLE_BUDGET);
+    registry.register(SpSamplerJob::new(ctx.clone(), sp_sample_budget));
+}
+
+/// Creates the jobs the bot runs on its own, unless they already exist.
+async fn ensure_jobs(scheduler: &Scheduler) {
+    let result = scheduler.ensure_job::<PlayerWatchJob>(
+        Schedule::Interval(PLAYER_WATCH_INTERVAL),
+        &(),
+        JobOptions { overlap: OverlapPolicy::Skip, timeout: PLAYER_WATCH_INTERVAL.to_std().ok() },
+    ).await;
+    if let Err(e) = result {
+        error!("Failed to create the player watch job: {:?}", e);
+    }
+
+    let result = scheduler.ensure_job::<ClanWatchJob>(
+        Schedule::Interval(CLAN_WATCH_INTERVAL),
+        &(),
+        JobOptions { overlap: OverlapPolicy::Skip, timeout: CLAN_WATCH_INTERVAL.to_std().ok() },
+    ).await;
+    if let Err(e) = result {
+        error!("Failed to create the clan watch job: {:?}", e);
+    }
+
+    let result = scheduler.ensure_job::<SpSamplerJob>(
+        Schedule::Interval(SP_SAMPLE_INTERVAL),
+        &(),
+        JobOptions { overlap: OverlapPolicy::Skip, timeout: SP_SAMPLE_INTERVAL.to_std().ok() },
+    ).await;
+    if let Err(e) = result {
+        error!("Failed to create the SP sampler job: {:?}", e);
+    }
+}
+
+/// Loads the persisted jobs and runs the scheduler loop in the background until `token` is cancelled.
+pub fn spawn_scheduler(scheduler: Scheduler, token: CancellationToken) {
+    tokio::spawn(async move {
+        if let Err(e) = scheduler.load_from_store().await {
+            error!("Failed to load jobs from store: {:?}", e);
+        }
+        ensure_jobs(&scheduler).await;
+        scheduler.run(token).await;
+    });
+}
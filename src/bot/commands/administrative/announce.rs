@@ -0,0 +1,416 @@
+use std::str::FromStr;
+use chrono::Duration;
+use chrono_tz::Tz;
+use crate::utils::logger::error;
+use poise::{serenity_prelude as serenity, CreateReply};
+use crate::bot::core::constants::embed_limits::EMBED_FIELD_AMOUNT_LIMIT;
+use crate::bot::core::structs::{Context, CustomColor, Data, Error};
+use crate::bot::jobs::announcement::{AnnouncementArgs, AnnouncementJob};
+use crate::utils::language::get_language;
+use crate::utils::scheduler::{CronSchedule, Job, JobOptions, JobStatus, Schedule};
+use crate::utils::time::get_relative_timestamp;
+
+const MAX_ANNOUNCEMENTS_PER_GUILD: usize = EMBED_FIELD_AMOUNT_LIMIT;
+const MAX_NAME_LENGTH: usize = 32;
+/// Announcements can't be scheduled more often than this, so a typo in the expression can't flood a channel.
+const MIN_ANNOUNCEMENT_INTERVAL: Duration = Duration::hours(1);
+
+/// Day names in the order standard cron numbers them, Sunday is both 0 and 7.
+const DAYS_OF_WEEK: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A cron expression given in a command. Accepts the common five-field form and adds the seconds field the scheduler expects.
+#[derive(Debug, Clone)]
+pub struct CronExpression(String);
+
+impl FromStr for CronExpression {
+    type Err = cron::error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let expression = match fields.as_slice() {
+            [minute, hour, day, month, days_of_week] => {
+                format!("0 {} {} {} {} {}", minute, hour, day, month, standard_days_of_week(days_of_week))
+            }
+            _ => fields.join(" "),
+        };
+        cron::Schedule::from_str(&expression)?;
+        Ok(Self(expression))
+    }
+}
+
+impl CronExpression {
+    pub fn in_timezone(&self, timezone: Tz) -> anyhow::Result<CronSchedule> {
+        CronSchedule::new(&self.0, timezone)
+    }
+}
+
+/// The scheduler counts days of the week from 1 for Sunday, standard cron from 0 (or 7) for Sunday.
+/// Numbers in a five-field expression are replaced by the day names they stand for, which both read the same.
+fn standard_days_of_week(field: &str) -> String {
+    field.split(',')
+        .map(|item| expand_days_of_week(item).unwrap_or_else(|| item.to_string()))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// `5`, `1-5` or `*/2` as the list of day names they select, or `None` if `item` has no numbers to convert.
+fn expand_days_of_week(item: &str) -> Option<String> {
+    let (range, step) = match item.split_once('/') {
+        Some((range, step)) => (range, step.parse::<usize>().ok().filter(|step| *step > 0)?),
+        None if item == "*" => return None,
+        None => (item, 1),
+    };
+    let (first, last) = match range.split_once('-') {
+        Some((first, last)) => (first.parse().ok()?, last.parse().ok()?),
+        None if range == "*" => (0, 6),
+        None => {
+            let day = range.parse().ok()?;
+            (day, if step > 1 { 6 } else { day })
+        }
+    };
+    // Out of range values are left for the cron parser to reject
+    if first > last || last > 7 {
+        return None;
+    }
+
+    Some((first..=last).step_by(step).map(|day| DAYS_OF_WEEK[day % 7]).collect::<Vec<_>>().join(","))
+}
+
+async fn on_announce_error(error: poise::FrameworkError<'_, Data, Error>) {
+    crate::bot::handlers::record_command_error(&error).await;
+    match error {
+        poise::FrameworkError::ArgumentParse { input, ctx, .. } => {
+            let language = get_language(ctx).await;
+            let embed = serenity::CreateEmbed::default()
+                .title(t!("common.error", locale = language))
+                .description(t!("commands.admin.announce.invalid_argument", input = input.unwrap_or_default(), locale = language))
+                .color(serenity::Color::RED);
+            if let Err(e) = ctx.send(CreateReply::default().reply(true).embed(embed)).await {
+                error!("Failed to respond to the announce argument error: {:?}", e);
+            }
+        }
+        error => crate::bot::handlers::handle_error(error).await,
+    }
+}
+
+/// Post messages to this server.
+#[poise::command(
+    prefix_command, slash_command,
+    guild_only,
+    category = "config",
+    required_permissions = "MANAGE_GUILD",
+    default_member_permissions = "MANAGE_GUILD",
+    subcommands("schedule"),
+    subcommand_required = true,
+)]
+pub async fn announce(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Manage recurring announcements.
+#[poise::command(
+    prefix_command, slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("create", "list", "preview", "edit", "delete"),
+    subcommand_required = true,
+)]
+pub async fn schedule(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Schedule a recurring announcement. A leading `# Title` line becomes the embed title.
+#[poise::command(
+    prefix_command, slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    on_error = on_announce_error,
+)]
+pub async fn create(
+    ctx: Context<'_>,
+    name: String,
+    cron: CronExpression,
+    channel: serenity::GuildChannel,
+    role: Option<serenity::Role>,
+    timezone: Option<Tz>,
+    #[rest] message: String,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap();
+    let name = name.trim().to_string();
+
+    if name.chars().count() > MAX_NAME_LENGTH {
+        ctx.reply(t!("commands.admin.announce.name_too_long", limit = MAX_NAME_LENGTH, locale = language)).await?;
+        return Ok(());
+    }
+
+    let announcements = guild_announcements(ctx).await;
+    if find_by_name(&announcements, &name).is_some() {
+        ctx.reply(t!("commands.admin.announce.exists", name = name, locale = language)).await?;
+        return Ok(());
+    }
+    if announcements.len() >= MAX_ANNOUNCEMENTS_PER_GUILD {
+        ctx.reply(t!("commands.admin.announce.limit", limit = MAX_ANNOUNCEMENTS_PER_GUILD, locale = language)).await?;
+        return Ok(());
+    }
+
+    let Some(schedule) = check_schedule(ctx, &cron, timezone.unwrap_or(Tz::UTC), &language).await? else { return Ok(()) };
+    if !check_targets(ctx, Some(&channel), role.as_ref(), &language).await? {
+        return Ok(());
+    }
+
+    let (title, message) = split_title(&message);
+    let args = AnnouncementArgs {
+        guild_id: guild_id.get(),
+        channel_id: channel.id.get(),
+        name,
+        title,
+        message,
+        role_id: role.map(|role| role.id.get()),
+        created_by: ctx.author().id.get(),
+    };
+
+    match data.scheduler.add_job::<AnnouncementJob>(Schedule::Cron(schedule), &args, JobOptions::default()).await {
+        Ok(def) => {
+            let next_run = def.schedule.next_run(data.clock.now());
+            ctx.reply(t!(
+                "commands.admin.announce.create.success",
+                name = args.name,
+                channel = format!("<#{}>", args.channel_id),
+                next_run = next_run.map(|at| get_relative_timestamp(&at.timestamp())).unwrap_or_default(),
+                locale = language
+            )).await?;
+        }
+        Err(e) => {
+            error!("Failed to schedule announcement `{}` in guild {}: {:?}", args.name, guild_id, e);
+            ctx.reply(t!("commands.admin.announce.invalid_message", error = e, locale = language)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// List the recurring announcements of this server.
+#[poise::command(
+    prefix_command, slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let announcements = guild_announcements(ctx).await;
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title(t!("commands.admin.announce.list.title", locale = language))
+        .color(CustomColor::CYAN);
+
+    if announcements.is_empty() {
+        embed = embed.description(t!("commands.admin.announce.list.empty", locale = language));
+    }
+
+    for (job, args) in announcements.iter().take(EMBED_FIELD_AMOUNT_LIMIT) {
+        let (expression, timezone) = match &job.definition.schedule {
+            Schedule::Cron(cron) => (cron.expression.clone(), cron.timezone.name().to_string()),
+            _ => continue,
+        };
+        let state = if job.definition.enabled { "" } else { " ⏸" };
+        embed = embed.field(
+            format!("{}{}", args.name, state),
+            t!(
+                "commands.admin.announce.list.value",
+                channel = format!("<#{}>", args.channel_id),
+                expression = expression,
+                timezone = timezone,
+                next_run = get_relative_timestamp(&job.next_run.timestamp()),
+                role = args.role_mention().unwrap_or_else(|| "—".to_string()),
+                locale = language
+            ),
+            false,
+        );
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Show how an announcement will look, without notifying anyone.
+#[poise::command(
+    prefix_command, slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn preview(
+    ctx: Context<'_>,
+    #[rest] name: String,
+) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let announcements = guild_announcements(ctx).await;
+    let Some((_, args)) = find_by_name(&announcements, &name) else {
+        ctx.reply(t!("commands.admin.announce.not_found", name = name.trim(), locale = language)).await?;
+        return Ok(());
+    };
+
+    let mut reply = CreateReply::default()
+        .embed(args.embed())
+        .ephemeral(true)
+        .allowed_mentions(serenity::CreateAllowedMentions::new());
+    if let Some(mention) = args.role_mention() {
+        reply = reply.content(mention);
+    }
+
+    ctx.send(reply).await?;
+    Ok(())
+}
+
+/// Change an announcement. Only the given options are changed.
+#[allow(clippy::too_many_arguments)]
+#[poise::command(
+    prefix_command, slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    on_error = on_announce_error,
+)]
+pub async fn edit(
+    ctx: Context<'_>,
+    name: String,
+    cron: Option<CronExpression>,
+    channel: Option<serenity::GuildChannel>,
+    role: Option<serenity::Role>,
+    timezone: Option<Tz>,
+    #[flag] no_role: bool,
+    #[rest] message: Option<String>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let announcements = guild_announcements(ctx).await;
+    let Some((job, args)) = find_by_name(&announcements, &name) else {
+        ctx.reply(t!("commands.admin.announce.not_found", name = name.trim(), locale = language)).await?;
+        return Ok(());
+    };
+    let Schedule::Cron(current) = &job.definition.schedule else { return Ok(()) };
+
+    let schedule = match (cron, timezone) {
+        (None, None) => current.clone(),
+        (cron, timezone) => {
+            let cron = cron.unwrap_or_else(|| CronExpression(current.expression.clone()));
+            match check_schedule(ctx, &cron, timezone.unwrap_or(current.timezone), &language).await? {
+                Some(schedule) => schedule,
+                None => return Ok(()),
+            }
+        }
+    };
+    if !check_targets(ctx, channel.as_ref(), role.as_ref(), &language).await? {
+        return Ok(());
+    }
+
+    let mut args = args.clone();
+    if let Some(channel) = channel {
+        args.channel_id = channel.id.get();
+    }
+    if let Some(role) = role {
+        args.role_id = Some(role.id.get());
+    } else if no_role {
+        args.role_id = None;
+    }
+    if let Some(message) = message {
+        (args.title, args.message) = split_title(&message);
+    }
+
+    match data.scheduler.update_job::<AnnouncementJob>(job.definition.id, Schedule::Cron(schedule), &args).await {
+        Ok(job) => {
+            ctx.reply(t!(
+                "commands.admin.announce.edit.success",
+                name = args.name,
+                next_run = get_relative_timestamp(&job.next_run.timestamp()),
+                locale = language
+            )).await?;
+        }
+        Err(e) => {
+            error!("Failed to update announcement `{}` in guild {}: {:?}", args.name, args.guild_id, e);
+            ctx.reply(t!("commands.admin.announce.invalid_message", error = e, locale = language)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete an announcement.
+#[poise::command(
+    prefix_command, slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn delete(
+    ctx: Context<'_>,
+    #[rest] name: String,
+) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let announcements = guild_announcements(ctx).await;
+    let Some((job, args)) = find_by_name(&announcements, &name) else {
+        ctx.reply(t!("commands.admin.announce.not_found", name = name.trim(), locale = language)).await?;
+        return Ok(());
+    };
+
+    match ctx.data().scheduler.remove_job(job.definition.id).await {
+        Ok(_) => ctx.reply(t!("commands.admin.announce.delete.success", name = args.name, locale = language)).await?,
+        Err(e) => {
+            error!("Failed to delete announcement {}: {:?}", job.definition.id, e);
+            ctx.reply(t!("commands.admin.announce.delete.fail", locale = language)).await?
+        }
+    };
+    Ok(())
+}
+
+/// Announcements of the guild the command was invoked in, soonest first.
+async fn guild_announcements(ctx: Context<'_>) -> Vec<(JobStatus, AnnouncementArgs)> {
+    let Some(guild_id) = ctx.guild_id() else { return Vec::new() };
+    ctx.data().scheduler.list_jobs().await.into_iter()
+        .filter(|job| job.definition.name == AnnouncementJob::NAME)
+        .filter_map(|job| {
+            let args: AnnouncementArgs = serde_json::from_value(job.definition.args.clone()).ok()?;
+            (args.guild_id == guild_id.get()).then_some((job, args))
+        })
+        .collect()
+}
+
+fn find_by_name<'a>(announcements: &'a [(JobStatus, AnnouncementArgs)], name: &str) -> Option<&'a (JobStatus, AnnouncementArgs)> {
+    announcements.iter().find(|(_, args)| args.name.eq_ignore_ascii_case(name.trim()))
+}
+
+/// Builds the schedule and replies with an error if it fires more often than `MIN_ANNOUNCEMENT_INTERVAL`.
+async fn check_schedule(ctx: Context<'_>, cron: &CronExpression, timezone: Tz, language: &str) -> Result<Option<CronSchedule>, Error> {
+    let schedule = cron.in_timezone(timezone)?;
+
+    let mut previous = schedule.next_run(ctx.data().clock.now());
+    for _ in 0..10 {
+        let (Some(last), Some(next)) = (previous, previous.and_then(|at| schedule.next_run(at))) else { break };
+        if next - last < MIN_ANNOUNCEMENT_INTERVAL {
+            ctx.reply(t!("commands.admin.announce.too_frequent", minutes = MIN_ANNOUNCEMENT_INTERVAL.num_minutes(), locale = language)).await?;
+            return Ok(None);
+        }
+        previous = Some(next);
+    }
+
+    Ok(Some(schedule))
+}
+
+/// Makes sure the channel and role belong to the guild the command was invoked in.
+async fn check_targets(ctx: Context<'_>, channel: Option<&serenity::GuildChannel>, role: Option<&serenity::Role>, language: &str) -> Result<bool, Error> {
+    let guild_id = ctx.guild_id();
+    if channel.is_some_and(|channel| Some(channel.guild_id) != guild_id || !channel.is_text_based()) {
+        ctx.reply(t!("commands.admin.announce.invalid_channel", locale = language)).await?;
+        return Ok(false);
+    }
+    if role.is_some_and(|role| Some(role.guild_id) != guild_id) {
+        ctx.reply(t!("commands.admin.announce.invalid_role", locale = language)).await?;
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Splits off a leading `# Title` line. Slash command options can't contain line breaks, so a literal `\n` counts as one.
+fn split_title(message: &str) -> (Option<String>, String) {
+    let message = message.replace("\\n", "\n");
+    match message.trim().strip_prefix("# ") {
+        Some(rest) => {
+            let (title, body) = rest.split_once('\n').unwrap_or((rest, ""));
+            (Some(title.trim().to_string()), body.trim().to_string())
+        }
+        None => (None, message.trim().to_string()),
+    }
+}
@@ -0,0 +1,131 @@
+pub mod announce;
+
+use crate::bot::core::structs::{Context, Error, Data, CustomColor};
+use crate::db::{guilds, prefixes};
+use crate::utils::language::{autocomplete_language, available_languages, find_language, get_guild_language, get_language, language_of, set_language};
+use crate::utils::logger::error;
+use poise::{serenity_prelude as serenity, CreateReply};
+use crate::bot::core::constants::DEFAULT_PREFIX;
+
+pub async fn on_missing_prefix_error(error: poise::FrameworkError<'_, Data, Error>) {
+    crate::bot::handlers::record_command_error(&error).await;
+    match error {
+        poise::FrameworkError::ArgumentParse { input, ctx, .. } => {
+            let language = get_language(ctx).await;
+            
+            if input == None {
+                if let Some(guild_id) = ctx.guild_id() {
+                    let prefix = ctx.data().prefix_cache.lock().await.get(&guild_id.to_string()).cloned().unwrap_or(DEFAULT_PREFIX.to_string());
+                    let embed = serenity::CreateEmbed::default()
+                        .title(t!("commands.admin.prefix.no_input_embed.title", locale = language))
+                        .description(t!("commands.admin.prefix.no_input_embed.description", prefix = prefix, locale = language))
+                        .color(CustomColor::CYAN)
+                        .field("\u{200B}", t!("commands.admin.prefix.no_input_embed.field", locale = language), false);
+
+                    ctx.send(CreateReply::default().embed(embed)).await.unwrap();
+                }
+            }
+        }
+        error => crate::bot::handlers::handle_error(error).await,
+}}
+
+
+/// Set the prefix for the bot in the current server.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    category = "config",
+    required_permissions = "MANAGE_GUILD",
+    on_error = on_missing_prefix_error,
+)]
+pub async fn prefix(
+    ctx: Context<'_>, 
+    #[rename = "new_prefix"] new_prefix: String
+) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+
+    let mut prefix_cache = ctx.data().prefix_cache.lock().await;
+    
+    if new_prefix.len() > 5 {
+        ctx.reply(format!("{}", t!("commands.admin.prefix.too_long", locale = language))).await?;
+        return Ok(());
+    }
+
+    let result = prefixes::set_prefix(&ctx.data().db_pool, &guild_id, &new_prefix).await;
+
+    match result {
+        Ok(_) => {
+            ctx.reply(format!("{}", t!("commands.admin.prefix.success", prefix = new_prefix, locale = language))).await?;
+            prefix_cache.put(guild_id, new_prefix);
+        }
+        Err(err) => {
+            ctx.reply(format!("{}", t!("commands.admin.prefix.fail", locale = language))).await?;
+            error!("Failed to set prefix `{}` for guild {}: {}", new_prefix, guild_id, err);
+        }
+    }
+    Ok(())
+}
+
+/// Set the language the bot replies in on the current server.
+///
+/// Members who picked their own language keep it. Without a language the server's members get replies in their Discord language.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    category = "config",
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn language(
+    ctx: Context<'_>,
+    #[description = "The server's language, `reset` to remove it"]
+    #[rename = "language"] #[autocomplete = "autocomplete_language"]
+    #[rest]
+    new_language: Option<String>
+) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+
+    let Some(new_language) = new_language.map(|new_language| new_language.trim().to_string()).filter(|new_language| !new_language.is_empty()) else {
+        match get_guild_language(ctx.data(), &guild_id).await {
+            Some(code) => ctx.reply(t!("commands.admin.language.current", language = language_of(&code).display(), locale = language)).await?,
+            None => ctx.reply(t!("commands.admin.language.unset", locale = language)).await?,
+        };
+        return Ok(());
+    };
+
+    if new_language.eq_ignore_ascii_case("reset") {
+        match guilds::clear_language_code(&ctx.data().db_pool, &guild_id).await {
+            Ok(_) => {
+                set_language(ctx.data(), &guild_id, None).await;
+                // Replied in the language the server falls back to now
+                let language = get_language(ctx).await;
+                ctx.reply(t!("commands.admin.language.reset.success", locale = language)).await?;
+            }
+            Err(err) => {
+                ctx.reply(t!("commands.admin.language.reset.fail", locale = language)).await?;
+                error!("Failed to reset the language of guild {}: {}", guild_id, err);
+            }
+        }
+        return Ok(());
+    }
+
+    let Some(new_language) = find_language(&new_language) else {
+        let languages = available_languages().iter().map(|language| format!("`{}`", language.name)).collect::<Vec<_>>().join(", ");
+        ctx.reply(t!("commands.directive.preferences.change_language.fail", language_fail = new_language, languages = languages, locale = language)).await?;
+        return Ok(());
+    };
+
+    match guilds::set_language_code(&ctx.data().db_pool, &guild_id, new_language.code).await {
+        Ok(_) => {
+            set_language(ctx.data(), &guild_id, Some(new_language.code)).await;
+            let language = get_language(ctx).await;
+            ctx.reply(t!("commands.admin.language.success", language = new_language.display(), locale = language)).await?;
+        }
+        Err(err) => {
+            ctx.reply(t!("commands.admin.language.fail", locale = language)).await?;
+            error!("Failed to set language `{}` for guild {}: {}", new_language.code, guild_id, err);
+        }
+    }
+    Ok(())
+}
@@ -1,27 +1,18 @@
-use std::collections::HashMap;
-
 use poise::serenity_prelude as serenity;
 use crate::bot::core::structs::{Context, Error, CustomColor};
-use crate::utils::language::{get_language, set_language};
-use crate::db::users::{add_user, hit_user, set_language_code};
-use logfather::error;
+use crate::utils::language::{autocomplete_language, available_languages, find_language, get_language, get_user_language, language_of, set_language};
+use crate::db::users::set_language_code;
+use crate::utils::logger::error;
 use crate::bot::core::constants::DEFAULT_PREFIX;
 use crate::bot::determine_prefix;
 use crate::db::prefixes;
 
 async fn show_common(ctx: Context<'_>) -> Result<(), Error> {
-    if !hit_user(&ctx.data().db_pool, &ctx.author().id.to_string()).await? { 
-        match add_user(&ctx.data().db_pool, &ctx.author().id.to_string()).await {
-            Ok(_) => {},
-            Err(e) => {
-                error!("Failed to add user in preferences with ID {}: {:?}", &ctx.author().id, e);
-                ctx.reply("Database error. Please try again later").await?;
-                return Ok(());
-            }
-        }
-    }
-
-    let language = get_language(ctx.data(), &ctx.author().id.to_string()).await;
+    let language = get_language(ctx).await;
+    let language_field = match get_user_language(ctx.data(), &ctx.author().id.to_string()).await {
+        Some(_) => language_of(&language).display(),
+        None => t!("commands.directive.preferences.fields.language.inherited", language = language_of(&language).display(), locale = language).to_string(),
+    };
     let prefix = determine_prefix(ctx.into()).await?;
 
     let embed = serenity::CreateEmbed::default()
@@ -30,7 +21,7 @@ async fn show_common(ctx: Context<'_>) -> Result<(), Error> {
         .color(CustomColor::CYAN)
         .field(
             t!("commands.directive.preferences.fields.language.name", locale = language), 
-            t!("commands.directive.preferences.fields.language.value", locale = language), 
+            language_field,
             false
         )
         .field(
@@ -83,8 +74,6 @@ async fn show_common(ctx: Context<'_>) -> Result<(), Error> {
 /// Configure the bot to your liking.
 #[poise::command(
     prefix_command, slash_command,
-    name_localized("uk", "налаштування"),
-    description_localized("uk", "Налаштуйте бота на свій смак."),
     category = "config",
     subcommands("show", "language", "prefix"),
     subcommand_required = false,
@@ -97,58 +86,42 @@ pub async fn preferences(ctx: Context<'_>) -> Result<(), Error> {
 /// Check your preferences.
 #[poise::command(
     slash_command, prefix_command,
-    name_localized("uk", "показати"),
-    description_localized("uk", "Перевірте ваші налаштування")
 )]
 pub async fn show(ctx: Context<'_>) -> Result<(), Error> {
     show_common(ctx).await
 }
 
-/// Set your locale. Currently supported languages are English and Ukrainian.
+/// Set the language the bot talks to you in.
 #[poise::command(
     slash_command, prefix_command,
     rename = "locale",
-    name_localized("uk", "локалізація"),
-    description_localized("uk", "Змініть мову бота. Підтримуються англійська та українська."),
     )]
 pub async fn language(
     ctx: Context<'_>, 
-    #[rename = "language"] #[name_localized("uk", "мова")]
+    #[rename = "language"] #[autocomplete = "autocomplete_language"]
+    #[rest]
     new_language: String
 ) -> Result<(), Error> {
-    let new_language = new_language.to_lowercase();
-    let language = get_language(ctx.data(), &ctx.author().id.to_string()).await;
-
-    let languages = vec!["en", "uk", "ua", "ukrainian", "english"];
-
-    let language_code_map: HashMap<&str, &str> = vec![
-        ("en", "English"),
-        ("uk", "Ukrainian"),
-    ].into_iter().collect();
-
-    if languages.contains(&new_language.as_str()) {
-        let new_language = match new_language.as_str() {
-            "ua" => "uk",
-            "ukrainian" => "uk",
-            "english" => "en",
-            _ => new_language.as_str()
-        };
-        let language_name = *language_code_map.get(new_language).unwrap_or(&"Unknown");
-
-        match set_language_code(&ctx.data().db_pool, &ctx.author().id.to_string(), new_language).await {
-            Ok(_) => {set_language(ctx.data(), &ctx.author().id.to_string(), new_language).await;},
-            Err(e) => {
-                error!("Failed to set language for user with ID {}: {:?}", &ctx.author().id, e);
-                ctx.reply("Database error. Please try again later").await?;
-                return Ok(());
-            }
+    let language = get_language(ctx).await;
+
+    let Some(new_language) = find_language(&new_language) else {
+        let languages = available_languages().iter().map(|language| format!("`{}`", language.name)).collect::<Vec<_>>().join(", ");
+        ctx.reply(t!("commands.directive.preferences.change_language.fail", locale = language, language_fail = new_language, languages = languages)).await?;
+        return Ok(());
+    };
+
+    match set_language_code(&ctx.data().db_pool, &ctx.author().id.to_string(), new_language.code).await {
+        Ok(_) => {set_language(ctx.data(), &ctx.author().id.to_string(), Some(new_language.code)).await;},
+        Err(e) => {
+            error!("Failed to set language for user with ID {}: {:?}", &ctx.author().id, e);
+            ctx.reply("Database error. Please try again later").await?;
+            return Ok(());
         }
-
-        ctx.reply(format!("{}", t!("commands.directive.preferences.change_language.success", locale = language, language_success = language_name))).await?;
-    } else {
-        ctx.reply(format!("{}", t!("commands.directive.preferences.change_language.fail", locale = language, language_fail = new_language))).await?;
     }
 
+    // Confirmed in the new language, so the user sees right away what they picked
+    ctx.reply(t!("commands.directive.preferences.change_language.success", locale = new_language.code, language_success = new_language.name)).await?;
+
     Ok(())
 }
 
@@ -157,16 +130,13 @@ pub async fn language(
 #[poise::command(
     slash_command, prefix_command,
     rename = "prefix",
-    name_localized("uk", "префікс"),
-    description_localized("uk", "Поставте власний префікс для бота."),
 )]
 pub async fn prefix(
     ctx: Context<'_>,
-    #[rename = "new_prefix"] #[name_localized("uk", "новий_префікс")]
-    new_prefix: Option<String>
+    #[rename = "new_prefix"] new_prefix: Option<String>
 ) -> Result<(), Error> {
     let user_id = ctx.author().id.to_string();
-    let language = get_language(ctx.data(), &user_id).await;
+    let language = get_language(ctx).await;
     
     let mut prefix_cache = ctx.data().prefix_cache.lock().await;
     
@@ -1,20 +1,19 @@
 use poise::{serenity_prelude as serenity, CreateReply};
 use crate::bot::core::structs::{Context, CustomColor, Data, Error};
-use crate::utils::language::get_language;
+use crate::utils::humanize;
+use crate::utils::language::{dynamic_translation, get_language};
 
 
 /// Help command to display available commands and their descriptions. I love recursion.
 #[poise::command(
     slash_command, prefix_command,
-    name_localized("uk", "довідка"),
-    description_localized("uk", "Показати доступні команди та їхні описи. Я люблю рекурсію."),
     category = "info",
 )]
 pub async fn help(
     ctx: Context<'_>, 
-    #[name_localized("uk", "команда")] command: Option<String>
+    command: Option<String>
 ) -> Result<(), Error> {
-    let language = get_language(ctx.data(), &ctx.author().id.to_string()).await;
+    let language = get_language(ctx).await;
     
     if let Some(_) = command {
         // TODO: Add command-specific help text
@@ -28,13 +27,13 @@ pub async fn help(
     
     let mut categories = std::collections::HashMap::new();
     let mut select_menu_options = Vec::new();
-    for command in &ctx.framework().options().commands {
+    for command in ctx.framework().options().commands.iter().filter(|command| !command.hide_in_help) {
         let category = command.category.clone().unwrap_or_else(|| "uncategorized".to_string());
         categories.entry(category).or_insert_with(Vec::new).push(command);
     }
     
     for (category, _) in &categories {
-        let category_translated = t!(format!("help.{}", category), locale = language);
+        let category_translated = dynamic_translation(&format!("help.{}", category), &language, &humanize(category));
         select_menu_options.push(serenity::CreateSelectMenuOption::new(
             category_translated,
             category.clone()
@@ -69,7 +68,7 @@ pub async fn help(
         
         if let Some(commands) = categories.get(selected_category) {
             embed = serenity::CreateEmbed::default()
-                .title(t!(format!("help.{}", selected_category), locale = language))
+                .title(dynamic_translation(&format!("help.{}", selected_category), &language, &humanize(selected_category)))
                 .description(t!("commands.info.help.category.description", locale = language))
                 .color(CustomColor::CYAN);
             
@@ -7,12 +7,10 @@ use crate::utils::language::get_language;
 /// Pong. Check if the bot is alive.
 #[poise::command(
     slash_command, prefix_command,
-    name_localized("uk", "пінг"),
-    description_localized("uk", "Понг. Перевір чи бот живий."),
     category = "info",
 )]
 pub async fn ping(ctx: Context<'_>) -> Result<(), Error> {
-    let language = get_language(ctx.data(), &ctx.author().id.to_string()).await;
+    let language = get_language(ctx).await;
     let runners = &ctx.framework().shard_manager.runners.lock().await;
     let runner_info = runners.get(&serenity::ShardId(0)).unwrap();
 
@@ -7,16 +7,14 @@ use crate::utils::{time, language::get_language};
 #[poise::command(
     slash_command, prefix_command,
     rename = "user-info",
-    name_localized("uk", "інфо-користувача"),
-    description_localized("uk", "Отримай інформацію про користувача"),
     category = "info",
 )]
 pub async fn user_info(
     ctx: Context<'_>, 
-    #[name_localized("uk", "користувач")] user: Option<serenity::User>
+    user: Option<serenity::User>
 ) -> Result<(), Error> {
     let user_info = user.unwrap_or(ctx.author().clone());
-    let language = get_language(ctx.data(), &ctx.author().id.to_string()).await;
+    let language = get_language(ctx).await;
 
     let embed = match ctx.guild() {
         Some(guild) =>  {
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use crate::bot::core::constants::DEFAULT_LANGUAGE;
+use crate::bot::core::structs::{Data, Error};
+use crate::utils::language::{available_languages, translation};
+
+type Command = poise::Command<Data, Error>;
+
+/// Every locale Discord knows, see https://discord.com/developers/docs/reference#locales
+const DISCORD_LOCALES: &[&str] = &[
+    "id", "da", "de", "en-GB", "en-US", "es-ES", "es-419", "fr", "hr", "it", "lt", "hu", "nl", "no", "pl", "pt-BR",
+    "ro", "fi", "sv-SE", "vi", "tr", "cs", "el", "bg", "ru", "uk", "hi", "th", "zh-CN", "ja", "zh-TW", "ko",
+];
+/// Where the translations of all commands are, `wolvesville clan search` is under `slash_commands.wolvesville.clan.search`.
+/// The keys are built while walking the tree, `qualified_name` is only set once the framework is built.
+const ROOT_KEY: &str = "slash_commands";
+const NAME_LIMIT: usize = 32;
+const DESCRIPTION_LIMIT: usize = 100;
+
+/// The Discord locales one of our languages covers, `en` covers both `en-GB` and `en-US`.
+pub fn discord_locales(code: &str) -> Vec<&'static str> {
+    let code = code.to_lowercase();
+    DISCORD_LOCALES.iter().copied()
+        .filter(|locale| {
+            let locale = locale.to_lowercase();
+            locale == code || locale.strip_prefix(&code).is_some_and(|region| region.starts_with('-'))
+        })
+        .collect()
+}
+
+/// Applies the names and descriptions under `slash_commands` in the locale files. The default language replaces
+/// the descriptions from the doc comments, every other language becomes a localization of the Discord locales it covers.
+pub fn localize_commands(commands: &mut [Command]) {
+    localize_commands_under(ROOT_KEY, commands);
+}
+
+fn localize_commands_under(parent_key: &str, commands: &mut [Command]) {
+    for command in commands.iter_mut().filter(|command| command.slash_action.is_some()) {
+        let key = format!("{}.{}", parent_key, command.name);
+        localize(&key, &mut command.description, &mut command.name_localizations, &mut command.description_localizations);
+
+        for parameter in &mut command.parameters {
+            let key = format!("{}.parameters.{}", key, parameter.name);
+            localize(&key, &mut parameter.description, &mut parameter.name_localizations, &mut parameter.description_localizations);
+        }
+
+        localize_commands_under(&key, &mut command.subcommands);
+    }
+}
+
+fn localize(key: &str, description: &mut Option<String>, name_localizations: &mut HashMap<String, String>, description_localizations: &mut HashMap<String, String>) {
+    for language in available_languages() {
+        let translated_name = translation(language.code, &format!("{}.name", key));
+        let translated_description = translation(language.code, &format!("{}.description", key));
+
+        if language.code == DEFAULT_LANGUAGE {
+            if translated_description.is_some() {
+                *description = translated_description;
+            }
+            continue;
+        }
+
+        for locale in discord_locales(language.code) {
+            if let Some(name) = &translated_name {
+                name_localizations.insert(locale.to_string(), name.clone());
+            }
+            if let Some(description) = &translated_description {
+                description_localizations.insert(locale.to_string(), description.clone());
+            }
+        }
+    }
+}
+
+/// Everything wrong with the translations of the slash commands: missing names and descriptions, default language
+/// names that differ from the ones in the code, and names or descriptions Discord would reject.
+/// Parameters only need a description when they have one in the code or in any locale.
+pub fn translation_problems(commands: &[Command]) -> Vec<String> {
+    let mut problems = Vec::new();
+    collect_translation_problems(ROOT_KEY, commands, &mut problems);
+    problems
+}
+
+fn collect_translation_problems(parent_key: &str, commands: &[Command], problems: &mut Vec<String>) {
+    for command in commands.iter().filter(|command| command.slash_action.is_some()) {
+        let key = format!("{}.{}", parent_key, command.name);
+        check_translations(&key, &command.name, true, problems);
+        for parameter in &command.parameters {
+            check_translations(&format!("{}.parameters.{}", key, parameter.name), &parameter.name, parameter.description.is_some(), problems);
+        }
+        collect_translation_problems(&key, &command.subcommands, problems);
+    }
+}
+
+fn check_translations(key: &str, name: &str, description_required: bool, problems: &mut Vec<String>) {
+    let name_key = format!("{}.name", key);
+    let description_key = format!("{}.description", key);
+    let description_required = description_required
+        || available_languages().iter().any(|language| translation(language.code, &description_key).is_some());
+
+    for language in available_languages() {
+        match translation(language.code, &name_key) {
+            None => problems.push(format!("`{}` is missing in `{}`", name_key, language.code)),
+            Some(translated) if language.code == DEFAULT_LANGUAGE && translated != name => {
+                problems.push(format!("`{}` is `{}` in `{}`, but the command is named `{}`", name_key, translated, language.code, name));
+            }
+            Some(translated) if !is_valid_name(&translated) => {
+                problems.push(format!("`{}` in `{}` is not a valid command name: `{}`", name_key, language.code, translated));
+            }
+            Some(_) => {}
+        }
+
+        match translation(language.code, &description_key) {
+            None if description_required => problems.push(format!("`{}` is missing in `{}`", description_key, language.code)),
+            Some(translated) if translated.is_empty() || translated.chars().count() > DESCRIPTION_LIMIT => {
+                problems.push(format!("`{}` in `{}` must be 1 to {} characters long", description_key, language.code, DESCRIPTION_LIMIT));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Discord only takes lowercase names of up to 32 letters, digits, dashes and underscores.
+fn is_valid_name(name: &str) -> bool {
+    (1..=NAME_LIMIT).contains(&name.chars().count())
+        && name.chars().all(|c| c == '-' || c == '_' || (c.is_alphanumeric() && !c.is_uppercase()))
+}
@@ -2,3 +2,9 @@ pub mod directive;
 pub mod informative;
 pub mod administrative;
 pub mod wov;
+pub mod owner;
+pub mod utility;
+pub mod localization;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,214 @@
+use poise::{serenity_prelude as serenity, CreateReply};
+use uuid::Uuid;
+use crate::bot::core::constants::embed_limits::{EMBED_FIELD_AMOUNT_LIMIT, EMBED_TOTAL_CHARACTERS_LIMIT};
+use crate::bot::core::structs::{Context, CustomColor, Error};
+use crate::utils::{language::get_language, logger, truncate};
+use crate::utils::logger::info;
+use crate::utils::scheduler::{JobOutcome, JobStatus, Schedule};
+use crate::utils::format::format_duration;
+use crate::utils::time::get_relative_timestamp;
+
+
+/// Inspect and manage scheduled jobs.
+#[poise::command(
+    prefix_command, slash_command,
+    owners_only, hide_in_help,
+    category = "owner",
+    subcommands("list", "pause", "resume", "trigger", "delete"),
+    subcommand_required = true,
+)]
+pub async fn jobs(_ctx: Context<'_>) -> Result<(), Error> {
+    Ok(())
+}
+
+/// List every scheduled job with its schedule, next run and last outcome.
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let jobs = ctx.data().scheduler.list_jobs().await;
+
+    let title = t!("commands.owner.jobs.list.title", locale = language);
+    let footer = if ctx.data().scheduler.is_leader() {
+        t!("commands.owner.jobs.list.leader", locale = language)
+    } else {
+        t!("commands.owner.jobs.list.standby", locale = language)
+    };
+    // Room for the note on hidden jobs, which is never longer than with every job shown
+    let note_length = t!("commands.owner.jobs.list.truncated", shown = jobs.len(), total = jobs.len(), locale = language).chars().count();
+    let mut remaining = EMBED_TOTAL_CHARACTERS_LIMIT.saturating_sub(title.chars().count() + footer.chars().count() + note_length);
+
+    let mut fields = Vec::new();
+    for job in jobs.iter().take(EMBED_FIELD_AMOUNT_LIMIT) {
+        let (name, value) = format_job_field(job, &language);
+        let length = name.chars().count() + value.chars().count();
+        if length > remaining {
+            break;
+        }
+        remaining -= length;
+        fields.push((name, value, false));
+    }
+
+    let shown = fields.len();
+    let mut embed = serenity::CreateEmbed::default()
+        .title(title)
+        .color(CustomColor::CYAN)
+        .footer(serenity::CreateEmbedFooter::new(footer))
+        .fields(fields);
+
+    if jobs.is_empty() {
+        embed = embed.description(t!("commands.owner.jobs.list.empty", locale = language));
+    } else if shown < jobs.len() {
+        embed = embed.description(t!("commands.owner.jobs.list.truncated", shown = shown, total = jobs.len(), locale = language));
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Pause a job. It stays paused across restarts until resumed.
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn pause(ctx: Context<'_>, id: String) -> Result<(), Error> {
+    set_enabled(ctx, id, false).await
+}
+
+/// Resume a paused job.
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn resume(ctx: Context<'_>, id: String) -> Result<(), Error> {
+    set_enabled(ctx, id, true).await
+}
+
+/// Run a job right away without changing its schedule.
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn trigger(ctx: Context<'_>, id: String) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let Some(id) = parse_job_id(ctx, &id, &language).await? else { return Ok(()) };
+
+    match ctx.data().scheduler.trigger_job(id).await {
+        Ok(_) => ctx.reply(t!("commands.owner.jobs.trigger.success", id = id, locale = language)).await?,
+        Err(e) => ctx.reply(t!("commands.owner.jobs.fail", id = id, error = e, locale = language)).await?,
+    };
+    Ok(())
+}
+
+/// Delete a job from the schedule and the database.
+#[poise::command(prefix_command, slash_command, owners_only)]
+pub async fn delete(ctx: Context<'_>, id: String) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let Some(id) = parse_job_id(ctx, &id, &language).await? else { return Ok(()) };
+
+    match ctx.data().scheduler.remove_job(id).await {
+        Ok(_) => ctx.reply(t!("commands.owner.jobs.delete.success", id = id, locale = language)).await?,
+        Err(e) => ctx.reply(t!("commands.owner.jobs.fail", id = id, error = e, locale = language)).await?,
+    };
+    Ok(())
+}
+
+/// Show or change the log levels until the next restart.
+///
+/// Takes directives like `warn,mif::bot=debug`, `reset` goes back to `LOG_LEVEL`.
+#[poise::command(
+    prefix_command, slash_command,
+    owners_only, hide_in_help,
+    category = "owner",
+    rename = "loglevel",
+)]
+pub async fn log_level(
+    ctx: Context<'_>,
+    #[description = "Directives like `warn,mif::bot=debug`, or `reset`"]
+    #[rest]
+    filter: Option<String>,
+) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+
+    let Some(filter) = filter.map(|filter| filter.trim().to_string()).filter(|filter| !filter.is_empty()) else {
+        ctx.reply(t!("commands.owner.log_level.current", filter = logger::current_filter(), locale = language)).await?;
+        return Ok(());
+    };
+
+    let filter = if filter.eq_ignore_ascii_case("reset") {
+        None
+    } else {
+        match filter.parse() {
+            Ok(filter) => Some(filter),
+            Err(e) => {
+                ctx.reply(t!("commands.owner.log_level.invalid", error = e, locale = language)).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    let filter = logger::set_filter(filter);
+    info!("Log levels changed to {} by {}", filter, ctx.author().id);
+    ctx.reply(t!("commands.owner.log_level.updated", filter = filter, locale = language)).await?;
+    Ok(())
+}
+
+async fn set_enabled(ctx: Context<'_>, id: String, enabled: bool) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let Some(id) = parse_job_id(ctx, &id, &language).await? else { return Ok(()) };
+
+    match ctx.data().scheduler.set_job_enabled(id, enabled).await {
+        Ok(job) => {
+            let (name, value) = format_job_field(&job, &language);
+            let embed = serenity::CreateEmbed::default()
+                .title(if enabled { t!("commands.owner.jobs.resume.success", locale = language) } else { t!("commands.owner.jobs.pause.success", locale = language) })
+                .color(CustomColor::CYAN)
+                .field(name, value, false);
+            ctx.send(CreateReply::default().embed(embed)).await?;
+        }
+        Err(e) => {
+            ctx.reply(t!("commands.owner.jobs.fail", id = id, error = e, locale = language)).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn parse_job_id(ctx: Context<'_>, id: &str, language: &str) -> Result<Option<Uuid>, Error> {
+    match Uuid::parse_str(id.trim()) {
+        Ok(id) => Ok(Some(id)),
+        Err(_) => {
+            ctx.reply(t!("commands.owner.jobs.invalid_id", id = id, locale = language)).await?;
+            Ok(None)
+        }
+    }
+}
+
+fn format_job_field(job: &JobStatus, language: &str) -> (String, String) {
+    let definition = &job.definition;
+    let schedule = match &definition.schedule {
+        Schedule::Once(at) => t!("commands.owner.jobs.schedule.once", at = get_relative_timestamp(&at.timestamp()), locale = language),
+        Schedule::Interval(every) => t!("commands.owner.jobs.schedule.interval", every = format_duration(every, language), locale = language),
+        Schedule::Cron(cron) => t!("commands.owner.jobs.schedule.cron", expression = cron.expression, timezone = cron.timezone.name(), locale = language),
+    };
+
+    let state = if job.running {
+        t!("commands.owner.jobs.state.running", locale = language)
+    } else if definition.enabled {
+        t!("commands.owner.jobs.state.enabled", locale = language)
+    } else {
+        t!("commands.owner.jobs.state.paused", locale = language)
+    };
+
+    let last_outcome = match &job.last_outcome {
+        None => t!("commands.owner.jobs.outcome.never", locale = language).to_string(),
+        Some((at, outcome)) => {
+            let outcome = match outcome {
+                JobOutcome::Succeeded => t!("commands.owner.jobs.outcome.succeeded", locale = language),
+                JobOutcome::Failed(e) => t!("commands.owner.jobs.outcome.failed", error = truncate(e, 200), locale = language),
+                JobOutcome::TimedOut => t!("commands.owner.jobs.outcome.timed_out", locale = language),
+            };
+            format!("{} ({})", outcome, get_relative_timestamp(&at.timestamp()))
+        }
+    };
+
+    let value = t!(
+        "commands.owner.jobs.list.value",
+        id = definition.id,
+        schedule = schedule,
+        next_run = get_relative_timestamp(&job.next_run.timestamp()),
+        last_outcome = last_outcome,
+        locale = language
+    ).to_string();
+
+    (format!("{} | {}", definition.name, state), value)
+}
@@ -0,0 +1,75 @@
+use crate::bot::commands;
+use crate::bot::commands::localization::{discord_locales, localize_commands, translation_problems};
+use crate::bot::core::structs::{Data, Error};
+
+fn find<'a>(commands: &'a [poise::Command<Data, Error>], qualified_name: &str) -> &'a poise::Command<Data, Error> {
+    let (first, rest) = qualified_name.split_once(' ').unwrap_or((qualified_name, ""));
+    let mut command = commands.iter().find(|command| command.name == first).unwrap();
+    for name in rest.split_whitespace() {
+        command = command.subcommands.iter().find(|command| command.name == name).unwrap();
+    }
+    command
+}
+
+#[test]
+fn test_discord_locales() {
+    assert_eq!(discord_locales("en"), vec!["en-GB", "en-US"]);
+    assert_eq!(discord_locales("uk"), vec!["uk"]);
+    assert_eq!(discord_locales("PT"), vec!["pt-BR"]);
+    assert!(discord_locales("e").is_empty());
+    assert!(discord_locales("xx").is_empty());
+}
+
+#[test]
+fn test_every_slash_command_is_translated() {
+    let problems = translation_problems(&commands());
+    assert!(problems.is_empty(), "Slash command translations have problems:\n{}", problems.join("\n"));
+}
+
+#[test]
+fn test_localize_commands() {
+    let mut commands = commands();
+    localize_commands(&mut commands);
+
+    let search = find(&commands, "wolvesville clan search");
+    assert_eq!(search.name, "search");
+    assert_eq!(search.name_localizations.get("uk").map(String::as_str), Some("пошук"));
+    assert_eq!(search.description.as_deref(), Some("Search for a Wolvesville clan by its name."));
+    assert_eq!(search.description_localizations.get("uk").map(String::as_str), Some("Знайдіть клан Wolvesville за назвою."));
+    assert_eq!(search.parameters[0].name_localizations.get("uk").map(String::as_str), Some("назва_клану"));
+    // The default language is the base, not a localization
+    assert!(!search.name_localizations.contains_key("en-US"));
+
+    // Groups get the descriptions their doc comments lack
+    let clan = find(&commands, "wolvesville clan");
+    assert_eq!(clan.description.as_deref(), Some("Wolvesville clans."));
+
+    let log_level = find(&commands, "loglevel");
+    assert_eq!(log_level.parameters[0].description_localizations.get("uk").map(String::as_str), Some("Директиви на кшталт `warn,mif::bot=debug` або `reset`"));
+}
+
+#[test]
+fn test_cron_expression_uses_standard_days_of_week() {
+    use std::str::FromStr;
+    use chrono::TimeZone;
+    use crate::bot::commands::administrative::announce::CronExpression;
+
+    // A Wednesday
+    let now = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+    let next_run = |expression: &str| CronExpression::from_str(expression).unwrap().in_timezone(chrono_tz::UTC).unwrap().next_run(now).unwrap();
+
+    let friday = chrono::Utc.with_ymd_and_hms(2025, 1, 3, 20, 0, 0).unwrap();
+    assert_eq!(next_run("0 20 * * 5"), friday);
+    assert_eq!(next_run("0 20 * * FRI"), friday);
+    let sunday = chrono::Utc.with_ymd_and_hms(2025, 1, 5, 9, 0, 0).unwrap();
+    assert_eq!(next_run("0 9 * * 0"), sunday);
+    assert_eq!(next_run("0 9 * * 7"), sunday);
+    assert_eq!(next_run("0 9 * * 6-7"), chrono::Utc.with_ymd_and_hms(2025, 1, 4, 9, 0, 0).unwrap());
+    assert_eq!(next_run("0 9 * * 1,5"), chrono::Utc.with_ymd_and_hms(2025, 1, 3, 9, 0, 0).unwrap());
+    // Sunday, Tuesday, Thursday and Saturday
+    assert_eq!(next_run("0 9 * * */2"), chrono::Utc.with_ymd_and_hms(2025, 1, 2, 9, 0, 0).unwrap());
+    // Six fields are passed to the scheduler as they are
+    assert_eq!(next_run("0 0 20 * * 6"), friday);
+
+    assert!(CronExpression::from_str("0 9 * * 8").is_err());
+}
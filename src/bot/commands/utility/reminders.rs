@@ -0,0 +1,172 @@
+use chrono::TimeDelta;
+use chrono_tz::Tz;
+use crate::utils::logger::error;
+use poise::{serenity_prelude as serenity, CreateReply};
+use crate::bot::core::constants::embed_limits::EMBED_FIELD_AMOUNT_LIMIT;
+use crate::bot::core::structs::{Context, CustomColor, Error};
+use crate::bot::jobs::reminder::{ReminderArgs, ReminderJob, REMINDER_TEXT_LIMIT};
+use crate::utils::{language::get_language, truncate};
+use crate::utils::scheduler::{Job, JobOptions, JobStatus, Schedule};
+use crate::utils::time::{get_relative_timestamp, parse_point_in_time};
+
+const MAX_REMINDERS_PER_USER: usize = EMBED_FIELD_AMOUNT_LIMIT;
+const MIN_REMINDER_DELAY: TimeDelta = TimeDelta::minutes(1);
+const MAX_REMINDER_DELAY: TimeDelta = TimeDelta::days(365);
+
+/// Set a reminder, e.g. `in 2h30m check clan quests` or `tomorrow 18:00 raid`. UTC by default.
+///
+/// Clock times are read in the timezone given before them, like `Europe/Kyiv tomorrow 18:00 raid`.
+#[poise::command(
+    prefix_command, slash_command,
+    category = "utility",
+)]
+pub async fn remind(
+    ctx: Context<'_>,
+    #[flag] dm: bool,
+    timezone: Option<Tz>,
+    #[rest] reminder: String,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let now = data.clock.now();
+
+    let Some((at, text)) = parse_point_in_time(&reminder, now, timezone.unwrap_or(Tz::UTC)) else {
+        ctx.reply(t!("commands.utility.remind.invalid_time", locale = language)).await?;
+        return Ok(());
+    };
+
+    if text.is_empty() {
+        ctx.reply(t!("commands.utility.remind.empty_text", locale = language)).await?;
+        return Ok(());
+    }
+    if text.chars().count() > REMINDER_TEXT_LIMIT {
+        ctx.reply(t!("commands.utility.remind.too_long", limit = REMINDER_TEXT_LIMIT, locale = language)).await?;
+        return Ok(());
+    }
+    if at - now < MIN_REMINDER_DELAY {
+        ctx.reply(t!("commands.utility.remind.too_soon", locale = language)).await?;
+        return Ok(());
+    }
+    if at - now > MAX_REMINDER_DELAY {
+        ctx.reply(t!("commands.utility.remind.too_far", days = MAX_REMINDER_DELAY.num_days(), locale = language)).await?;
+        return Ok(());
+    }
+    if user_reminders(ctx).await.len() >= MAX_REMINDERS_PER_USER {
+        ctx.reply(t!("commands.utility.remind.limit", limit = MAX_REMINDERS_PER_USER, locale = language)).await?;
+        return Ok(());
+    }
+
+    let args = ReminderArgs {
+        user_id: ctx.author().id.get(),
+        channel_id: if dm || ctx.guild_id().is_none() { None } else { Some(ctx.channel_id().get()) },
+        text: text.to_string(),
+        language: language.clone(),
+        created_at: now,
+    };
+
+    match data.scheduler.add_job::<ReminderJob>(Schedule::Once(at), &args, JobOptions::default()).await {
+        Ok(def) => {
+            let key = if args.channel_id.is_some() { "commands.utility.remind.success.channel" } else { "commands.utility.remind.success.dm" };
+            ctx.reply(t!(key, at = format!("<t:{}:f>", at.timestamp()), relative = get_relative_timestamp(&at.timestamp()), id = short_id(&def.id), locale = language)).await?;
+        }
+        Err(e) => {
+            error!("Failed to schedule a reminder for user {}: {:?}", ctx.author().id, e);
+            ctx.reply(t!("commands.utility.remind.fail", locale = language)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Manage your reminders.
+#[poise::command(
+    prefix_command, slash_command,
+    category = "utility",
+    subcommands("list", "cancel"),
+    subcommand_required = false,
+)]
+pub async fn reminders(ctx: Context<'_>) -> Result<(), Error> {
+    list_common(ctx).await
+}
+
+/// List your pending reminders.
+#[poise::command(
+    prefix_command, slash_command,
+)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    list_common(ctx).await
+}
+
+/// Cancel one of your reminders by its ID.
+#[poise::command(
+    prefix_command, slash_command,
+)]
+pub async fn cancel(
+    ctx: Context<'_>,
+    id: String,
+) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let id = id.trim().to_lowercase();
+
+    let matching: Vec<JobStatus> = user_reminders(ctx).await.into_iter()
+        .map(|(job, _)| job)
+        .filter(|job| !id.is_empty() && job.definition.id.to_string().starts_with(&id))
+        .collect();
+
+    let [job] = matching.as_slice() else {
+        ctx.reply(t!("commands.utility.reminders.cancel.not_found", id = id, locale = language)).await?;
+        return Ok(());
+    };
+
+    match ctx.data().scheduler.remove_job(job.definition.id).await {
+        Ok(_) => ctx.reply(t!("commands.utility.reminders.cancel.success", id = short_id(&job.definition.id), locale = language)).await?,
+        Err(e) => {
+            error!("Failed to cancel reminder {}: {:?}", job.definition.id, e);
+            ctx.reply(t!("commands.utility.reminders.cancel.fail", locale = language)).await?
+        }
+    };
+    Ok(())
+}
+
+async fn list_common(ctx: Context<'_>) -> Result<(), Error> {
+    let language = get_language(ctx).await;
+    let reminders = user_reminders(ctx).await;
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title(t!("commands.utility.reminders.list.title", locale = language))
+        .color(CustomColor::CYAN);
+
+    if reminders.is_empty() {
+        embed = embed.description(t!("commands.utility.reminders.list.empty", locale = language));
+    }
+
+    for (job, args) in reminders.iter().take(EMBED_FIELD_AMOUNT_LIMIT) {
+        let delivery = match args.channel_id {
+            Some(channel_id) => format!("<#{}>", channel_id),
+            None => t!("commands.utility.reminders.list.dm", locale = language).to_string(),
+        };
+        embed = embed.field(
+            format!("`{}` · {}", short_id(&job.definition.id), delivery),
+            format!("{}\n{}", get_relative_timestamp(&job.next_run.timestamp()), truncate(&args.text, 200)),
+            false,
+        );
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Pending reminders of the invoking user, soonest first.
+async fn user_reminders(ctx: Context<'_>) -> Vec<(JobStatus, ReminderArgs)> {
+    let user_id = ctx.author().id.get();
+    ctx.data().scheduler.list_jobs().await.into_iter()
+        .filter(|job| job.definition.name == ReminderJob::NAME)
+        .filter_map(|job| {
+            let args: ReminderArgs = serde_json::from_value(job.definition.args.clone()).ok()?;
+            (args.user_id == user_id).then_some((job, args))
+        })
+        .collect()
+}
+
+fn short_id(id: &uuid::Uuid) -> String {
+    id.to_string()[..8].to_string()
+}
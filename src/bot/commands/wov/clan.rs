@@ -2,23 +2,26 @@ use std::path::Path;
 use std::vec;
 
 use chrono::{DateTime, TimeDelta, Utc};
-use logfather::{debug, error, info};
+use crate::utils::logger::{debug, error, info};
 use poise::{serenity_prelude as serenity, CreateReply, ReplyHandle};
 use ::serenity::all::CreateEmbedFooter;
 use tokio::fs::File;
 use crate::bot::core::constants;
 use crate::bot::core::structs::{Context, CustomColor, Data, Error};
-use crate::utils::comma_readable_number;
-use crate::utils::time::{get_long_date, get_relative_timestamp};
+use crate::utils::format::format_number;
+use crate::utils::time::{cooldown_remaining, get_long_date, get_relative_timestamp};
 use crate::{db, utils};
 use crate::utils::apicallers::wolvesville;
 use crate::utils::apicallers::wolvesville::models::{Refreshable, WolvesvilleClan, WolvesvilleClanMember};
-use crate::utils::language::get_language;
+use crate::utils::humanize;
+use crate::utils::language::{dynamic_translation, get_language};
+use super::clan_watch::clan_watch;
 
 async fn on_missing_clan_name(error: poise::FrameworkError<'_, Data, Error>) {
+    crate::bot::handlers::record_command_error(&error).await;
     match error {
         poise::FrameworkError::ArgumentParse { input, ctx, ..} => {
-            let language = get_language(&ctx.data(), &ctx.author().id.to_string()).await;
+            let language = get_language(ctx).await;
 
             if input == None {
                 let embed = serenity::CreateEmbed::default()
@@ -28,16 +31,13 @@ async fn on_missing_clan_name(error: poise::FrameworkError<'_, Data, Error>) {
                 ctx.send(CreateReply::default().reply(true).embed(embed)).await.unwrap();
             }
         }
-        _ => {
-            error!("Unexpected error when running wolvesville clan search command: {}", error);
-        }
+        error => crate::bot::handlers::handle_error(error).await,
     }
 }
 
 #[poise::command(
     prefix_command, slash_command,
-    name_localized("uk", "клан"),
-    subcommands("search"),
+    subcommands("search", "clan_watch"),
     subcommand_required = true,
 )]
 pub async fn clan(_ctx: Context<'_>) -> Result<(), Error> {
@@ -48,15 +48,13 @@ pub async fn clan(_ctx: Context<'_>) -> Result<(), Error> {
 #[poise::command(
     prefix_command, slash_command, 
     on_error = on_missing_clan_name,
-    name_localized("uk", "пошук"),
-    description_localized("uk", "Знайдіть клан Wolvesville за назвою.")
 )]
 pub async fn search(
     ctx: Context<'_>, 
-    #[rest] #[rename = "clan_name"] #[name_localized("uk", "назва_клану")] clan_name: String
+    #[rest] #[rename = "clan_name"] clan_name: String
 ) -> Result<(), Error> {
     let data = ctx.data();
-    let language = get_language(data, &ctx.author().id.to_string()).await;
+    let language = get_language(ctx).await;
 
     info!("Searching for clan: {}", clan_name);
     let mut clans: Vec<WolvesvilleClan> = match db::wolvesville::clan::get_wolvesville_clan_info_by_name(&data.db_pool, clan_name.as_str()).await {
@@ -166,7 +164,7 @@ pub async fn search(
 
     let mut clan = clan.unwrap();
     let mut clan_box: WolvesvilleClan;
-    match clan.is_outdated() {
+    match clan.is_outdated(&*data.clock) {
         true => {
             debug!("Clan is outdated, refreshing");
             match wolvesville::get_wolvesville_clan_info_by_id(&data.wolvesville_client, &clan.id).await {
@@ -192,7 +190,7 @@ pub async fn search(
     debug!("Clan found: {:?}", &clan);
 
     let embed_thumbnail = serenity::CreateAttachment::file(&File::open(Path::new("res/images/wov_logo.png")).await.unwrap(), "wov_logo.png").await.unwrap();
-    let mut embed = construct_clan_embed(&clan, &language);
+    let mut embed = construct_clan_embed(&clan, &language, data.clock.now());
     let button_components = get_clan_search_buttons(ctx.id(), !clan.members.is_none(), false, &language);
 
 
@@ -215,8 +213,20 @@ pub async fn search(
             id if id.ends_with(".fetch_members") => {
                 let members = match wolvesville::get_wolvesville_clan_members_by_id(&data.wolvesville_client, &clan.id).await {
                     Ok(Some(members)) => members,
-                    Ok(None) | Err(_) => {
-                        vec![] // should be unreachable from Ok(None), but if error occurs, just return an empty vec
+                    result => {
+                        if let Err(e) = result {
+                            error!("Failed to fetch the members of clan {}: {:?}", clan.id, e);
+                        }
+                        // Keeps the stored roster and the message as they are, the button can be pressed again
+                        press.create_response(
+                            &ctx.serenity_context(),
+                            serenity::CreateInteractionResponse::Message(
+                                serenity::CreateInteractionResponseMessage::default()
+                                    .embed(get_api_error_embed(&language))
+                                    .ephemeral(true)
+                            )
+                        ).await.unwrap();
+                        continue;
                     }
                 };
 
@@ -236,9 +246,8 @@ pub async fn search(
                 clan.members = Some(members); 
             },
             id if id.ends_with(".refresh") => {
-                let clan_log_timestamp = clan.timestamp.unwrap_or(Utc::now());
-                let time_difference = Utc::now() - clan_log_timestamp;
-                if time_difference.num_hours() < 1 {
+                let now = data.clock.now();
+                if let Some(time_left) = cooldown_remaining(clan.timestamp.unwrap_or(now), TimeDelta::minutes(60), now) {
                     let components = get_clan_search_buttons(ctx.id(), !clan.members.is_none(), true, &language);
 
                     press.create_response(
@@ -257,7 +266,7 @@ pub async fn search(
                                 t!(
                                     "commands.wov.common.buttons.refresh.too_frequent",
                                     interval = 60,
-                                    time_left = get_relative_timestamp(&(Utc::now()+(TimeDelta::minutes(60)-time_difference)).timestamp()),
+                                    time_left = get_relative_timestamp(&(now + time_left).timestamp()),
                                     locale = &language
                                 )
                             )
@@ -295,7 +304,7 @@ pub async fn search(
                     }
 
                     if updated {
-                        embed = construct_clan_embed(&clan, &language);
+                        embed = construct_clan_embed(&clan, &language, data.clock.now());
                         let components = get_clan_search_buttons(ctx.id(), !clan.members.is_none(), false, &language);
                         press.create_response(
                             &ctx.serenity_context(), 
@@ -331,18 +340,18 @@ fn normalize_level(level: i32) -> String {
     if level < 0 { "?".to_string() } else { level.to_string() }
 }
 
-fn construct_clan_embed(clan: &WolvesvilleClan, language: &String) -> serenity::CreateEmbed {
+fn construct_clan_embed(clan: &WolvesvilleClan, language: &String, now: DateTime<Utc>) -> serenity::CreateEmbed {
     serenity::CreateEmbed::default()
         .title(format!("`{}` | {}", clan.tag.clone().unwrap_or("\u{200B}".to_string()), clan.name))
         .description(clan.description.clone().unwrap_or(t!("commands.wov.clan.search.no_description", locale = language).to_string()))
         .color(serenity::Color::new(u32::from_str_radix(&clan.icon_color.trim_start_matches("#"), 16).unwrap_or(0)))
-        .timestamp(clan.timestamp.unwrap_or(Utc::now()))
+        .timestamp(clan.timestamp.unwrap_or(now))
         .thumbnail("attachment://wov_logo.png")
-        .field("XP", format!("**{}**", comma_readable_number(clan.xp as i64)), true)
+        .field("XP", format!("**{}**", format_number(clan.xp as i64, language)), true)
         .field(t!("commands.wov.clan.search.language", locale = language), format!(":flag_{}:", clan.language.to_lowercase()), true)
         .field(t!("commands.wov.clan.search.member_count", locale = language), format!("**{}/50**", clan.member_count), true)
         .field(t!("commands.wov.common.created_on", locale = language), get_long_date(&DateTime::parse_from_rfc3339(&clan.creation_time).unwrap().timestamp()), true)
-        .field(t!("commands.wov.clan.search.status", locale = language), format!("**{}**", t!(format!("commands.wov.clan.search.status.{}", clan.join_type), locale = language)), true)
+        .field(t!("commands.wov.clan.search.status", locale = language), format!("**{}**", dynamic_translation(&format!("commands.wov.clan.search.status.{}", clan.join_type), language, &humanize(&clan.join_type))), true)
         .field(t!("commands.wov.clan.search.minimum_level", locale = language), format!("**{}**", clan.min_level), true)
         .field(t!("commands.wov.clan.search.quests_done", locale = language), format!("**{}**", clan.quest_history_count), true)
 }
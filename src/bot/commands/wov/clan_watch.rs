@@ -0,0 +1,193 @@
+use crate::utils::logger::error;
+use poise::{serenity_prelude as serenity, CreateReply};
+use crate::bot::core::constants::embed_limits::EMBED_FIELD_AMOUNT_LIMIT;
+use crate::bot::core::structs::{Context, CustomColor, Data, Error};
+use crate::db;
+use crate::db::wolvesville::clan_watch::ClanWatch;
+use crate::utils::apicallers::wolvesville;
+use crate::utils::apicallers::wolvesville::models::WolvesvilleClan;
+use crate::utils::language::get_language;
+
+const MAX_WATCHES_PER_GUILD: usize = EMBED_FIELD_AMOUNT_LIMIT;
+const DEFAULT_INACTIVE_DAYS: u32 = 7;
+const MAX_INACTIVE_DAYS: u32 = 365;
+/// Clans listed when a name matches more than one.
+const MAX_AMBIGUOUS_CLANS: usize = 10;
+
+/// Get notified about joins, leaves, promotions and inactive members of a clan.
+#[poise::command(
+    slash_command, prefix_command,
+    rename = "watch",
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("add", "remove", "list"),
+    subcommand_required = true,
+)]
+pub async fn clan_watch(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Watch a clan by its name or ID. Changes are posted to the given channel, or this one.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn add(
+    ctx: Context<'_>,
+    clan: String,
+    channel: Option<serenity::GuildChannel>,
+    inactive_days: Option<u32>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+
+    if channel.as_ref().is_some_and(|channel| Some(channel.guild_id) != ctx.guild_id() || !channel.is_text_based()) {
+        ctx.reply(t!("commands.wov.watch.invalid_channel", locale = language)).await?;
+        return Ok(());
+    }
+
+    let mut clans = match resolve_clan(data, &clan).await {
+        Ok(clans) => clans,
+        Err(e) => {
+            error!("Failed to look up clan `{}` to watch: {:?}", clan, e);
+            let embed = serenity::CreateEmbed::default()
+                .title(t!("common.error", locale = language))
+                .description(t!("common.api_error", locale = language))
+                .color(serenity::Color::RED);
+            ctx.send(CreateReply::default().reply(true).embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let clan = match clans.len() {
+        0 => {
+            ctx.reply(t!("commands.wov.clan.search.not_found", locale = language)).await?;
+            return Ok(());
+        }
+        1 => clans.remove(0),
+        _ => {
+            let mut embed = serenity::CreateEmbed::default()
+                .title(t!("commands.wov.clan.search.multiple_results.title", locale = language))
+                .description(t!("commands.wov.clan.watch.add.ambiguous", locale = language))
+                .color(CustomColor::CYAN);
+            for clan in clans.iter().take(MAX_AMBIGUOUS_CLANS) {
+                embed = embed.field(
+                    format!("`{}` | {}", clan.tag.clone().unwrap_or_default(), clan.name),
+                    format!("`{}`", clan.id),
+                    false,
+                );
+            }
+            ctx.send(CreateReply::default().reply(true).embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let watches = db::wolvesville::clan_watch::get_clan_watches_of_guild(&data.db_pool, &guild_id).await?;
+    let already_watched = watches.iter().any(|(watch, _)| watch.clan_id == clan.id);
+    if !already_watched && watches.len() >= MAX_WATCHES_PER_GUILD {
+        ctx.reply(t!("commands.wov.clan.watch.add.limit", limit = MAX_WATCHES_PER_GUILD, locale = language)).await?;
+        return Ok(());
+    }
+
+    let watch = ClanWatch {
+        guild_id,
+        clan_id: clan.id.clone(),
+        channel_id: channel.map(|channel| channel.id).unwrap_or(ctx.channel_id()).to_string(),
+        inactive_days: inactive_days.unwrap_or(DEFAULT_INACTIVE_DAYS).clamp(1, MAX_INACTIVE_DAYS) as i32,
+        language_code: language.clone(),
+        added_by: ctx.author().id.to_string(),
+        notified_members: None,
+        checked_at: None,
+    };
+    db::wolvesville::clan_watch::upsert_clan_watch(&data.db_pool, &watch).await?;
+
+    ctx.reply(t!(
+        "commands.wov.clan.watch.add.success",
+        clan = clan.name,
+        channel = format!("<#{}>", watch.channel_id),
+        days = watch.inactive_days,
+        locale = language
+    )).await?;
+    Ok(())
+}
+
+/// Stop watching a clan.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn remove(
+    ctx: Context<'_>,
+    #[rest] clan: String,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+    let clan = clan.trim();
+
+    let watches = db::wolvesville::clan_watch::get_clan_watches_of_guild(&data.db_pool, &guild_id).await?;
+    let Some((watch, clan_name)) = watches.iter().find(|(watch, name)| watch.clan_id == clan || name.eq_ignore_ascii_case(clan)) else {
+        ctx.reply(t!("commands.wov.clan.watch.not_watched", clan = clan, locale = language)).await?;
+        return Ok(());
+    };
+
+    db::wolvesville::clan_watch::delete_clan_watch(&data.db_pool, &guild_id, &watch.clan_id).await?;
+    ctx.reply(t!("commands.wov.clan.watch.remove.success", clan = clan_name, locale = language)).await?;
+    Ok(())
+}
+
+/// List the clans watched on this server.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+    let watches = db::wolvesville::clan_watch::get_clan_watches_of_guild(&data.db_pool, &guild_id).await?;
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title(t!("commands.wov.clan.watch.list.title", locale = language))
+        .color(CustomColor::CYAN);
+
+    if watches.is_empty() {
+        embed = embed.description(t!("commands.wov.clan.watch.list.empty", locale = language));
+    }
+
+    for (watch, clan_name) in watches.iter().take(EMBED_FIELD_AMOUNT_LIMIT) {
+        embed = embed.field(
+            clan_name,
+            t!("commands.wov.clan.watch.list.value", channel = format!("<#{}>", watch.channel_id), days = watch.inactive_days, locale = language),
+            true,
+        );
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Finds the clans matching an ID or name, narrowed down to the exact name match if there is one.
+/// Name lookups go to the API, since a stale cache could miss newer clans with the same name.
+/// Found clans are cached, because watches refer to the cached row.
+async fn resolve_clan(data: &Data, query: &str) -> anyhow::Result<Vec<WolvesvilleClan>> {
+    let query = query.trim();
+
+    if uuid::Uuid::parse_str(query).is_ok() {
+        let Some(clan) = wolvesville::get_wolvesville_clan_info_by_id(&data.wolvesville_client, query).await? else {
+            return Ok(Vec::new());
+        };
+        db::wolvesville::clan::upsert_wolvesville_clan(&data.db_pool, clan.clone()).await?;
+        return Ok(vec![clan]);
+    }
+
+    let Some(clans) = wolvesville::get_wolvesville_clan_info_by_name(&data.wolvesville_client, query).await? else {
+        return Ok(Vec::new());
+    };
+    db::wolvesville::clan::upsert_multiple_wolvesville_clans(&data.db_pool, &clans).await?;
+
+    let (exact, others): (Vec<_>, Vec<_>) = clans.into_iter().partition(|clan| clan.name.eq_ignore_ascii_case(query));
+    Ok(if exact.is_empty() { others } else { exact })
+}
@@ -1,7 +1,9 @@
 pub mod player;
 pub mod clan;
+pub mod watch;
+pub mod clan_watch;
 
-use crate::bot::wov::{player::player, clan::clan };
+use crate::bot::wov::{player::player, clan::clan, watch::watch};
 use crate::bot::core::structs::{Context, Error};
 
 
@@ -9,8 +11,7 @@ use crate::bot::core::structs::{Context, Error};
 #[poise::command(
     slash_command, prefix_command,
     category = "wolvesville",
-    description_localized("uk", "Команди Wolvesville."),
-    subcommands("player", "clan"),
+    subcommands("player", "clan", "watch"),
     subcommand_required = true,
 )]
 pub async fn wolvesville(_ctx: Context<'_>) -> Result<(), Error> {
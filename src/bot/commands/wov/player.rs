@@ -4,23 +4,25 @@ use std::path::Path;
 use std::sync::Arc;
 use poise::{serenity_prelude as serenity, CreateReply};
 use crate::bot::core::structs::{Context, Error, Data, CustomEmoji, CustomColor};
-use crate::utils::{language::get_language, apicallers::wolvesville, math::calculate_percentage, image::wolvesville as wov_image};
-use logfather::{debug, info, error};
-use chrono::{DateTime, TimeDelta, Utc};
+use crate::utils::{humanize, language::{dynamic_translation, get_language}, apicallers::wolvesville, math::calculate_percentage, image::wolvesville as wov_image};
+use crate::utils::logger::{debug, info, error};
+use chrono::{DateTime, NaiveDate, TimeDelta, Utc};
 use image::{DynamicImage, ImageFormat};
 use tokio::fs::File;
 use crate::db;
 use crate::utils::apicallers::wolvesville::models::{Avatar, Refreshable, WolvesvillePlayer};
-use crate::utils::time::{get_long_date, get_relative_timestamp, pretty_time_delta};
+use crate::utils::format::{format_date, format_duration, format_number, format_percent};
+use crate::utils::time::{cooldown_remaining, get_long_date, get_relative_timestamp};
 
 #[allow(unused_imports)]
 use crate::utils::apicallers::save_to_file;
 
 
 async fn on_missing_username_input(error: poise::FrameworkError<'_, Data, Error>) {
+    crate::bot::handlers::record_command_error(&error).await;
     match error {
         poise::FrameworkError::ArgumentParse { input, ctx, .. } => {
-            let language = get_language(ctx.data(), &ctx.author().id.to_string()).await;
+            let language = get_language(ctx).await;
 
             if input.is_none() {
                 let embed = serenity::CreateEmbed::default()
@@ -30,15 +32,12 @@ async fn on_missing_username_input(error: poise::FrameworkError<'_, Data, Error>
                 ctx.send(CreateReply::default().reply(true).embed(embed)).await.unwrap();
             }
         }
-        _ => {
-            error!("An error occurred while running the player command: {}", error);
-        }
+        error => crate::bot::handlers::handle_error(error).await,
     }
 }
 
 #[poise::command(
     slash_command, prefix_command,
-    name_localized("uk", "гравець"),
     subcommands("search"),
     subcommand_required = true,
 )]
@@ -48,16 +47,14 @@ pub async fn player(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
 #[poise::command(
     slash_command, prefix_command,
     on_error = on_missing_username_input,
-    name_localized("uk", "пошук"),
-    description_localized("uk", "Знайдіть гравця Wolvesville за їхнім ім'ям.")
 )]
 pub async fn search(
     ctx: Context<'_>, 
-    #[name_localized("uk", "імя_користувача")] username: String
+    username: String
 ) -> Result<(), Error> {
     let data = ctx.data();
     let ctx_id = ctx.id();
-    let language = Arc::new(get_language(data, &ctx.author().id.to_string()).await);
+    let language = Arc::new(get_language(ctx).await);
 
     if username.len() < 3 {
         let embed_too_short = serenity::CreateEmbed::default()
@@ -115,7 +112,7 @@ pub async fn search(
         }
     };
 
-    player = match player.is_outdated() {
+    player = match player.is_outdated(&*data.clock) {
         true => {
             debug!("Player outdated, queried the API for updated information");
             match wolvesville::get_wolvesville_player_by_id(&data.wolvesville_client, &player.id).await {
@@ -138,6 +135,10 @@ pub async fn search(
         false => player
     };
 
+    if let Err(e) = db::wolvesville::player::mark_player_searched(&data.db_pool, &player.id, data.clock.now()).await {
+        error!("Failed to mark player {} as searched: {:?}", player.id, e);
+    }
+
 
     // debug!("{:?}", player);
     // save_to_file(&player, player.username.as_str());
@@ -291,7 +292,7 @@ pub async fn search(
                 });
             },
             id if id.ends_with(".sp_plot") => {
-                let data = db::wolvesville::player::get_all_sp_records_of_player_for_last_n_days(&data.db_pool, &player.id, 30).await.map_err(|e| {
+                let data = db::wolvesville::player::get_all_sp_records_of_player_for_last_n_days(&data.db_pool, &player.id, 30, data.clock.now()).await.map_err(|e| {
                     error!("An error occurred while running the `wolvesville player search` command at request for the SP plot: {:?}", e);
                     e
                 })?;
@@ -339,9 +340,8 @@ pub async fn search(
                 }
             },
             id if id.ends_with(".refresh") => {
-                let player_log_timestamp = player.timestamp.unwrap_or(Utc::now());
-                let time_difference = Utc::now() - player_log_timestamp;
-                if time_difference < TimeDelta::minutes(30) {
+                let now = data.clock.now();
+                if let Some(time_left) = cooldown_remaining(player.timestamp.unwrap_or(now), TimeDelta::minutes(30), now) {
                     // Disable the button
                     let button_components = get_player_search_buttons(ctx_id, false, false, true, &language);
 
@@ -361,7 +361,7 @@ pub async fn search(
                                 t!(
                                     "commands.wov.common.buttons.refresh.too_frequent",
                                     interval = 30,
-                                    time_left = get_relative_timestamp(&(Utc::now()+(TimeDelta::minutes(30)-time_difference)).timestamp()),
+                                    time_left = get_relative_timestamp(&(now + time_left).timestamp()),
                                     locale = language
                                 ))
                             .ephemeral(true)
@@ -478,7 +478,7 @@ async fn construct_player_embed(ctx_data: &Data, language: &String, player: &mut
         else {t!("commands.wov.player.search.description.has_previous_username", username=player.username, previous_username= player.previous_username.as_mut().unwrap(), locale = language)})
         .color(serenity::Color::new(color))
         .thumbnail(format!("attachment://{}", thumbnail_filename))
-        .timestamp(player.timestamp.unwrap_or(Utc::now()));
+        .timestamp(player.timestamp.unwrap_or(ctx_data.clock.now()));
 
     embed = match player.personal_message {
         Some(ref mut pm) => if !pm.is_empty() { embed } else { embed.field(t!("commands.wov.player.search.personal_message", locale = language), pm.clone(), false) },
@@ -499,10 +499,10 @@ async fn construct_player_embed(ctx_data: &Data, language: &String, player: &mut
     };
 
     embed = embed.field(t!("commands.wov.player.search.online_status", locale = language),
-                        format!("{} **{}**", status_emoji, t!(format!("commands.wov.player.search.online_status.{}", player.status), locale = language)), true);
+                        format!("{} **{}**", status_emoji, dynamic_translation(&format!("commands.wov.player.search.online_status.{}", player.status), language, &humanize(&player.status))), true);
 
     let last_online = DateTime::parse_from_rfc3339(player.last_online.as_mut().unwrap().as_str()).unwrap();
-    let last_online = match Utc::now() - last_online.with_timezone(&Utc) < TimeDelta::minutes(7) {
+    let last_online = match ctx_data.clock.now() - last_online.with_timezone(&Utc) < TimeDelta::minutes(7) {
         true => format!("{}", t!("commands.wov.player.search.last_online.just_now", locale = language)),
         false => get_relative_timestamp(&last_online.timestamp())
     };
@@ -515,7 +515,9 @@ async fn construct_player_embed(ctx_data: &Data, language: &String, player: &mut
     } else if player.game_stats.total_play_time_in_minutes < 0 {
         format!("{}", t!("commands.wov.common.created_on.private", locale = language))
     } else {
-        format!("{}", t!("commands.wov.common.created_on.august_3rd_2018", locale = language))
+        // Wolvesville only started recording creation dates on this day
+        let recording_started = NaiveDate::from_ymd_opt(2018, 8, 3).unwrap();
+        format!("{}", t!("commands.wov.common.created_on.or_before", date = format_date(recording_started, language), locale = language))
     };
 
     embed = embed.field(t!("commands.wov.common.created_on", locale = language), created_at, true);
@@ -596,22 +598,22 @@ async fn construct_player_embed(ctx_data: &Data, language: &String, player: &mut
 
         let total_playtime = match player.game_stats.total_play_time_in_minutes {
             -1 => format!("{}", t!("commands.wov.player.search.private", locale = language)),
-            minutes => pretty_time_delta(&TimeDelta::minutes(minutes as i64))
+            minutes => format_duration(&TimeDelta::minutes(minutes as i64), language)
         };
 
         embed = embed.field(
             t!("commands.wov.player.search.general_stats", locale = language),
             t!(
                 "commands.wov.player.search.general_stats.value",
-                total_games = total_amount_of_games,
-                total_wins = player.game_stats.total_win_count,
-                win_percentage = format!("{:.2}", calculate_percentage(player.game_stats.total_win_count, total_amount_of_games)),
-                total_losses = player.game_stats.total_lose_count,
-                lose_percentage = format!("{:.2}", calculate_percentage(player.game_stats.total_lose_count, total_amount_of_games)),
-                total_ties = player.game_stats.total_tie_count,
-                tie_percentage = format!("{:.2}", calculate_percentage(player.game_stats.total_tie_count, total_amount_of_games)),
-                total_flees = player.game_stats.exit_game_by_suicide_count,
-                flee_percentage = format!("{:.2}", calculate_percentage(player.game_stats.exit_game_by_suicide_count, total_amount_of_games)),
+                total_games = format_number(total_amount_of_games as i64, language),
+                total_wins = format_number(player.game_stats.total_win_count as i64, language),
+                win_percentage = format_percent(calculate_percentage(player.game_stats.total_win_count, total_amount_of_games), language),
+                total_losses = format_number(player.game_stats.total_lose_count as i64, language),
+                lose_percentage = format_percent(calculate_percentage(player.game_stats.total_lose_count, total_amount_of_games), language),
+                total_ties = format_number(player.game_stats.total_tie_count as i64, language),
+                tie_percentage = format_percent(calculate_percentage(player.game_stats.total_tie_count, total_amount_of_games), language),
+                total_flees = format_number(player.game_stats.exit_game_by_suicide_count as i64, language),
+                flee_percentage = format_percent(calculate_percentage(player.game_stats.exit_game_by_suicide_count, total_amount_of_games), language),
                 total_playtime = total_playtime,
                 locale = language
             ),
@@ -630,18 +632,18 @@ async fn construct_player_embed(ctx_data: &Data, language: &String, player: &mut
             t!("commands.wov.player.search.team_stats", locale = language),
             t!(
                 "commands.wov.player.search.team_stats.value",
-                village_wins = player.game_stats.village_win_count,
-                village_losses = player.game_stats.village_lose_count,
-                village_wr = format!("{:.2}", calculate_percentage(player.game_stats.village_win_count, player.game_stats.village_win_count + player.game_stats.village_lose_count)),
-                werewolf_wins = player.game_stats.werewolf_win_count,
-                werewolf_losses = player.game_stats.werewolf_lose_count,
-                werewolf_wr = format!("{:.2}", calculate_percentage(player.game_stats.werewolf_win_count, player.game_stats.werewolf_win_count + player.game_stats.werewolf_lose_count)),
-                voting_wins = player.game_stats.voting_win_count,
-                voting_losses = player.game_stats.voting_lose_count,
-                voting_wr = format!("{:.2}", calculate_percentage(player.game_stats.voting_win_count, player.game_stats.voting_win_count + player.game_stats.voting_lose_count)),
-                solo_wins = player.game_stats.solo_win_count,
-                solo_losses = player.game_stats.solo_lose_count,
-                solo_wr = format!("{:.2}", calculate_percentage(player.game_stats.solo_win_count, player.game_stats.solo_win_count + player.game_stats.solo_lose_count)),
+                village_wins = format_number(player.game_stats.village_win_count as i64, language),
+                village_losses = format_number(player.game_stats.village_lose_count as i64, language),
+                village_wr = format_percent(calculate_percentage(player.game_stats.village_win_count, player.game_stats.village_win_count + player.game_stats.village_lose_count), language),
+                werewolf_wins = format_number(player.game_stats.werewolf_win_count as i64, language),
+                werewolf_losses = format_number(player.game_stats.werewolf_lose_count as i64, language),
+                werewolf_wr = format_percent(calculate_percentage(player.game_stats.werewolf_win_count, player.game_stats.werewolf_win_count + player.game_stats.werewolf_lose_count), language),
+                voting_wins = format_number(player.game_stats.voting_win_count as i64, language),
+                voting_losses = format_number(player.game_stats.voting_lose_count as i64, language),
+                voting_wr = format_percent(calculate_percentage(player.game_stats.voting_win_count, player.game_stats.voting_win_count + player.game_stats.voting_lose_count), language),
+                solo_wins = format_number(player.game_stats.solo_win_count as i64, language),
+                solo_losses = format_number(player.game_stats.solo_lose_count as i64, language),
+                solo_wr = format_percent(calculate_percentage(player.game_stats.solo_win_count, player.game_stats.solo_win_count + player.game_stats.solo_lose_count), language),
                 locale = language
             ),
             false
@@ -0,0 +1,160 @@
+use crate::utils::logger::error;
+use poise::{serenity_prelude as serenity, CreateReply};
+use crate::bot::core::constants::embed_limits::EMBED_FIELD_AMOUNT_LIMIT;
+use crate::bot::core::structs::{Context, CustomColor, Data, Error};
+use crate::db;
+use crate::db::wolvesville::watch::PlayerWatch;
+use crate::utils::apicallers::wolvesville;
+use crate::utils::apicallers::wolvesville::models::WolvesvillePlayer;
+use crate::utils::language::get_language;
+
+const MAX_WATCHES_PER_GUILD: usize = EMBED_FIELD_AMOUNT_LIMIT;
+const DEFAULT_SP_THRESHOLD: u32 = 100;
+
+/// Follow Wolvesville players and get notified when they change.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    subcommands("add", "remove", "list"),
+    subcommand_required = true,
+)]
+pub async fn watch(_ctx: Context<'_>) -> Result<(), Error> { Ok(()) }
+
+/// Watch a player. Changes are posted to the given channel, or this one.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn add(
+    ctx: Context<'_>,
+    username: String,
+    channel: Option<serenity::GuildChannel>,
+    sp_threshold: Option<u32>,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+
+    if channel.as_ref().is_some_and(|channel| Some(channel.guild_id) != ctx.guild_id() || !channel.is_text_based()) {
+        ctx.reply(t!("commands.wov.watch.invalid_channel", locale = language)).await?;
+        return Ok(());
+    }
+
+    let player = match resolve_player(data, &username).await {
+        Ok(Some(player)) => player,
+        Ok(None) => {
+            ctx.reply(t!("commands.wov.player.search.not_found", username = username, locale = language)).await?;
+            return Ok(());
+        }
+        Err(e) => {
+            error!("Failed to look up player `{}` to watch: {:?}", username, e);
+            let embed = serenity::CreateEmbed::default()
+                .title(t!("common.error", locale = language))
+                .description(t!("common.api_error", locale = language))
+                .color(serenity::Color::RED);
+            ctx.send(CreateReply::default().reply(true).embed(embed)).await?;
+            return Ok(());
+        }
+    };
+
+    let watches = db::wolvesville::watch::get_player_watches_of_guild(&data.db_pool, &guild_id).await?;
+    let already_watched = watches.iter().any(|(watch, _)| watch.player_id == player.id);
+    if !already_watched && watches.len() >= MAX_WATCHES_PER_GUILD {
+        ctx.reply(t!("commands.wov.watch.add.limit", limit = MAX_WATCHES_PER_GUILD, locale = language)).await?;
+        return Ok(());
+    }
+
+    let watch = PlayerWatch {
+        guild_id,
+        player_id: player.id.clone(),
+        channel_id: channel.map(|channel| channel.id).unwrap_or(ctx.channel_id()).to_string(),
+        sp_threshold: sp_threshold.unwrap_or(DEFAULT_SP_THRESHOLD).clamp(1, i32::MAX as u32) as i32,
+        language_code: language.clone(),
+        added_by: ctx.author().id.to_string(),
+        notified: Some((&player).into()),
+    };
+    db::wolvesville::watch::upsert_player_watch(&data.db_pool, &watch).await?;
+
+    ctx.reply(t!(
+        "commands.wov.watch.add.success",
+        username = player.username,
+        channel = format!("<#{}>", watch.channel_id),
+        threshold = watch.sp_threshold,
+        locale = language
+    )).await?;
+    Ok(())
+}
+
+/// Stop watching a player.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn remove(
+    ctx: Context<'_>,
+    username: String,
+) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+
+    let watches = db::wolvesville::watch::get_player_watches_of_guild(&data.db_pool, &guild_id).await?;
+    let Some((watch, current_username)) = watches.iter().find(|(_, current)| current.eq_ignore_ascii_case(username.trim())) else {
+        ctx.reply(t!("commands.wov.watch.not_watched", username = username, locale = language)).await?;
+        return Ok(());
+    };
+
+    db::wolvesville::watch::delete_player_watch(&data.db_pool, &guild_id, &watch.player_id).await?;
+    ctx.reply(t!("commands.wov.watch.remove.success", username = current_username, locale = language)).await?;
+    Ok(())
+}
+
+/// List the players watched on this server.
+#[poise::command(
+    slash_command, prefix_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+)]
+pub async fn list(ctx: Context<'_>) -> Result<(), Error> {
+    let data = ctx.data();
+    let language = get_language(ctx).await;
+    let guild_id = ctx.guild_id().unwrap().to_string();
+    let watches = db::wolvesville::watch::get_player_watches_of_guild(&data.db_pool, &guild_id).await?;
+
+    let mut embed = serenity::CreateEmbed::default()
+        .title(t!("commands.wov.watch.list.title", locale = language))
+        .color(CustomColor::CYAN);
+
+    if watches.is_empty() {
+        embed = embed.description(t!("commands.wov.watch.list.empty", locale = language));
+    }
+
+    for (watch, username) in watches.iter().take(EMBED_FIELD_AMOUNT_LIMIT) {
+        embed = embed.field(
+            username,
+            t!("commands.wov.watch.list.value", channel = format!("<#{}>", watch.channel_id), threshold = watch.sp_threshold, locale = language),
+            true,
+        );
+    }
+
+    ctx.send(CreateReply::default().embed(embed)).await?;
+    Ok(())
+}
+
+/// Finds a player by their current or previous username, asking the API if they aren't cached yet.
+/// Players fetched from the API are cached, so the watch job has a snapshot to compare against.
+async fn resolve_player(data: &Data, username: &str) -> anyhow::Result<Option<WolvesvillePlayer>> {
+    if let Some(player) = db::wolvesville::player::get_player_by_username(&data.db_pool, username).await? {
+        return Ok(Some(player));
+    }
+
+    if let Some(player) = wolvesville::get_wolvesville_player_by_username(&data.wolvesville_client, username).await? {
+        db::wolvesville::player::upsert_full_player(&data.db_pool, &player).await?;
+        return Ok(Some(player));
+    }
+
+    db::wolvesville::player::get_player_by_previous_username(&data.db_pool, username).await
+}
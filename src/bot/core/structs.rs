@@ -2,23 +2,33 @@ extern crate lru;
 
 use std::collections::HashMap;
 use std::sync::Arc;
-use logfather::error;
+use crate::utils::logger::error;
 use tokio::sync::Mutex;
 use tokio::runtime;
 use lru::LruCache;
 use poise::serenity_prelude as serenity;
-use prometheus::{Registry, IntGauge, Gauge, TextEncoder, Encoder};
+use prometheus::{Registry, IntGauge, Gauge, TextEncoder, Encoder, IntCounterVec, HistogramVec, HistogramOpts, Opts, GaugeVec, IntGaugeVec};
 use serenity::prelude::TypeMapKey;
 use sysinfo::{Pid, System};
+use chrono::{DateTime, Utc};
+use crate::bot::handlers::incidents::IncidentReporter;
+use crate::utils::scheduler::Scheduler;
+use crate::utils::time::Clock;
 
 
 #[derive(Clone)]
 pub struct Data {
     pub db_pool: sqlx::SqlitePool,
     pub prefix_cache: Arc<Mutex<LruCache<String, String>>>,
-    pub language_cache: Arc<Mutex<LruCache<String, String>>>,
+    /// Languages of users and guilds, `None` for those without one.
+    pub language_cache: Arc<Mutex<LruCache<String, Option<String>>>>,
+    pub wolvesville_player_refresh_cache: Arc<Mutex<LruCache<String, DateTime<Utc>>>>,
     pub wolvesville_client: Arc<reqwest::Client>,
     pub custom_emojis: HashMap<String, serenity::Emoji>,
+    pub scheduler: Scheduler,
+    pub clock: Arc<dyn Clock>,
+    pub metrics: Arc<MetricsManager>,
+    pub incidents: Arc<IncidentReporter>,
 }
 
 impl TypeMapKey for Data {
@@ -47,15 +57,13 @@ pub type Context<'a> = poise::Context<'a, Data, Error>;
 pub type ApiResult<T> = Result<Option<T>, reqwest::Error>;
 
 pub struct SystemMetrics {
-    registry: Registry,
     cpu_usage: Gauge,
     memory_usage: Gauge,
     thread_count: IntGauge,
 }
 
 impl SystemMetrics {
-    pub fn new() -> Self {
-        let registry = Registry::new();
+    pub fn new(registry: &Registry) -> Self {
         let cpu_usage = Gauge::new("cpu_usage", "Overall CPU utilization percentage").unwrap();
         let memory_usage = Gauge::new("memory_usage", "Total system memory usage in MB").unwrap();
         let thread_count = IntGauge::new("thread_count", "Total amount of threads").unwrap();
@@ -65,7 +73,6 @@ impl SystemMetrics {
         registry.register(Box::new(thread_count.clone())).unwrap();
 
         Self {
-            registry,
             cpu_usage,
             memory_usage,
             thread_count,
@@ -83,27 +90,180 @@ impl SystemMetrics {
             error!("Could not find process with pid {}", pid);
         }
     }
+}
 
+/// Usage of the bot commands. Every metric is labeled with the qualified command name
+/// and whether it was invoked as a prefix or a slash command.
+pub struct CommandMetrics {
+    pub invocations: IntCounterVec,
+    pub duration: HistogramVec,
+    pub errors: IntCounterVec,
+}
 
-    pub fn render_metrics(&self) -> String {
-        let encoder = TextEncoder::new();
-        let metric_families = self.registry.gather();
-        let mut buffer = Vec::new();
+impl CommandMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        let invocations = IntCounterVec::new(
+            Opts::new("command_invocations_total", "Commands that passed their checks and started running"),
+            &["command", "kind"],
+        ).unwrap();
+        let duration = HistogramVec::new(
+            HistogramOpts::new("command_duration_seconds", "Time from the start of a command until it finished or failed")
+                .buckets(vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0]),
+            &["command", "kind"],
+        ).unwrap();
+        let errors = IntCounterVec::new(
+            Opts::new("command_errors_total", "Commands that failed, by the kind of error"),
+            &["command", "kind", "error"],
+        ).unwrap();
 
-        encoder.encode(&metric_families, &mut buffer)
-            .expect("Failed to encode metrics");
+        registry.register(Box::new(invocations.clone())).unwrap();
+        registry.register(Box::new(duration.clone())).unwrap();
+        registry.register(Box::new(errors.clone())).unwrap();
 
-        String::from_utf8(buffer).expect("Failed to convert metrics to string")
+        Self {
+            invocations,
+            duration,
+            errors,
+        }
+    }
+}
+
+/// Health of the Discord connection and the contents of the serenity cache.
+pub struct GatewayMetrics {
+    pub shard_latency: GaugeVec,
+    pub shard_stage: IntGaugeVec,
+    pub guilds: IntGauge,
+    pub cached_users: IntGauge,
+    pub cached_members: IntGauge,
+    pub connections: IntCounterVec,
+}
+
+impl GatewayMetrics {
+    pub fn new(registry: &Registry) -> Self {
+        let shard_latency = GaugeVec::new(
+            Opts::new("gateway_shard_latency_seconds", "Time between the last heartbeat of a shard and its acknowledgement"),
+            &["shard"],
+        ).unwrap();
+        let shard_stage = IntGaugeVec::new(
+            Opts::new("gateway_shard_stage", "Connection stage of a shard, 1 for the current stage"),
+            &["shard", "stage"],
+        ).unwrap();
+        let guilds = IntGauge::new("gateway_guilds", "Guilds the bot is in").unwrap();
+        let cached_users = IntGauge::new("cache_users", "Users in the serenity cache").unwrap();
+        let cached_members = IntGauge::new("cache_members", "Guild members in the serenity cache").unwrap();
+        let connections = IntCounterVec::new(
+            Opts::new("gateway_connections_total", "Gateway sessions started anew (ready) or resumed"),
+            &["kind"],
+        ).unwrap();
+
+        registry.register(Box::new(shard_latency.clone())).unwrap();
+        registry.register(Box::new(shard_stage.clone())).unwrap();
+        registry.register(Box::new(guilds.clone())).unwrap();
+        registry.register(Box::new(cached_users.clone())).unwrap();
+        registry.register(Box::new(cached_members.clone())).unwrap();
+        registry.register(Box::new(connections.clone())).unwrap();
+
+        Self {
+            shard_latency,
+            shard_stage,
+            guilds,
+            cached_users,
+            cached_members,
+            connections,
+        }
+    }
+
+    pub async fn update(&self, shard_manager: &serenity::ShardManager, cache: &serenity::Cache) {
+        self.shard_latency.reset();
+        self.shard_stage.reset();
+        for (shard_id, runner) in shard_manager.runners.lock().await.iter() {
+            let shard = shard_id.to_string();
+            if let Some(latency) = runner.latency {
+                self.shard_latency.with_label_values(&[&shard]).set(latency.as_secs_f64());
+            }
+            self.shard_stage.with_label_values(&[shard, runner.stage.to_string()]).set(1);
+        }
+
+        self.guilds.set(cache.guild_count() as i64);
+        self.cached_users.set(cache.user_count() as i64);
+        let members: usize = cache.guilds().into_iter()
+            .filter_map(|guild_id| cache.guild(guild_id).map(|guild| guild.members.len()))
+            .sum();
+        self.cached_members.set(members as i64);
+    }
+}
+
+/// Sizes and effectiveness of the in-memory LRU caches in `Data`.
+pub struct CacheMetrics {
+    pub entries: IntGaugeVec,
+    pub hits: IntCounterVec,
+    pub misses: IntCounterVec,
+}
+
+impl CacheMetrics {
+    pub const PREFIX: &'static str = "prefix";
+    pub const LANGUAGE: &'static str = "language";
+    pub const WOLVESVILLE_PLAYER_REFRESH: &'static str = "wolvesville_player_refresh";
+
+    pub fn new(registry: &Registry) -> Self {
+        let entries = IntGaugeVec::new(Opts::new("lru_cache_entries", "Entries in an in-memory cache"), &["cache"]).unwrap();
+        let hits = IntCounterVec::new(Opts::new("lru_cache_hits_total", "Lookups answered by an in-memory cache"), &["cache"]).unwrap();
+        let misses = IntCounterVec::new(Opts::new("lru_cache_misses_total", "Lookups an in-memory cache couldn't answer"), &["cache"]).unwrap();
+
+        registry.register(Box::new(entries.clone())).unwrap();
+        registry.register(Box::new(hits.clone())).unwrap();
+        registry.register(Box::new(misses.clone())).unwrap();
+
+        Self {
+            entries,
+            hits,
+            misses,
+        }
+    }
+
+    /// Counts a lookup as a hit or a miss and passes its result through.
+    pub fn record<T>(&self, cache: &str, lookup: Option<T>) -> Option<T> {
+        match lookup {
+            Some(_) => self.hits.with_label_values(&[cache]).inc(),
+            None => self.misses.with_label_values(&[cache]).inc(),
+        }
+        lookup
+    }
+
+    pub async fn update(&self, data: &Data) {
+        self.entries.with_label_values(&[Self::PREFIX]).set(data.prefix_cache.lock().await.len() as i64);
+        self.entries.with_label_values(&[Self::LANGUAGE]).set(data.language_cache.lock().await.len() as i64);
+        self.entries.with_label_values(&[Self::WOLVESVILLE_PLAYER_REFRESH]).set(data.wolvesville_player_refresh_cache.lock().await.len() as i64);
     }
 }
 
 pub struct MetricsManager {
+    registry: Registry,
     pub system_metrics: SystemMetrics,
+    pub command_metrics: CommandMetrics,
+    pub gateway_metrics: GatewayMetrics,
+    pub cache_metrics: CacheMetrics,
 }
 
 impl MetricsManager {
     pub fn new() -> Self {
-        let system_metrics = SystemMetrics::new();
-        Self { system_metrics }
+        let registry = Registry::new();
+        let system_metrics = SystemMetrics::new(&registry);
+        let command_metrics = CommandMetrics::new(&registry);
+        let gateway_metrics = GatewayMetrics::new(&registry);
+        let cache_metrics = CacheMetrics::new(&registry);
+        crate::utils::metrics::register(&registry);
+        Self { registry, system_metrics, command_metrics, gateway_metrics, cache_metrics }
+    }
+
+    pub fn render_metrics(&self) -> String {
+        let encoder = TextEncoder::new();
+        let metric_families = self.registry.gather();
+        let mut buffer = Vec::new();
+
+        encoder.encode(&metric_families, &mut buffer)
+            .expect("Failed to encode metrics");
+
+        String::from_utf8(buffer).expect("Failed to convert metrics to string")
     }
 }
@@ -1,8 +1,10 @@
 use poise::serenity_prelude as serenity;
-use logfather::error;
+use crate::utils::logger::{error, info};
 use crate::bot::core::structs::Data;
+use crate::bot::jobs::announcement::{AnnouncementArgs, AnnouncementJob};
+use crate::utils::scheduler::Job;
 
-use crate::db::prefixes;
+use crate::db::{guilds, prefixes, wolvesville};
 
 pub async fn on_guild_join(ctx: serenity::Context, guild: serenity::Guild) {
     let data = ctx.data.blocking_read();
@@ -18,9 +20,10 @@ pub async fn on_guild_join(ctx: serenity::Context, guild: serenity::Guild) {
 }
 
 pub async fn on_guild_remove(ctx: serenity::Context, guild_id: String) {
-    let data = ctx.data.blocking_read();
+    let data = ctx.data.read().await;
     let pool = &data.get::<Data>().unwrap().db_pool;
     let prefix_cache = &data.get::<Data>().unwrap().prefix_cache;
+    let scheduler = &data.get::<Data>().unwrap().scheduler;
 
     match prefixes::delete_prefix(pool, &guild_id).await {
         Ok(_) => {}
@@ -28,4 +31,27 @@ pub async fn on_guild_remove(ctx: serenity::Context, guild_id: String) {
     }
 
     prefix_cache.lock().await.pop(&guild_id);
+
+    if let Err(err) = guilds::delete_guild(pool, &guild_id).await {
+        error!("Failed to delete the settings of guild {}: {}", guild_id, err);
+    }
+    data.get::<Data>().unwrap().language_cache.lock().await.pop(&guild_id);
+
+    if let Err(err) = wolvesville::watch::delete_player_watches_of_guild(pool, &guild_id).await {
+        error!("Failed to delete player watches of guild {}: {}", guild_id, err);
+    }
+
+    if let Err(err) = wolvesville::clan_watch::delete_clan_watches_of_guild(pool, &guild_id).await {
+        error!("Failed to delete clan watches of guild {}: {}", guild_id, err);
+    }
+
+    let removed = scheduler.remove_jobs_where(|job| {
+        job.name == AnnouncementJob::NAME
+            && serde_json::from_value::<AnnouncementArgs>(job.args.clone()).is_ok_and(|args| args.guild_id.to_string() == guild_id)
+    }).await;
+    match removed {
+        Ok(0) => {}
+        Ok(count) => info!("Removed {} announcements of guild {}", count, guild_id),
+        Err(err) => error!("Failed to remove announcements of guild {}: {}", guild_id, err),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use poise::serenity_prelude as serenity;
+use uuid::Uuid;
+use crate::bot::core::constants::embed_limits::EMBED_FIELD_VALUE_LIMIT;
+use crate::bot::core::structs::Context;
+use crate::utils::logger::{crash, error, info, warn};
+use crate::utils::truncate;
+
+/// How long identical errors are held back after one was reported.
+const DEFAULT_COOLDOWN: Duration = Duration::minutes(10);
+/// Past this many fingerprints, the ones out of their cooldown are forgotten.
+const MAX_TRACKED: usize = 500;
+
+/// Short id shown to the user and in the report, so the two can be matched up.
+pub fn new_incident_id() -> String {
+    Uuid::new_v4().simple().to_string()[..8].to_uppercase()
+}
+
+/// The error and everything it was caused by, outermost first.
+pub fn error_chain(error: &(dyn std::error::Error + 'static)) -> Vec<String> {
+    std::iter::successors(Some(error), |error| error.source()).map(|error| error.to_string()).collect()
+}
+
+/// What makes two errors "the same" for rate limiting: the command and the outermost error, without the parts that
+/// change from one invocation to the next. See `error_shape`.
+pub fn fingerprint(command: &str, chain: &[String]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    command.hash(&mut hasher);
+    chain.first().map(|message| error_shape(message)).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// `message` up to the first `: `, which is usually followed by the input or the underlying error, with quoted
+/// values, URLs and numbers replaced by placeholders. `No clans found by name: wolves` becomes `No clans found by name`
+/// and `Invalid cron expression '0 9 * * 5'` becomes `Invalid cron expression <value>`.
+pub fn error_shape(message: &str) -> String {
+    let message = message.split_once(": ").map_or(message, |(outer, _)| outer);
+    let mut shape = String::with_capacity(message.len());
+    let mut previous = ' ';
+    let mut rest = message;
+
+    while let Some(c) = rest.chars().next() {
+        // An apostrophe within a word, like in `can't`, doesn't start a quote
+        let quote = matches!(c, '"' | '`') || (c == '\'' && !previous.is_alphanumeric());
+        if rest.starts_with("http://") || rest.starts_with("https://") {
+            shape.push_str("<url>");
+            rest = &rest[rest.find(char::is_whitespace).unwrap_or(rest.len())..];
+        } else if quote && let Some(end) = rest[1..].find(c) {
+            shape.push_str("<value>");
+            rest = &rest[end + 2..];
+        } else if c.is_ascii_digit() {
+            shape.push('#');
+            rest = &rest[rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len())..];
+        } else {
+            shape.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+        previous = shape.chars().next_back().unwrap_or(' ');
+    }
+    shape
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Delivery {
+    /// Post the report, mentioning how many identical errors were held back since the previous one.
+    Report { suppressed: u32 },
+    /// Hold it back, the incident reported last already covers it.
+    Suppress { reported_incident_id: String },
+}
+
+struct Reported {
+    incident_id: String,
+    at: DateTime<Utc>,
+    suppressed: u32,
+}
+
+/// Reports command errors to the developers, either in `ERROR_REPORT_CHANNEL_ID` or by DM to the bot owners.
+/// Identical errors are reported at most once per `ERROR_REPORT_COOLDOWN_SECS`.
+pub struct IncidentReporter {
+    channel_id: Option<serenity::ChannelId>,
+    cooldown: Duration,
+    reported: Mutex<HashMap<u64, Reported>>,
+}
+
+impl IncidentReporter {
+    pub fn new(channel_id: Option<serenity::ChannelId>, cooldown: Duration) -> Self {
+        Self { channel_id, cooldown, reported: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn from_env() -> Self {
+        let channel_id = std::env::var("ERROR_REPORT_CHANNEL_ID").ok().and_then(|v| v.trim().parse().ok()).map(serenity::ChannelId::new);
+        let cooldown = std::env::var("ERROR_REPORT_COOLDOWN_SECS").ok().and_then(|v| v.trim().parse().ok()).map(Duration::seconds).unwrap_or(DEFAULT_COOLDOWN);
+        Self::new(channel_id, cooldown)
+    }
+
+    /// Decides whether the incident gets reported, and remembers it if so.
+    pub fn delivery(&self, fingerprint: u64, incident_id: &str, now: DateTime<Utc>) -> Delivery {
+        let mut reported = self.reported.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let Some(previous) = reported.get_mut(&fingerprint) && now - previous.at < self.cooldown {
+            previous.suppressed += 1;
+            return Delivery::Suppress { reported_incident_id: previous.incident_id.clone() };
+        }
+
+        if reported.len() >= MAX_TRACKED {
+            reported.retain(|_, previous| now - previous.at < self.cooldown);
+        }
+        let suppressed = reported.insert(fingerprint, Reported { incident_id: incident_id.to_string(), at: now, suppressed: 0 })
+            .map_or(0, |previous| previous.suppressed);
+        Delivery::Report { suppressed }
+    }
+
+    /// Posts a report of a failed invocation, unless an identical error was reported recently.
+    pub async fn report(&self, ctx: Context<'_>, incident_id: &str, chain: &[String]) {
+        let command = ctx.command().qualified_name.clone();
+        let suppressed = match self.delivery(fingerprint(&command, chain), incident_id, ctx.data().clock.now()) {
+            Delivery::Report { suppressed } => suppressed,
+            Delivery::Suppress { reported_incident_id } => {
+                warn!("Incident {} is not reported, it repeats incident {}", incident_id, reported_incident_id);
+                return;
+            }
+        };
+
+        let guild = match ctx.guild_id() {
+            Some(guild_id) => {
+                let name = ctx.guild().map(|guild| guild.name.clone()).unwrap_or_default();
+                format!("{} (`{}`)", name, guild_id)
+            }
+            None => "DM".to_string(),
+        };
+        let chain = chain.iter().enumerate().map(|(i, cause)| format!("{}: {}", i, cause)).collect::<Vec<_>>().join("\n");
+
+        let mut embed = serenity::CreateEmbed::default()
+            .title(format!("Incident {}", incident_id))
+            .color(serenity::Colour::RED)
+            .field("Command", format!("`{}`", command), true)
+            .field("Guild", guild, true)
+            .field("User", format!("{} (`{}`)", ctx.author().name, ctx.author().id), true)
+            .field("Invocation", format!("```{}```", truncate(&ctx.invocation_string(), EMBED_FIELD_VALUE_LIMIT - 10)), false)
+            .field("Error", format!("```{}```", truncate(&chain, EMBED_FIELD_VALUE_LIMIT - 10)), false)
+            .timestamp(serenity::Timestamp::now());
+        if suppressed > 0 {
+            embed = embed.footer(serenity::CreateEmbedFooter::new(format!("{} identical errors were held back since the last report", suppressed)));
+        }
+        let message = serenity::CreateMessage::default().embed(embed);
+
+        if !self.send_to_developers(ctx.http(), &ctx.framework().options().owners, message).await {
+            warn!("Incident {} could not be reported to the developers", incident_id);
+        }
+    }
+
+    /// Posts `message` in the developer channel, or DMs it to every owner when there is none.
+    /// Returns whether it reached anyone.
+    pub async fn send_to_developers(&self, http: &serenity::Http, owners: &HashSet<serenity::UserId>, message: serenity::CreateMessage) -> bool {
+        if let Some(channel_id) = self.channel_id {
+            return match channel_id.send_message(http, message).await {
+                Ok(_) => true,
+                Err(e) => {
+                    error!("Failed to post to the developer channel {}: {:?}", channel_id, e);
+                    false
+                }
+            };
+        }
+
+        if owners.is_empty() {
+            warn!("There's no ERROR_REPORT_CHANNEL_ID and no known owner to report to");
+        }
+        let mut delivered = false;
+        for owner in owners {
+            match owner.direct_message(http, message.clone()).await {
+                Ok(_) => delivered = true,
+                Err(e) => error!("Failed to DM owner {}: {:?}", owner, e),
+            }
+        }
+        delivered
+    }
+}
+
+/// Posts the crash reports left behind by earlier runs, see `set_up_panic_hook`, and marks them delivered.
+/// Reports that can't be delivered are kept for the next start, delivered ones for `crash::DELIVERED_RETENTION`.
+pub async fn deliver_crash_reports(http: Arc<serenity::Http>, reporter: Arc<IncidentReporter>, owners: HashSet<serenity::UserId>) {
+    let dir = crash::crash_dir();
+    match crash::prune_delivered(&dir, Utc::now(), crash::DELIVERED_RETENTION) {
+        Ok(0) => {},
+        Ok(pruned) => info!("Deleted {} delivered crash reports", pruned),
+        Err(e) => error!("Failed to delete old crash reports in {}: {}", dir.display(), e),
+    }
+
+    let reports = match crash::undelivered(&dir) {
+        Ok(reports) => reports,
+        Err(e) => {
+            error!("Failed to read the crash reports in {}: {}", dir.display(), e);
+            return;
+        }
+    };
+
+    for (path, mut report) in reports {
+        let embed = serenity::CreateEmbed::default()
+            .title(format!("Crash report {}", report.id))
+            .description(format!("```{}```", truncate(&report.payload, EMBED_FIELD_VALUE_LIMIT - 10)))
+            .color(serenity::Colour::DARK_RED)
+            .field("Location", format!("`{}`", report.location), false)
+            .field("Thread", format!("`{}`", report.thread), true)
+            .field("Occurred", format!("<t:{}:F>", report.occurred_at.timestamp()), true)
+            .footer(serenity::CreateEmbedFooter::new("The backtrace and the last log lines are in the attached report"));
+        let attachment = match serenity::CreateAttachment::path(&path).await {
+            Ok(attachment) => attachment,
+            Err(e) => {
+                error!("Failed to attach crash report {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let message = serenity::CreateMessage::default().embed(embed).add_file(attachment);
+
+        if !reporter.send_to_developers(&http, &owners, message).await {
+            warn!("Crash report {} could not be delivered, it will be retried on the next start", report.id);
+            continue;
+        }
+        match report.mark_delivered(&path, Utc::now()) {
+            Ok(_) => info!("Delivered crash report {}", report.id),
+            Err(e) => error!("Failed to mark crash report {} as delivered: {}", path.display(), e),
+        }
+    }
+}
@@ -1,11 +1,25 @@
+use std::time::Instant;
 use poise::{serenity_prelude as serenity, CreateReply};
-use logfather::{warn, info, error};
-use crate::bot::core::structs::{Data, Error};
+use crate::utils::logger::{warn, info, error};
+use std::sync::Arc;
+use crate::bot::core::structs::{Context, Data, Error, MetricsManager};
+use crate::utils::logger::{self, LogContext};
 
 mod guild_events;
+pub mod incidents;
 mod ready;
+#[cfg(test)]
+mod tests;
 
-pub struct Handler;
+pub struct Handler {
+    metrics: Arc<MetricsManager>,
+}
+
+impl Handler {
+    pub fn new(metrics: Arc<MetricsManager>) -> Self {
+        Self { metrics }
+    }
+}
 
 #[serenity::async_trait]
 impl serenity::EventHandler for Handler {
@@ -16,36 +30,125 @@ impl serenity::EventHandler for Handler {
     }
 
     async fn guild_delete(&self, ctx: serenity::Context, incomplete: serenity::UnavailableGuild, _full: Option<serenity::Guild>) {
+        // An unavailable guild is an outage, not a removal, so its settings are kept
+        if incomplete.unavailable {
+            return;
+        }
         let guild_id = incomplete.id.get().to_string();
         guild_events::on_guild_remove(ctx, guild_id).await;
     }
 
     async fn ready(&self, ctx: serenity::Context, ready: serenity::Ready) {
+        self.metrics.gateway_metrics.connections.with_label_values(&["ready"]).inc();
         ready::on_ready(ctx, ready).await;
     }
 
     async fn resume(&self, _ctx: serenity::Context, _event: serenity::ResumedEvent) {
+        self.metrics.gateway_metrics.connections.with_label_values(&["resumed"]).inc();
         info!("Resumed");
     }
 }
 
+/// Runs every event in its own log context, which `pre_command` fills in for command invocations.
+pub struct LogContextFramework<F>(pub F);
+
+#[serenity::async_trait]
+impl<F: serenity::Framework> serenity::Framework for LogContextFramework<F> {
+    async fn init(&mut self, client: &serenity::Client) {
+        self.0.init(client).await;
+    }
+
+    async fn dispatch(&self, ctx: serenity::Context, event: serenity::FullEvent) {
+        logger::context::scope(self.0.dispatch(ctx, event)).await;
+    }
+}
+
+fn set_log_context(ctx: Context<'_>) {
+    logger::context::set(LogContext {
+        command: Some(ctx.command().qualified_name.clone()),
+        invocation_id: Some(ctx.id().to_string()),
+        guild_id: ctx.guild_id().map(|id| id.to_string()),
+        user_id: Some(ctx.author().id.to_string()),
+    });
+}
+
+/// Labels of the command metrics: the qualified command name and how it was invoked.
+fn command_labels(ctx: Context<'_>) -> [String; 2] {
+    let kind = match ctx {
+        poise::Context::Prefix(_) => "prefix",
+        poise::Context::Application(_) => "slash",
+    };
+    [ctx.command().qualified_name.clone(), kind.to_string()]
+}
+
+pub async fn pre_command(ctx: Context<'_>) {
+    set_log_context(ctx);
+    let labels = command_labels(ctx);
+    ctx.data().metrics.command_metrics.invocations.with_label_values(&labels).inc();
+    ctx.set_invocation_data(Instant::now()).await;
+}
+
+pub async fn post_command(ctx: Context<'_>) {
+    observe_command_duration(ctx).await;
+}
+
+async fn observe_command_duration(ctx: Context<'_>) {
+    // Missing when the command failed its checks and never started
+    let Some(started) = ctx.invocation_data::<Instant>().await.map(|started| *started) else { return };
+    let labels = command_labels(ctx);
+    ctx.data().metrics.command_metrics.duration.with_label_values(&labels).observe(started.elapsed().as_secs_f64());
+}
+
+/// Counts a failed command in the metrics. Commands with their own `on_error` have to call this themselves,
+/// since poise only runs the framework handler for commands without one.
+pub async fn record_command_error(error: &poise::FrameworkError<'_, Data, Error>) {
+    let Some(ctx) = error.ctx() else { return };
+    // Errors in checks and argument parsing come before `pre_command`
+    set_log_context(ctx);
+    let kind = match error {
+        poise::FrameworkError::Command { .. } => "command",
+        poise::FrameworkError::CommandPanic { .. } => "panic",
+        poise::FrameworkError::ArgumentParse { .. } => "argument_parse",
+        poise::FrameworkError::SubcommandRequired { .. } => "subcommand_required",
+        poise::FrameworkError::CooldownHit { .. } => "cooldown",
+        poise::FrameworkError::MissingBotPermissions { .. } => "missing_bot_permissions",
+        poise::FrameworkError::MissingUserPermissions { .. } => "missing_user_permissions",
+        poise::FrameworkError::NotAnOwner { .. } => "not_an_owner",
+        poise::FrameworkError::GuildOnly { .. } | poise::FrameworkError::DmOnly { .. } | poise::FrameworkError::NsfwOnly { .. } => "wrong_channel",
+        poise::FrameworkError::CommandCheckFailed { .. } => "check_failed",
+        _ => "other",
+    };
+
+    let [command, invocation] = command_labels(ctx);
+    ctx.data().metrics.command_metrics.errors.with_label_values(&[command, invocation, kind.to_string()]).inc();
+    observe_command_duration(ctx).await;
+}
 
 pub async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
+    record_command_error(&error).await;
+    handle_error(error).await;
+}
+
+/// Responds to an error without counting it, for `on_error` handlers of commands that already did.
+pub async fn handle_error(error: poise::FrameworkError<'_, Data, Error>) {
     match error {
         poise::FrameworkError::Setup { error, .. } => {
             error!("An error occurred during setup: {:?}", error);
         },
         poise::FrameworkError::Command { error, ctx, .. } => {
-            error!("An error occurred while running a command: {:?}", error);
+            let incident_id = incidents::new_incident_id();
+            error!("Incident {}: an error occurred while running a command: {:?}", incident_id, error);
 
-            let embed = serenity::CreateEmbed::default()
-                .title("Error")
-                .description("An error occurred while running the command. It has been automatically reported to the developer.\n\nPlease try again later.")
-                .color(serenity::Colour::RED);
+            respond_with_incident(ctx, &incident_id).await;
+            ctx.data().incidents.report(ctx, &incident_id, &incidents::error_chain(error.as_ref())).await;
+        },
+        poise::FrameworkError::CommandPanic { payload, ctx, .. } => {
+            let incident_id = incidents::new_incident_id();
+            let payload = payload.unwrap_or("<unknown>".to_string());
+            error!("Incident {}: a command panicked: {}", incident_id, payload);
 
-            if let Err(err) = ctx.send(CreateReply::default().embed(embed)).await {
-                error!("Failed to respond to the command error: {:?}", err);
-            };
+            respond_with_incident(ctx, &incident_id).await;
+            ctx.data().incidents.report(ctx, &incident_id, &[format!("panic: {}", payload)]).await;
         },
         poise::FrameworkError::MissingUserPermissions { missing_permissions, ctx, .. } => {
             let missing_permissions = missing_permissions
@@ -90,4 +193,15 @@ pub async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
             error!("An error occurred: {:?}", error.to_string());
         }
     }
-}
\ No newline at end of file
+}
+
+async fn respond_with_incident(ctx: Context<'_>, incident_id: &str) {
+    let embed = serenity::CreateEmbed::default()
+        .title("Error")
+        .description(format!("An error occurred while running the command. It has been automatically reported to the developer as incident `{}`.\n\nPlease try again later.", incident_id))
+        .color(serenity::Colour::RED);
+
+    if let Err(err) = ctx.send(CreateReply::default().embed(embed)).await {
+        error!("Failed to respond to the command error: {:?}", err);
+    };
+}
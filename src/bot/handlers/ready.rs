@@ -1,8 +1,6 @@
 use poise::serenity_prelude as serenity;
-use logfather::info;
-use crate::db;
+use crate::utils::logger::info;
 
 pub async fn on_ready(_ctx: serenity::Context, ready: serenity::Ready) {
-    db::create_db().await;
     info!("Connected to {}", ready.user.name);
 }
\ No newline at end of file
@@ -0,0 +1,56 @@
+use chrono::{Duration, TimeZone, Utc};
+use crate::bot::handlers::incidents::{error_chain, error_shape, fingerprint, new_incident_id, Delivery, IncidentReporter};
+
+#[test]
+fn test_incident_ids_are_short_and_unique() {
+    let id = new_incident_id();
+    assert_eq!(id.len(), 8);
+    assert!(id.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_lowercase()));
+    assert_ne!(id, new_incident_id());
+}
+
+#[test]
+fn test_error_chain_follows_the_sources() {
+    let error = anyhow::anyhow!("connection refused").context("failed to fetch the player").context("search failed");
+    let error: Box<dyn std::error::Error + Send + Sync> = error.into();
+    assert_eq!(error_chain(error.as_ref()), vec!["search failed", "failed to fetch the player", "connection refused"]);
+}
+
+#[test]
+fn test_errors_differing_only_in_user_input_share_a_fingerprint() {
+    let chain = |messages: &[&str]| messages.iter().map(|message| message.to_string()).collect::<Vec<_>>();
+    let search = |name: &str, url: &str| fingerprint("wolvesville clan search", &chain(&[
+        &format!("No clans found by name: {}", name),
+        &format!("error sending request for url ({})", url),
+    ]));
+
+    assert_eq!(
+        search("wolves", "https://api.wolvesville.com/clans/search?name=wolves"),
+        search("Night Owls", "https://api.wolvesville.com/clans/search?name=Night%20Owls"),
+    );
+    assert_ne!(search("wolves", ""), fingerprint("wolvesville clan search", &chain(&["Failed to deserialize clan: EOF"])));
+    assert_ne!(search("wolves", ""), fingerprint("wolvesville clan info", &chain(&["No clans found by name: wolves"])));
+
+    assert_eq!(error_shape("Invalid cron expression '0 9 * * 5'"), "Invalid cron expression <value>");
+    assert_eq!(error_shape("Reminder can't be set for `tomorrow`"), "Reminder can't be set for <value>");
+    assert_eq!(error_shape("Status 429 from https://api.wolvesville.com/players/1?x=2 after 3 tries"), "Status # from <url> after # tries");
+    assert_eq!(error_shape("Unclosed \"quote"), "Unclosed \"quote");
+}
+
+#[test]
+fn test_identical_errors_are_rate_limited() {
+    let reporter = IncidentReporter::new(None, Duration::minutes(10));
+    let start = Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap();
+    let timeout = fingerprint("wolvesville player search", &["timed out".to_string()]);
+    let other = fingerprint("wolvesville clan search", &["timed out".to_string()]);
+
+    assert_eq!(reporter.delivery(timeout, "A", start), Delivery::Report { suppressed: 0 });
+    assert_eq!(reporter.delivery(timeout, "B", start + Duration::minutes(1)), Delivery::Suppress { reported_incident_id: "A".to_string() });
+    assert_eq!(reporter.delivery(timeout, "C", start + Duration::minutes(9)), Delivery::Suppress { reported_incident_id: "A".to_string() });
+    // A different command failing the same way is its own incident
+    assert_eq!(reporter.delivery(other, "D", start + Duration::minutes(9)), Delivery::Report { suppressed: 0 });
+
+    // Once the cooldown is over, the next report counts what was held back
+    assert_eq!(reporter.delivery(timeout, "E", start + Duration::minutes(10)), Delivery::Report { suppressed: 2 });
+    assert_eq!(reporter.delivery(timeout, "F", start + Duration::minutes(11)), Delivery::Suppress { reported_incident_id: "E".to_string() });
+}
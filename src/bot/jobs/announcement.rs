@@ -0,0 +1,78 @@
+use poise::serenity_prelude::{self as serenity, Mentionable};
+use serde::{Deserialize, Serialize};
+use crate::bot::background::DiscordHandle;
+use crate::bot::core::constants::embed_limits::{EMBED_DESCRIPTION_LIMIT, EMBED_TITLE_LIMIT};
+use crate::bot::core::structs::CustomColor;
+use crate::utils::scheduler::Job;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AnnouncementArgs {
+    pub guild_id: u64,
+    pub channel_id: u64,
+    /// Unique per guild, used to refer to the announcement in commands.
+    pub name: String,
+    pub title: Option<String>,
+    pub message: String,
+    /// Role mentioned above the embed.
+    pub role_id: Option<u64>,
+    pub created_by: u64,
+}
+
+impl AnnouncementArgs {
+    pub fn embed(&self) -> serenity::CreateEmbed {
+        let embed = serenity::CreateEmbed::default()
+            .description(&self.message)
+            .color(CustomColor::CYAN);
+        match &self.title {
+            Some(title) => embed.title(title),
+            None => embed,
+        }
+    }
+
+    pub fn role_mention(&self) -> Option<String> {
+        self.role_id.map(|role_id| serenity::RoleId::new(role_id).mention().to_string())
+    }
+}
+
+/// Posts a recurring announcement set up with `announce schedule`. Scheduled as a cron job.
+pub struct AnnouncementJob {
+    discord: DiscordHandle,
+}
+
+impl AnnouncementJob {
+    pub fn new(discord: DiscordHandle) -> Self {
+        Self { discord }
+    }
+}
+
+impl Job for AnnouncementJob {
+    const NAME: &'static str = "announcement";
+
+    type Args = AnnouncementArgs;
+
+    fn validate(args: &Self::Args) -> anyhow::Result<()> {
+        if args.message.trim().is_empty() {
+            return Err(anyhow::anyhow!("Announcement message is empty"));
+        }
+        if args.message.chars().count() > EMBED_DESCRIPTION_LIMIT {
+            return Err(anyhow::anyhow!("Announcement message is longer than {} characters", EMBED_DESCRIPTION_LIMIT));
+        }
+        if args.title.as_ref().is_some_and(|title| title.chars().count() > EMBED_TITLE_LIMIT) {
+            return Err(anyhow::anyhow!("Announcement title is longer than {} characters", EMBED_TITLE_LIMIT));
+        }
+        Ok(())
+    }
+
+    async fn run(&self, args: Self::Args) -> anyhow::Result<()> {
+        let http = self.discord.http()?;
+        let mut message = serenity::CreateMessage::default().embed(args.embed());
+        if let (Some(role_id), Some(mention)) = (args.role_id, args.role_mention()) {
+            message = message
+                .content(mention)
+                .allowed_mentions(serenity::CreateAllowedMentions::new().roles(vec![serenity::RoleId::new(role_id)]));
+        }
+
+        serenity::ChannelId::new(args.channel_id).send_message(&http, message).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use chrono::TimeDelta;
+use crate::utils::logger::{info, warn};
+use poise::serenity_prelude as serenity;
+use crate::bot::background::JobContext;
+use crate::bot::core::constants::embed_limits::EMBED_DESCRIPTION_LIMIT;
+use crate::bot::core::structs::CustomColor;
+use crate::db;
+use crate::db::wolvesville::clan_watch::ClanWatch;
+use crate::utils::apicallers::wolvesville;
+use crate::utils::scheduler::Job;
+use crate::utils::time::get_relative_timestamp;
+use crate::utils::wolvesville::{diff_clan_members, ClanChange};
+
+/// How often the rosters of watched clans are checked.
+pub const CLAN_WATCH_INTERVAL: chrono::Duration = chrono::Duration::minutes(30);
+/// Pause between two API requests, to stay well within the Wolvesville rate limit.
+const REQUEST_SPACING: Duration = Duration::from_millis(500);
+
+/// Fetches the members of every watched clan and posts roster changes to the guilds watching it.
+pub struct ClanWatchJob {
+    ctx: JobContext,
+}
+
+impl ClanWatchJob {
+    pub fn new(ctx: JobContext) -> Self {
+        Self { ctx }
+    }
+
+    async fn refresh(&self, http: &serenity::Http, clan_id: &str, watches: &[ClanWatch]) -> anyhow::Result<()> {
+        let stored = db::wolvesville::clan::get_wolvesville_clan_info_by_id(&self.ctx.db_pool, clan_id).await?;
+        let Some(members) = wolvesville::get_wolvesville_clan_members_by_id(&self.ctx.wolvesville_client, clan_id).await? else {
+            warn!("Watched clan {} was not found by the API", clan_id);
+            return Ok(());
+        };
+        // Only a cache for `clan search`, each watch compares against the roster it last posted
+        db::wolvesville::clan::update_wolvesville_clan_members_explicitly(&self.ctx.db_pool, clan_id, &members).await?;
+        let clan_name = stored.as_ref().map_or(clan_id, |clan| clan.name.as_str());
+
+        let now = self.ctx.clock.now();
+        for watch in watches {
+            // The first check only sets the baseline to compare against
+            if let Some(notified_members) = &watch.notified_members {
+                // A new watch doesn't report members who were already inactive when it was added
+                let previous_check = watch.checked_at.unwrap_or(now);
+                let changes = diff_clan_members(notified_members, &members, TimeDelta::days(watch.inactive_days.into()), previous_check, now);
+                if !changes.is_empty() {
+                    let embed = changes_embed(clan_name, &changes, &watch.language_code);
+                    let channel_id = serenity::ChannelId::new(watch.channel_id.parse()?);
+                    if let Err(e) = channel_id.send_message(http, serenity::CreateMessage::default().embed(embed)).await {
+                        // Compared against the same roster again next time, so the changes are posted late rather than never
+                        warn!("Failed to post roster changes of clan {} to channel {} of guild {}: {}", clan_id, watch.channel_id, watch.guild_id, e);
+                        continue;
+                    }
+                }
+            }
+
+            db::wolvesville::clan_watch::set_clan_watch_notified(&self.ctx.db_pool, &watch.guild_id, clan_id, &members, now).await?;
+        }
+
+        Ok(())
+    }
+}
+
+fn changes_embed(clan_name: &str, changes: &[ClanChange], language: &str) -> serenity::CreateEmbed {
+    let mut description = String::new();
+    for change in changes {
+        let line = match change {
+            ClanChange::Joined { username } => t!("jobs.clan_watch.joined", username = username, locale = language),
+            ClanChange::Left { username } => t!("jobs.clan_watch.left", username = username, locale = language),
+            ClanChange::Promoted { username } => t!("jobs.clan_watch.promoted", username = username, locale = language),
+            ClanChange::Demoted { username } => t!("jobs.clan_watch.demoted", username = username, locale = language),
+            ClanChange::WentInactive { username, last_online } => t!("jobs.clan_watch.inactive", username = username, last_online = get_relative_timestamp(&last_online.timestamp()), locale = language),
+        };
+        // Stay within the embed limit, which a big reshuffle of a full clan could exceed
+        if description.len() + line.len() + 1 > EMBED_DESCRIPTION_LIMIT {
+            break;
+        }
+        description.push_str(&line);
+        description.push('\n');
+    }
+
+    let color = match changes.first() {
+        Some(ClanChange::Left { .. }) | Some(ClanChange::WentInactive { .. }) => serenity::Color::ORANGE,
+        _ => CustomColor::CYAN,
+    };
+
+    serenity::CreateEmbed::default()
+        .title(t!("jobs.clan_watch.title", clan = clan_name, locale = language))
+        .description(description)
+        .color(color)
+}
+
+impl Job for ClanWatchJob {
+    const NAME: &'static str = "wolvesville_clan_watch";
+
+    type Args = ();
+
+    async fn run(&self, _args: Self::Args) -> anyhow::Result<()> {
+        let http = self.ctx.discord.http()?;
+
+        let mut watches_by_clan: BTreeMap<String, Vec<ClanWatch>> = BTreeMap::new();
+        for watch in db::wolvesville::clan_watch::get_all_clan_watches(&self.ctx.db_pool).await? {
+            watches_by_clan.entry(watch.clan_id.clone()).or_default().push(watch);
+        }
+
+        for (clan_id, watches) in &watches_by_clan {
+            if let Err(e) = self.refresh(&http, clan_id, watches).await {
+                warn!("Failed to refresh watched clan {}: {:?}", clan_id, e);
+            }
+            tokio::time::sleep(REQUEST_SPACING).await;
+        }
+
+        info!("Refreshed {} watched clans", watches_by_clan.len());
+        Ok(())
+    }
+}
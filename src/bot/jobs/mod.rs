@@ -0,0 +1,5 @@
+pub mod reminder;
+pub mod announcement;
+pub mod player_watch;
+pub mod sp_sampler;
+pub mod clan_watch;
@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+use crate::utils::logger::{info, warn};
+use poise::serenity_prelude as serenity;
+use crate::bot::background::JobContext;
+use crate::bot::core::structs::CustomColor;
+use crate::db;
+use crate::db::wolvesville::watch::PlayerWatch;
+use crate::utils::apicallers::wolvesville;
+use crate::utils::apicallers::wolvesville::models::WolvesvillePlayer;
+use crate::utils::scheduler::Job;
+use crate::utils::wolvesville::{diff_players, PlayerChange, PlayerSnapshot};
+
+/// How often watched players are refreshed.
+pub const PLAYER_WATCH_INTERVAL: chrono::Duration = chrono::Duration::minutes(30);
+/// Pause between two API requests, to stay well within the Wolvesville rate limit.
+const REQUEST_SPACING: Duration = Duration::from_millis(500);
+
+/// Refreshes every watched player and posts what changed to the guilds watching them.
+pub struct PlayerWatchJob {
+    ctx: JobContext,
+}
+
+impl PlayerWatchJob {
+    pub fn new(ctx: JobContext) -> Self {
+        Self { ctx }
+    }
+
+    async fn refresh(&self, http: &serenity::Http, player_id: &str, watches: &[PlayerWatch]) -> anyhow::Result<()> {
+        let old = db::wolvesville::player::get_player_by_id(&self.ctx.db_pool, player_id).await?;
+        let Some(new) = wolvesville::get_wolvesville_player_by_id(&self.ctx.wolvesville_client, player_id).await? else {
+            warn!("Watched player {} was not found by the API", player_id);
+            return Ok(());
+        };
+        db::wolvesville::player::upsert_full_player(&self.ctx.db_pool, &new).await?;
+
+        for watch in watches {
+            // Watches stored before the snapshot was kept start out from the cached player
+            let mut notified = watch.notified.clone()
+                .unwrap_or_else(|| PlayerSnapshot::from(old.as_ref().unwrap_or(&new)));
+
+            let changes = diff_players(&notified, &new, watch.sp_threshold);
+            if !changes.is_empty() {
+                let embed = self.changes_embed(&new, &changes, &watch.language_code).await;
+                let channel_id = serenity::ChannelId::new(watch.channel_id.parse()?);
+                if let Err(e) = channel_id.send_message(http, serenity::CreateMessage::default().embed(embed)).await {
+                    warn!("Failed to post changes of player {} to channel {} of guild {}: {}", player_id, watch.channel_id, watch.guild_id, e);
+                    continue;
+                }
+            }
+
+            let previous = notified.clone();
+            notified.update(&new, &changes);
+            if watch.notified.is_none() || notified != previous {
+                db::wolvesville::watch::set_notified_snapshot(&self.ctx.db_pool, &watch.guild_id, player_id, &notified).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn changes_embed(&self, player: &WolvesvillePlayer, changes: &[PlayerChange], language: &str) -> serenity::CreateEmbed {
+        let mut lines = Vec::new();
+        for change in changes {
+            lines.push(match change {
+                PlayerChange::Username { from, to } => t!("jobs.player_watch.username", from = from, to = to, locale = language),
+                PlayerChange::Level { from, to } => t!("jobs.player_watch.level", from = from, to = to, locale = language),
+                PlayerChange::JoinedClan { clan_id } => t!("jobs.player_watch.joined_clan", clan = self.clan_name(clan_id).await, locale = language),
+                PlayerChange::LeftClan { clan_id } => t!("jobs.player_watch.left_clan", clan = self.clan_name(clan_id).await, locale = language),
+                PlayerChange::SwitchedClan { from, to } => t!("jobs.player_watch.switched_clan", from = self.clan_name(from).await, to = self.clan_name(to).await, locale = language),
+                PlayerChange::RankedSkill { from, to } => t!("jobs.player_watch.ranked_skill", from = from, to = to, delta = format!("{:+}", to - from), locale = language),
+            });
+        }
+
+        serenity::CreateEmbed::default()
+            .title(t!("jobs.player_watch.title", username = player.username, locale = language))
+            .description(lines.join("\n"))
+            .color(CustomColor::CYAN)
+    }
+
+    /// Name of a clan from the cache, or from the API if it isn't cached. Falls back to the ID.
+    async fn clan_name(&self, clan_id: &str) -> String {
+        if let Ok(Some(clan)) = db::wolvesville::clan::get_wolvesville_clan_info_by_id(&self.ctx.db_pool, clan_id).await {
+            return format!("**{}**", clan.name);
+        }
+        match wolvesville::get_wolvesville_clan_info_by_id(&self.ctx.wolvesville_client, clan_id).await {
+            Ok(Some(clan)) => format!("**{}**", clan.name),
+            _ => format!("`{}`", clan_id),
+        }
+    }
+}
+
+impl Job for PlayerWatchJob {
+    const NAME: &'static str = "wolvesville_player_watch";
+
+    type Args = ();
+
+    async fn run(&self, _args: Self::Args) -> anyhow::Result<()> {
+        let http = self.ctx.discord.http()?;
+
+        let mut watches_by_player: BTreeMap<String, Vec<PlayerWatch>> = BTreeMap::new();
+        for watch in db::wolvesville::watch::get_all_player_watches(&self.ctx.db_pool).await? {
+            watches_by_player.entry(watch.player_id.clone()).or_default().push(watch);
+        }
+
+        for (player_id, watches) in &watches_by_player {
+            if let Err(e) = self.refresh(&http, player_id, watches).await {
+                warn!("Failed to refresh watched player {}: {:?}", player_id, e);
+            }
+            tokio::time::sleep(REQUEST_SPACING).await;
+        }
+
+        info!("Refreshed {} watched players", watches_by_player.len());
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use crate::utils::logger::warn;
+use poise::serenity_prelude::{self as serenity, Mentionable};
+use serde::{Deserialize, Serialize};
+use crate::bot::background::DiscordHandle;
+use crate::bot::core::structs::CustomColor;
+use crate::utils::scheduler::Job;
+use crate::utils::time::get_relative_timestamp;
+
+pub const REMINDER_TEXT_LIMIT: usize = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ReminderArgs {
+    pub user_id: u64,
+    /// Channel the reminder was set in. `None` delivers it by DM.
+    pub channel_id: Option<u64>,
+    pub text: String,
+    /// Language of the user when the reminder was set, used for the delivered message.
+    pub language: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Delivers a reminder set with the `remind` command. Scheduled as a one-off job, so it is removed after running.
+pub struct ReminderJob {
+    discord: DiscordHandle,
+}
+
+impl ReminderJob {
+    pub fn new(discord: DiscordHandle) -> Self {
+        Self { discord }
+    }
+}
+
+impl Job for ReminderJob {
+    const NAME: &'static str = "reminder";
+
+    type Args = ReminderArgs;
+
+    fn validate(args: &Self::Args) -> anyhow::Result<()> {
+        if args.text.trim().is_empty() {
+            return Err(anyhow::anyhow!("Reminder text is empty"));
+        }
+        if args.text.chars().count() > REMINDER_TEXT_LIMIT {
+            return Err(anyhow::anyhow!("Reminder text is longer than {} characters", REMINDER_TEXT_LIMIT));
+        }
+        Ok(())
+    }
+
+    async fn run(&self, args: Self::Args) -> anyhow::Result<()> {
+        let http = self.discord.http()?;
+        let user_id = serenity::UserId::new(args.user_id);
+
+        let embed = serenity::CreateEmbed::default()
+            .title(t!("jobs.reminder.title", locale = &args.language))
+            .description(&args.text)
+            .color(CustomColor::CYAN)
+            .field("\u{200B}", t!("jobs.reminder.set", when = get_relative_timestamp(&args.created_at.timestamp()), locale = &args.language), false);
+
+        if let Some(channel_id) = args.channel_id {
+            let message = serenity::CreateMessage::default()
+                .content(user_id.mention().to_string())
+                .embed(embed.clone())
+                .allowed_mentions(serenity::CreateAllowedMentions::new().users(vec![user_id]));
+
+            match serenity::ChannelId::new(channel_id).send_message(&http, message).await {
+                Ok(_) => return Ok(()),
+                Err(e) => warn!("Failed to deliver reminder to channel {}, sending it by DM instead: {}", channel_id, e),
+            }
+        }
+
+        let dm = user_id.create_dm_channel(&http).await?;
+        dm.send_message(&http, serenity::CreateMessage::default().embed(embed)).await?;
+        Ok(())
+    }
+}
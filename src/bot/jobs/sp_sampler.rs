@@ -0,0 +1,64 @@
+use std::time::Duration;
+use crate::utils::logger::{info, warn};
+use crate::bot::background::JobContext;
+use crate::db;
+use crate::utils::apicallers::wolvesville;
+use crate::utils::scheduler::Job;
+
+/// How often the ranked SP of tracked players is sampled.
+pub const SP_SAMPLE_INTERVAL: chrono::Duration = chrono::Duration::hours(6);
+/// How long a search keeps a player in the sample.
+const SEARCH_RETENTION: chrono::Duration = chrono::Duration::days(14);
+/// API requests a single run may spend, unless `WOV_SP_SAMPLE_BUDGET` says otherwise.
+pub const DEFAULT_SP_SAMPLE_BUDGET: usize = 100;
+/// Pause between two API requests, to stay well within the Wolvesville rate limit.
+const REQUEST_SPACING: Duration = Duration::from_millis(500);
+
+/// Records the ranked SP of watched and recently searched players, so the SP plot has more than
+/// the points left behind by searches.
+pub struct SpSamplerJob {
+    ctx: JobContext,
+    budget: usize,
+}
+
+impl SpSamplerJob {
+    pub fn new(ctx: JobContext, budget: usize) -> Self {
+        Self { ctx, budget }
+    }
+}
+
+impl Job for SpSamplerJob {
+    const NAME: &'static str = "wolvesville_sp_sampler";
+
+    type Args = ();
+
+    async fn run(&self, _args: Self::Args) -> anyhow::Result<()> {
+        let now = self.ctx.clock.now();
+        // Players that got a record from a search or the watch job in the meantime don't need another one yet
+        let candidates = db::wolvesville::player::get_sp_sample_candidates(
+            &self.ctx.db_pool,
+            now - SEARCH_RETENTION,
+            now - SP_SAMPLE_INTERVAL / 2,
+            self.budget,
+        ).await?;
+
+        let mut sampled = 0;
+        for player_id in &candidates {
+            match wolvesville::get_wolvesville_player_by_id(&self.ctx.wolvesville_client, player_id).await {
+                Ok(player) => {
+                    let skill = player.and_then(|player| player.ranked_season_skill);
+                    if let Err(e) = db::wolvesville::player::record_sp_sample(&self.ctx.db_pool, player_id, skill, self.ctx.clock.now()).await {
+                        warn!("Failed to store the SP sample of player {}: {:?}", player_id, e);
+                    } else if skill.is_some() {
+                        sampled += 1;
+                    }
+                }
+                Err(e) => warn!("Failed to sample the SP of player {}: {:?}", player_id, e),
+            }
+            tokio::time::sleep(REQUEST_SPACING).await;
+        }
+
+        info!("Sampled the SP of {} out of {} players", sampled, candidates.len());
+        Ok(())
+    }
+}
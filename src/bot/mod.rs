@@ -2,18 +2,23 @@ mod handlers;
 mod commands;
 pub mod core;
 pub mod background;
+mod jobs;
+pub mod server;
 
 use poise::serenity_prelude as serenity;
 use std::{num::NonZeroUsize, sync::Arc};
 use tokio::sync::Mutex;
 use ::serenity::all::ActivityData;
 use lru::LruCache;
-use logfather::error;
+use crate::utils::logger::{error, warn};
 use sqlx::SqlitePool;
-use crate::{db::{get_pool, prefixes::get_prefix}, utils::apicallers::wolvesville};
-use core::{structs::{Data, Error, PartialContext}, constants::DEFAULT_PREFIX};
+use tokio_util::sync::CancellationToken;
+use crate::{db::{self, get_pool, prefixes::get_prefix}, utils::apicallers::wolvesville};
+use core::{structs::{CacheMetrics, Data, Error, MetricsManager, PartialContext}, constants::DEFAULT_PREFIX};
 use commands::*;
 use crate::utils::scheduler::{JobRegistry, Scheduler};
+use crate::utils::time::system_clock;
+use background::{DiscordHandle, JobContext};
 
 /// This function is used to determine the prefix on a command call for each separate server/user.
 /// It first checks the cache, if the prefix is not found in the cache, it queries the database, or the default '.' prefix if it's not found in the database either.
@@ -22,9 +27,10 @@ async fn determine_prefix(ctx: PartialContext<'_>) -> Result<Option<String>, Err
     let guild_id = ctx.guild_id.map(|id| id.to_string());
     let user_id = ctx.author.id.to_string();
     
+    let cache_metrics = &ctx.data.metrics.cache_metrics;
     let mut prefix_cache = ctx.data.prefix_cache.lock().await;
     
-    if let Some(user_prefix) = prefix_cache.get(&user_id) {
+    if let Some(user_prefix) = cache_metrics.record(CacheMetrics::PREFIX, prefix_cache.get(&user_id)) {
         return Ok(Some(user_prefix.clone()));
     }
 
@@ -34,7 +40,7 @@ async fn determine_prefix(ctx: PartialContext<'_>) -> Result<Option<String>, Err
     }
     
     if let Some(guild_id) = guild_id {
-        if let Some(guild_prefix) = prefix_cache.get(&guild_id) {
+        if let Some(guild_prefix) = cache_metrics.record(CacheMetrics::PREFIX, prefix_cache.get(&guild_id)) {
             return Ok(Some(guild_prefix.clone()));
         }
 
@@ -49,52 +55,113 @@ async fn determine_prefix(ctx: PartialContext<'_>) -> Result<Option<String>, Err
 
 pub struct Bot {
     client: serenity::Client,
-    job_registry: Arc<JobRegistry>,
     scheduler: Scheduler,
+    db_pool: SqlitePool,
+    metrics: Arc<MetricsManager>,
+    shutdown: CancellationToken,
 }
 
 impl Bot {
-    pub async fn new(token: String) -> Self {
-        let (client, pool) = build_client(token).await;
-        let job_registry = Arc::new(JobRegistry::new());
+    pub async fn new(token: String, metrics: Arc<MetricsManager>) -> Self {
+        // The schema has to be in place before the scheduler loads its jobs
+        db::create_db().await;
+
+        let pool = Arc::new(get_pool().await.map_err(|e| {
+            error!("Failed to get database pool: {}", e);
+            serenity::Error::Other("Failed to get database pool")
+        }).expect("Failed to get database pool"));
+
+        let wolvesville_client = wolvesville::initialize_client();
+        let job_context = JobContext {
+            discord: DiscordHandle::default(),
+            db_pool: (*pool).clone(),
+            wolvesville_client: wolvesville_client.clone(),
+            clock: system_clock(),
+        };
+        let mut job_registry = JobRegistry::new();
+        background::register_jobs(&mut job_registry, &job_context);
+        let mut scheduler = Scheduler::new(pool.clone(), Arc::new(job_registry)).with_clock(job_context.clock.clone());
+        if let Some(max_concurrent_jobs) = std::env::var("SCHEDULER_MAX_CONCURRENT_JOBS").ok().and_then(|v| v.parse().ok()) {
+            scheduler = scheduler.with_max_concurrent_jobs(max_concurrent_jobs);
+        }
+        if let Some(lease_seconds) = std::env::var("SCHEDULER_LEASE_SECONDS").ok().and_then(|v| v.parse().ok()) {
+            scheduler = scheduler.with_lease_duration(std::time::Duration::from_secs(lease_seconds));
+        }
+
+        let db_pool = (*pool).clone();
+        let client = build_client(token, pool, wolvesville_client, scheduler.clone(), metrics.clone()).await.expect("Failed to create Serenity client");
+        job_context.discord.set(client.http.clone());
         Bot { 
-            client: client.expect("Failed to create Serenity client"),
-            job_registry: job_registry.clone(),
-            scheduler: Scheduler::new(pool, job_registry),
+            client,
+            scheduler,
+            db_pool,
+            metrics,
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// What the readiness endpoint of the metrics server checks.
+    pub fn health_state(&self) -> server::HealthState {
+        server::HealthState {
+            db_pool: self.db_pool.clone(),
+            shard_manager: self.client.shard_manager.clone(),
+            scheduler: self.scheduler.clone(),
         }
     }
 
     pub async fn start(&mut self) {
+        background::spawn_scheduler(self.scheduler.clone(), self.shutdown.clone());
+        server::spawn_gateway_metrics_updater(
+            self.metrics.clone(),
+            self.client.shard_manager.clone(),
+            self.client.cache.clone(),
+            self.client.data.clone(),
+            self.shutdown.clone(),
+        );
+
         if let Err(why) = self.client.start().await {
             error!("An error occurred while running the client: {:?}", why);
         }
+
+        self.shutdown.cancel();
     }
 }
 
-async fn build_client(token: String) -> (Result<serenity::Client, serenity::Error>, Arc<SqlitePool>) {
+/// Every command the bot registers.
+pub fn commands() -> Vec<poise::Command<Data, Error>> {
+    vec![
+        informative::help::help(),
+        informative::ping::ping(),
+        informative::userinfo::user_info(),
+        administrative::prefix(),
+        administrative::language(),
+        administrative::announce::announce(),
+        directive::preferences(),
+        wov::wolvesville(),
+        utility::reminders::remind(),
+        utility::reminders::reminders(),
+        owner::jobs(),
+        owner::log_level(),
+    ]
+}
+
+async fn build_client(token: String, pool: Arc<SqlitePool>, wolvesville_client: Arc<reqwest::Client>, scheduler: Scheduler, metrics: Arc<MetricsManager>) -> Result<serenity::Client, serenity::Error> {
     let intents = serenity::GatewayIntents::non_privileged()
         | serenity::GatewayIntents::MESSAGE_CONTENT 
         | serenity::GatewayIntents::GUILD_MESSAGES
         | serenity::GatewayIntents::GUILD_MEMBERS
         | serenity::GatewayIntents::GUILD_PRESENCES;
-    
-    let pool = Arc::new(get_pool().await.map_err(|e| {
-        error!("Failed to get database pool: {}", e);
-        serenity::Error::Other("Failed to get database pool")
-    }).expect("Failed to get database pool"));
-    
-    let pool_return = pool.clone();
 
+    let mut commands = commands();
+    for problem in localization::translation_problems(&commands) {
+        warn!("Slash command translations: {}", problem);
+    }
+    localization::localize_commands(&mut commands);
+
+    let handler = handlers::Handler::new(metrics.clone());
     let framework = poise::Framework::builder()
         .options(poise::FrameworkOptions {
-            commands: vec![
-                informative::help::help(),
-                informative::ping::ping(),
-                informative::userinfo::user_info(),
-                administrative::prefix(),
-                directive::preferences(),
-                wov::wolvesville(),
-            ],
+            commands,
             prefix_options: poise::PrefixFrameworkOptions {
                 prefix: Some(DEFAULT_PREFIX.to_string()),
                 dynamic_prefix: Some(|ctx| Box::pin(determine_prefix(ctx))),
@@ -103,20 +170,32 @@ async fn build_client(token: String) -> (Result<serenity::Client, serenity::Erro
             on_error: |error| {
                 Box::pin(handlers::on_error(error))
             },
+            pre_command: |ctx| Box::pin(handlers::pre_command(ctx)),
+            post_command: |ctx| Box::pin(handlers::post_command(ctx)),
             ..Default::default()
         })
         .setup(|ctx, _ready, framework| {
             Box::pin(async move {
                 poise::builtins::register_globally(ctx, &framework.options().commands).await?;
                 let data = Data {
-                    db_pool: pool.clone(),
+                    db_pool: (*pool).clone(),
                     prefix_cache: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(100).unwrap()))),
                     language_cache: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(100).unwrap()))),
                     wolvesville_player_refresh_cache: Arc::new(Mutex::new(LruCache::new(NonZeroUsize::new(20).unwrap()))),
-                    wolvesville_client: wolvesville::initialize_client(),
+                    wolvesville_client: wolvesville_client.clone(),
+                    scheduler: scheduler.clone(),
+                    clock: scheduler.clock(),
+                    metrics: metrics.clone(),
+                    incidents: Arc::new(handlers::incidents::IncidentReporter::from_env()),
                     custom_emojis: ctx.get_application_emojis().await.unwrap().iter().map(|emoji| (emoji.name.clone(), emoji.clone())).collect(),
                 };
 
+                tokio::spawn(handlers::incidents::deliver_crash_reports(
+                    ctx.http.clone(),
+                    data.incidents.clone(),
+                    framework.options().owners.clone(),
+                ));
+
                 // I also need to insert the data into the context of serenity
                 let mut data_lock = ctx.data.write().await;
                 data_lock.insert::<Data>(Arc::new(data.clone()));
@@ -126,12 +205,10 @@ async fn build_client(token: String) -> (Result<serenity::Client, serenity::Erro
         })
         .build();
 
-    let client = serenity::ClientBuilder::new(token, intents)
-        .framework(framework)
+    serenity::ClientBuilder::new(token, intents)
+        .framework(handlers::LogContextFramework(framework))
         .status(serenity::OnlineStatus::Online)
         .activity(ActivityData::listening("voices in my RAM"))
-        .event_handler(handlers::Handler)
-        .await;
-
-    (client, pool_return)
+        .event_handler(handler)
+        .await
 }
@@ -0,0 +1,205 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+use axum::{Json, Router, middleware, routing::get};
+use axum::body::Body;
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use crate::utils::logger::error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use crate::bot::server::config::constant_time_eq;
+use crate::db;
+use crate::utils::apicallers::wolvesville::models::WolvesvillePlayer;
+
+pub const DEFAULT_PER_PAGE: u32 = 50;
+pub const MAX_PER_PAGE: u32 = 200;
+/// Header carrying the API key.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// Read-only access to the cached Wolvesville data.
+#[derive(Clone)]
+pub struct ApiState {
+    pub db_pool: SqlitePool,
+    pub keys: Arc<Vec<String>>,
+}
+
+pub fn router(state: ApiState) -> Router {
+    Router::new()
+        .route("/players", get(search_player))
+        .route("/players/{id}", get(player))
+        .route("/players/{id}/sp", get(sp_history))
+        .route("/clans", get(search_clans))
+        .route("/clans/{id}", get(clan))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_api_key))
+        .with_state(state)
+}
+
+pub enum ApiError {
+    Unauthorized,
+    BadRequest(&'static str),
+    NotFound,
+    Internal(anyhow::Error),
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        ApiError::Internal(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::Unauthorized => (StatusCode::UNAUTHORIZED, "missing or invalid API key"),
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound => (StatusCode::NOT_FOUND, "not found"),
+            ApiError::Internal(e) => {
+                error!("REST API request failed: {:?}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+            }
+        };
+        (status, Json(json!({ "error": message }))).into_response()
+    }
+}
+
+async fn require_api_key(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let key = request.headers().get(API_KEY_HEADER).map(|key| key.as_bytes()).unwrap_or_default();
+    // Every key is compared, so the time taken doesn't tell which one came close
+    let authorized = state.keys.iter().fold(false, |found, expected| found | constant_time_eq(key, expected.as_bytes()));
+    if !authorized {
+        return ApiError::Unauthorized.into_response();
+    }
+    next.run(request).await
+}
+
+#[derive(Deserialize)]
+struct PageQuery {
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+/// A 1-based page, with `per_page` clamped to `1..=MAX_PER_PAGE`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pagination {
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl Pagination {
+    pub fn new(page: Option<u32>, per_page: Option<u32>) -> Self {
+        Self {
+            page: page.unwrap_or(1).max(1),
+            per_page: per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE),
+        }
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.per_page as i64
+    }
+
+    pub fn offset(&self) -> i64 {
+        (self.page as i64 - 1) * self.per_page as i64
+    }
+
+    fn wrap<T: Serialize>(&self, data: Vec<T>, total: i64) -> Value {
+        json!({
+            "data": data,
+            "page": self.page,
+            "per_page": self.per_page,
+            "total": total,
+        })
+    }
+}
+
+/// Weak ETag of a response body. The cache only changes when the bot refreshes it,
+/// so hashing the body is cheaper than tracking versions per row.
+pub fn etag(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    body.hash(&mut hasher);
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+/// Whether an `If-None-Match` header value covers `etag`.
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let bare = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    if_none_match.split(',').any(|tag| tag.trim() == "*" || bare(tag) == bare(etag))
+}
+
+/// Serializes `body`, answering with `304 Not Modified` when the client already has it.
+fn respond(headers: &HeaderMap, body: Value) -> Response {
+    let body = body.to_string();
+    let etag = etag(body.as_bytes());
+
+    let not_modified = headers.get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, &etag));
+    let builder = Response::builder().header(header::ETAG, &etag);
+    if not_modified {
+        return builder.status(StatusCode::NOT_MODIFIED).body(Body::empty()).unwrap();
+    }
+
+    builder
+        .header(header::CONTENT_TYPE, "application/json")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn player_with_history(pool: &SqlitePool, player: WolvesvillePlayer) -> Result<Value, ApiError> {
+    let usernames = db::wolvesville::player::get_username_history(pool, &player.id).await?;
+    Ok(json!({ "player": player, "usernames": usernames }))
+}
+
+async fn player(State(state): State<ApiState>, Path(id): Path<String>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let player = db::wolvesville::player::get_player_by_id(&state.db_pool, &id).await?.ok_or(ApiError::NotFound)?;
+    Ok(respond(&headers, player_with_history(&state.db_pool, player).await?))
+}
+
+#[derive(Deserialize)]
+struct NameQuery {
+    name: Option<String>,
+}
+
+/// A player by their current username, or by a previous one if nobody uses it now.
+async fn search_player(State(state): State<ApiState>, Query(query): Query<NameQuery>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let name = query.name.filter(|name| !name.trim().is_empty()).ok_or(ApiError::BadRequest("the `name` parameter is required"))?;
+    let name = name.trim();
+
+    let player = match db::wolvesville::player::get_player_by_username(&state.db_pool, name).await? {
+        Some(player) => player,
+        None => db::wolvesville::player::get_player_by_previous_username(&state.db_pool, name).await?.ok_or(ApiError::NotFound)?,
+    };
+    Ok(respond(&headers, player_with_history(&state.db_pool, player).await?))
+}
+
+async fn sp_history(State(state): State<ApiState>, Path(id): Path<String>, Query(query): Query<PageQuery>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let pagination = Pagination::new(query.page, query.per_page);
+    if db::wolvesville::player::get_player_by_id(&state.db_pool, &id).await?.is_none() {
+        return Err(ApiError::NotFound);
+    }
+
+    let (records, total) = db::wolvesville::player::get_sp_records_page(&state.db_pool, &id, pagination.limit(), pagination.offset()).await?;
+    Ok(respond(&headers, pagination.wrap(records, total)))
+}
+
+async fn clan(State(state): State<ApiState>, Path(id): Path<String>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let clan = db::wolvesville::clan::get_wolvesville_clan_info_by_id(&state.db_pool, &id).await?.ok_or(ApiError::NotFound)?;
+    Ok(respond(&headers, json!(clan)))
+}
+
+#[derive(Deserialize)]
+struct ClanSearchQuery {
+    name: Option<String>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+}
+
+async fn search_clans(State(state): State<ApiState>, Query(query): Query<ClanSearchQuery>, headers: HeaderMap) -> Result<Response, ApiError> {
+    let pagination = Pagination::new(query.page, query.per_page);
+    let name = query.name.unwrap_or_default();
+
+    let (clans, total) = db::wolvesville::clan::search_wolvesville_clans_page(&state.db_pool, name.trim(), pagination.limit(), pagination.offset()).await?;
+    Ok(respond(&headers, pagination.wrap(clans, total)))
+}
@@ -0,0 +1,117 @@
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use anyhow::{anyhow, bail};
+use axum::http::HeaderValue;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+
+const DEFAULT_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
+const DEFAULT_PORT: u16 = 4020;
+const DEFAULT_PATH: &str = "/metrics";
+/// Paths of the probes, which the metrics path must not shadow.
+pub const HEALTH_PATHS: [&str; 2] = ["/healthz", "/readyz"];
+/// Where the REST API is nested.
+pub const API_PATH: &str = "/api/v1";
+
+/// Where and how the metrics server listens. The defaults match `prometheus.yaml`.
+///
+/// Read from `METRICS_ENABLED`, `METRICS_ADDRESS`, `METRICS_PORT`, `METRICS_PATH`, and either
+/// `METRICS_BEARER_TOKEN` or `METRICS_BASIC_AUTH_USERNAME` with `METRICS_BASIC_AUTH_PASSWORD`.
+/// The REST API is served on the same address once `API_KEYS` holds at least one key, whether or not the metrics are enabled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricsServerConfig {
+    /// Whether the metrics path is served, the API has its own switch in `api_keys`.
+    pub enabled: bool,
+    pub address: SocketAddr,
+    pub path: String,
+    /// Protects the metrics path. The probes stay open, since orchestrators usually can't authenticate.
+    pub auth: Option<MetricsAuth>,
+    /// Keys accepted by the REST API, comma separated in `API_KEYS`. The API is off without any.
+    pub api_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricsAuth {
+    Bearer(String),
+    Basic { username: String, password: String },
+}
+
+impl MetricsServerConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let lookup = |key: &str| lookup(key).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+        let enabled = match lookup("METRICS_ENABLED").map(|value| value.to_lowercase()).as_deref() {
+            None | Some("true" | "1" | "yes" | "on") => true,
+            Some("false" | "0" | "no" | "off") => false,
+            Some(other) => bail!("METRICS_ENABLED must be true or false, got `{}`", other),
+        };
+
+        let ip = match lookup("METRICS_ADDRESS") {
+            Some(address) => address.parse().map_err(|e| anyhow!("METRICS_ADDRESS `{}` is not an IP address: {}", address, e))?,
+            None => DEFAULT_ADDRESS,
+        };
+        let port = match lookup("METRICS_PORT") {
+            Some(port) => port.parse().map_err(|e| anyhow!("METRICS_PORT `{}` is not a port: {}", port, e))?,
+            None => DEFAULT_PORT,
+        };
+
+        let path = lookup("METRICS_PATH").unwrap_or(DEFAULT_PATH.to_string());
+        let path = if path.starts_with('/') { path } else { format!("/{}", path) };
+        if path == "/" || HEALTH_PATHS.contains(&path.as_str()) || path.starts_with(API_PATH) {
+            bail!("METRICS_PATH can't be `{}`", path);
+        }
+
+        let bearer = lookup("METRICS_BEARER_TOKEN");
+        let username = lookup("METRICS_BASIC_AUTH_USERNAME");
+        let password = lookup("METRICS_BASIC_AUTH_PASSWORD");
+        let auth = match (bearer, username, password) {
+            (None, None, None) => None,
+            (Some(token), None, None) => Some(MetricsAuth::Bearer(token)),
+            (None, Some(username), Some(password)) => Some(MetricsAuth::Basic { username, password }),
+            (Some(_), _, _) => bail!("Set either METRICS_BEARER_TOKEN or basic auth credentials, not both"),
+            (None, _, _) => bail!("METRICS_BASIC_AUTH_USERNAME and METRICS_BASIC_AUTH_PASSWORD have to be set together"),
+        };
+
+        let api_keys = lookup("API_KEYS")
+            .map(|keys| keys.split(',').map(str::trim).filter(|key| !key.is_empty()).map(str::to_string).collect())
+            .unwrap_or_default();
+
+        Ok(Self {
+            enabled,
+            address: SocketAddr::new(ip, port),
+            path,
+            auth,
+            api_keys,
+        })
+    }
+}
+
+impl MetricsAuth {
+    /// Whether the `Authorization` header of a request carries these credentials.
+    pub fn authorizes(&self, header: Option<&HeaderValue>) -> bool {
+        let Some(header) = header.and_then(|header| header.to_str().ok()) else {
+            return false;
+        };
+        let expected = match self {
+            MetricsAuth::Bearer(token) => format!("Bearer {}", token),
+            MetricsAuth::Basic { username, password } => format!("Basic {}", STANDARD.encode(format!("{}:{}", username, password))),
+        };
+        constant_time_eq(header.as_bytes(), expected.as_bytes())
+    }
+
+    /// Value of the `WWW-Authenticate` header sent along with a rejection.
+    pub fn challenge(&self) -> &'static str {
+        match self {
+            MetricsAuth::Bearer(_) => "Bearer",
+            MetricsAuth::Basic { .. } => "Basic realm=\"metrics\"",
+        }
+    }
+}
+
+/// Compares without returning early, so the time taken doesn't tell how much of a guess was right.
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
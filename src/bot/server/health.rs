@@ -0,0 +1,55 @@
+use std::sync::Arc;
+use std::time::Duration;
+use axum::Json;
+use axum::extract::State;
+use axum::http::StatusCode;
+use poise::serenity_prelude as serenity;
+use serde_json::{json, Value};
+use sqlx::SqlitePool;
+use crate::utils::scheduler::Scheduler;
+
+/// How long the database gets to answer the readiness query.
+const DATABASE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// What the readiness probe looks at.
+#[derive(Clone)]
+pub struct HealthState {
+    pub db_pool: SqlitePool,
+    pub shard_manager: Arc<serenity::ShardManager>,
+    pub scheduler: Scheduler,
+}
+
+/// The process is up and serving requests.
+pub async fn healthz() -> (StatusCode, Json<Value>) {
+    (StatusCode::OK, Json(json!({ "status": "ok" })))
+}
+
+/// The bot can do its work: the database answers, every shard is connected and the scheduler loop is running.
+pub async fn readyz(State(state): State<HealthState>) -> (StatusCode, Json<Value>) {
+    let database = match tokio::time::timeout(DATABASE_TIMEOUT, sqlx::query("SELECT 1;").execute(&state.db_pool)).await {
+        Ok(Ok(_)) => json!({ "ok": true }),
+        Ok(Err(e)) => json!({ "ok": false, "error": e.to_string() }),
+        Err(_) => json!({ "ok": false, "error": "timed out" }),
+    };
+
+    let runners = state.shard_manager.runners.lock().await;
+    let shards: serde_json::Map<String, Value> = runners.iter()
+        .map(|(shard_id, runner)| (shard_id.to_string(), json!(runner.stage.to_string())))
+        .collect();
+    let gateway_ok = !runners.is_empty() && runners.values().all(|runner| runner.stage == serenity::ConnectionStage::Connected);
+    drop(runners);
+    let gateway = json!({ "ok": gateway_ok, "shards": shards });
+
+    let scheduler = json!({ "ok": state.scheduler.is_running(), "leader": state.scheduler.is_leader() });
+
+    let ready = [&database, &gateway, &scheduler].iter().all(|check| check["ok"] == true);
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "checks": {
+            "database": database,
+            "gateway": gateway,
+            "scheduler": scheduler,
+        },
+    })))
+}
@@ -1,38 +1,134 @@
-use std::net::SocketAddr;
 use std::sync::Arc;
 use axum::body::Body;
 use axum::{Router, routing::get, serve};
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
 use axum::response::Response;
-use logfather::info;
+use crate::utils::logger::{error, info};
 use sysinfo::Pid;
 use tokio::net::TcpListener;
-use crate::bot::core::structs::MetricsManager;
+use poise::serenity_prelude as serenity;
+use tokio_util::sync::CancellationToken;
+use crate::bot::core::structs::{Data, MetricsManager};
 
-pub async fn run_metrics_manager(metrics_manager: Arc<MetricsManager>, pid: u32) {
-    let updater = metrics_manager.clone();
+mod api;
+mod config;
+mod health;
+#[cfg(test)]
+mod tests;
+
+pub use config::MetricsServerConfig;
+pub use health::HealthState;
+use config::MetricsAuth;
+
+const GATEWAY_METRICS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(15);
+
+/// Samples the shard and cache gauges until `token` is cancelled. The LRU caches live in `Data`,
+/// which only exists once the framework has been set up, so they are skipped until then.
+pub fn spawn_gateway_metrics_updater(
+    metrics_manager: Arc<MetricsManager>,
+    shard_manager: Arc<serenity::ShardManager>,
+    cache: Arc<serenity::Cache>,
+    type_map: Arc<tokio::sync::RwLock<serenity::prelude::TypeMap>>,
+    token: CancellationToken,
+) {
     tokio::spawn(async move {
-        let mut system = sysinfo::System::new_all();
         loop {
-            updater.system_metrics.update(&mut system, Pid::from(pid as usize));
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            metrics_manager.gateway_metrics.update(&shard_manager, &cache).await;
+            let data = type_map.read().await.get::<Data>().cloned();
+            if let Some(data) = data {
+                metrics_manager.cache_metrics.update(&data).await;
+            }
+
+            tokio::select! {
+                _ = token.cancelled() => break,
+                _ = tokio::time::sleep(GATEWAY_METRICS_INTERVAL) => {}
+            }
         }
     });
+}
+
+#[derive(Clone)]
+struct MetricsState {
+    metrics_manager: Arc<MetricsManager>,
+    auth: Option<Arc<MetricsAuth>>,
+}
 
-    let addr = SocketAddr::from(([0, 0, 0, 0], 4020));
-    info!("Metrics server listening on {}", addr);
-    let router = Router::new()
-        .route("/metrics", get(move || {
-            let metrics_manager = metrics_manager.clone();
-            async move {
-                let metrics = metrics_manager.system_metrics.render_metrics();
-                Response::builder()
-                    .header("Content-Type", "text/plain; charset=utf-8; version=0.0.4")
-                    .body(Body::from(metrics))
-                    .unwrap()
+/// Serves the metrics for Prometheus unless they're disabled, and the REST API under `/api/v1` when keys are configured,
+/// along with `/healthz` for liveness and `/readyz` for readiness probes, as configured by `MetricsServerConfig`.
+/// Nothing is served when both are off.
+pub async fn run_metrics_manager(metrics_manager: Arc<MetricsManager>, health: HealthState, pid: u32) {
+    let config = match MetricsServerConfig::from_env() {
+        Ok(config) => config,
+        Err(e) => {
+            error!("Failed to start the metrics server, the configuration is invalid: {}", e);
+            return;
+        }
+    };
+    let api_enabled = !config.api_keys.is_empty();
+    if !config.enabled && !api_enabled {
+        info!("Metrics server is disabled");
+        return;
+    }
+
+    let mut router = Router::new()
+        .route("/healthz", get(health::healthz))
+        .route("/readyz", get(health::readyz))
+        .with_state(health.clone());
+
+    if config.enabled {
+        let updater = metrics_manager.clone();
+        tokio::spawn(async move {
+            let mut system = sysinfo::System::new_all();
+            loop {
+                updater.system_metrics.update(&mut system, Pid::from(pid as usize));
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
             }
-        }));
+        });
+
+        let metrics_state = MetricsState {
+            metrics_manager,
+            auth: config.auth.map(Arc::new),
+        };
+        router = router.merge(Router::new()
+            .route(&config.path, get(render_metrics))
+            .with_state(metrics_state));
+        info!("Metrics served under {}", config.path);
+    }
+    if api_enabled {
+        let api_state = api::ApiState {
+            db_pool: health.db_pool,
+            keys: Arc::new(config.api_keys),
+        };
+        router = router.nest(config::API_PATH, api::router(api_state));
+        info!("REST API enabled under {}", config::API_PATH);
+    }
+
+    let listener = match TcpListener::bind(config.address).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to start the metrics server, could not bind to {}: {}", config.address, e);
+            return;
+        }
+    };
+    info!("Metrics server listening on {}", config.address);
+
+    if let Err(e) = serve(listener, router).await {
+        error!("Metrics server stopped: {}", e);
+    }
+}
 
-    let listener = TcpListener::bind(addr).await.unwrap();
+async fn render_metrics(State(state): State<MetricsState>, headers: HeaderMap) -> Response {
+    if let Some(auth) = &state.auth && !auth.authorizes(headers.get(header::AUTHORIZATION)) {
+        return Response::builder()
+            .status(StatusCode::UNAUTHORIZED)
+            .header(header::WWW_AUTHENTICATE, auth.challenge())
+            .body(Body::empty())
+            .unwrap();
+    }
 
-    serve(listener, router).await.unwrap();
-}
\ No newline at end of file
+    Response::builder()
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8; version=0.0.4")
+        .body(Body::from(state.metrics_manager.render_metrics()))
+        .unwrap()
+}
@@ -0,0 +1,237 @@
+use std::collections::HashMap;
+use axum::http::HeaderValue;
+use sqlx::sqlite::SqlitePoolOptions;
+use crate::bot::server::api::{etag, etag_matches, Pagination, DEFAULT_PER_PAGE, MAX_PER_PAGE};
+use crate::bot::server::config::{MetricsAuth, MetricsServerConfig};
+use crate::db::initialize_schema;
+use crate::db::wolvesville::{clan, player};
+
+fn config_from(vars: &[(&str, &str)]) -> anyhow::Result<MetricsServerConfig> {
+    let vars: HashMap<String, String> = vars.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+    MetricsServerConfig::from_lookup(|key| vars.get(key).cloned())
+}
+
+#[test]
+fn test_defaults_match_prometheus_config() {
+    let config = config_from(&[]).unwrap();
+    assert!(config.enabled);
+    assert_eq!(config.address, "0.0.0.0:4020".parse().unwrap());
+    assert_eq!(config.path, "/metrics");
+    assert_eq!(config.auth, None);
+}
+
+#[test]
+fn test_overrides() {
+    let config = config_from(&[
+        ("METRICS_ENABLED", "off"),
+        ("METRICS_ADDRESS", "127.0.0.1"),
+        ("METRICS_PORT", "9100"),
+        ("METRICS_PATH", "internal/metrics"),
+        ("METRICS_BEARER_TOKEN", "secret"),
+    ]).unwrap();
+    assert!(!config.enabled);
+    assert_eq!(config.address, "127.0.0.1:9100".parse().unwrap());
+    assert_eq!(config.path, "/internal/metrics");
+    assert_eq!(config.auth, Some(MetricsAuth::Bearer("secret".to_string())));
+
+    assert_eq!(config_from(&[("METRICS_ADDRESS", "::1")]).unwrap().address, "[::1]:4020".parse().unwrap());
+}
+
+#[test]
+fn test_invalid_values_are_rejected() {
+    assert!(config_from(&[("METRICS_ENABLED", "maybe")]).is_err());
+    assert!(config_from(&[("METRICS_ADDRESS", "localhost")]).is_err());
+    assert!(config_from(&[("METRICS_PORT", "70000")]).is_err());
+    assert!(config_from(&[("METRICS_PATH", "/readyz")]).is_err());
+    assert!(config_from(&[("METRICS_BASIC_AUTH_USERNAME", "prometheus")]).is_err());
+    assert!(config_from(&[("METRICS_BEARER_TOKEN", "secret"), ("METRICS_BASIC_AUTH_USERNAME", "a"), ("METRICS_BASIC_AUTH_PASSWORD", "b")]).is_err());
+}
+
+#[test]
+fn test_auth_checks_the_authorization_header() {
+    let bearer = MetricsAuth::Bearer("secret".to_string());
+    assert!(bearer.authorizes(Some(&HeaderValue::from_static("Bearer secret"))));
+    assert!(!bearer.authorizes(Some(&HeaderValue::from_static("Bearer secreT"))));
+    assert!(!bearer.authorizes(None));
+
+    // "prometheus:hunter2" in base64
+    let basic = MetricsAuth::Basic { username: "prometheus".to_string(), password: "hunter2".to_string() };
+    assert!(basic.authorizes(Some(&HeaderValue::from_static("Basic cHJvbWV0aGV1czpodW50ZXIy"))));
+    assert!(!basic.authorizes(Some(&HeaderValue::from_static("Bearer secret"))));
+}
+
+#[test]
+fn test_api_keys() {
+    assert!(config_from(&[]).unwrap().api_keys.is_empty());
+    assert_eq!(config_from(&[("API_KEYS", "one, two,,")]).unwrap().api_keys, vec!["one", "two"]);
+    assert!(config_from(&[("METRICS_PATH", "/api/v1/metrics")]).is_err());
+}
+
+#[test]
+fn test_pagination_is_clamped() {
+    assert_eq!(Pagination::new(None, None), Pagination { page: 1, per_page: DEFAULT_PER_PAGE });
+    assert_eq!(Pagination::new(Some(0), Some(0)), Pagination { page: 1, per_page: 1 });
+    assert_eq!(Pagination::new(Some(3), Some(10_000)), Pagination { page: 3, per_page: MAX_PER_PAGE });
+
+    let pagination = Pagination::new(Some(3), Some(20));
+    assert_eq!((pagination.limit(), pagination.offset()), (20, 40));
+}
+
+#[test]
+fn test_etag() {
+    let tag = etag(b"{\"a\":1}");
+    assert!(tag.starts_with("W/\"") && tag.ends_with('"'));
+    assert_eq!(tag, etag(b"{\"a\":1}"));
+    assert_ne!(tag, etag(b"{\"a\":2}"));
+
+    assert!(etag_matches(&tag, &tag));
+    assert!(etag_matches(tag.trim_start_matches("W/"), &tag));
+    assert!(etag_matches(&format!("\"other\", {}", tag), &tag));
+    assert!(etag_matches("*", &tag));
+    assert!(!etag_matches("\"other\"", &tag));
+}
+
+#[tokio::test]
+async fn test_api_queries() {
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    initialize_schema(&pool).await.unwrap();
+
+    sqlx::query(r#"
+        INSERT INTO wolvesville_players (id, json) VALUES ('p1', '{}');
+        INSERT INTO wolvesville_player_usernames (player_id, username, timestamp) VALUES
+            ('p1', 'old', '2024-01-01 00:00:00'),
+            ('p1', 'new', '2024-02-01 00:00:00');
+        INSERT INTO wolvesville_player_ranked_skill (player_id, skill, timestamp) VALUES
+            ('p1', 1500, '2024-01-01 00:00:00'),
+            ('p1', 1520, '2024-01-02 00:00:00'),
+            ('p1', 1540, '2024-01-03 00:00:00');
+    "#).execute(&pool).await.unwrap();
+
+    let usernames = player::get_username_history(&pool, "p1").await.unwrap();
+    assert_eq!(usernames.iter().map(|u| u.username.as_str()).collect::<Vec<_>>(), vec!["new", "old"]);
+
+    let (records, total) = player::get_sp_records_page(&pool, "p1", 2, 0).await.unwrap();
+    assert_eq!(total, 3);
+    assert_eq!(records.iter().map(|r| r.skill).collect::<Vec<_>>(), vec![1540, 1520]);
+    let (records, _) = player::get_sp_records_page(&pool, "p1", 2, 2).await.unwrap();
+    assert_eq!(records.iter().map(|r| r.skill).collect::<Vec<_>>(), vec![1500]);
+
+    for (id, name) in [("c1", "Wolves"), ("c2", "Night Wolves"), ("c3", "Villagers")] {
+        let json = serde_json::json!({
+            "creationTime": "2020-01-01T00:00:00Z", "icon": "", "iconColor": "", "id": id, "joinType": "PUBLIC",
+            "language": "EN", "leaderId": "p1", "memberCount": 1, "minLevel": 0, "name": name, "questHistoryCount": 0, "xp": 0,
+        });
+        sqlx::query("INSERT INTO wolvesville_clans (id, name, json) VALUES ($1, $2, $3);")
+            .bind(id).bind(name).bind(json).execute(&pool).await.unwrap();
+    }
+
+    let (clans, total) = clan::search_wolvesville_clans_page(&pool, "wolves", 1, 0).await.unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(clans.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), vec!["Night Wolves"]);
+    let (_, total) = clan::search_wolvesville_clans_page(&pool, "", 10, 0).await.unwrap();
+    assert_eq!(total, 3);
+}
+
+mod router {
+    use std::sync::Arc;
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Request, StatusCode};
+    use axum::response::Response;
+    use serde_json::Value;
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tower::ServiceExt;
+    use crate::bot::server::api::{router, ApiState};
+    use crate::db::initialize_schema;
+
+    async fn state() -> ApiState {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+        initialize_schema(&pool).await.unwrap();
+        for (id, name) in [("c1", "Wolves"), ("c2", "Night Wolves"), ("c3", "Villagers")] {
+            let json = serde_json::json!({
+                "creationTime": "2020-01-01T00:00:00Z", "icon": "", "iconColor": "", "id": id, "joinType": "PUBLIC",
+                "language": "EN", "leaderId": "p1", "memberCount": 1, "minLevel": 0, "name": name, "questHistoryCount": 0, "xp": 0,
+            });
+            sqlx::query("INSERT INTO wolvesville_clans (id, name, json, members_json) VALUES ($1, $2, $3, 'null');")
+                .bind(id).bind(name).bind(json).execute(&pool).await.unwrap();
+        }
+
+        ApiState { db_pool: pool, keys: Arc::new(vec!["first".to_string(), "second".to_string()]) }
+    }
+
+    async fn get(state: &ApiState, uri: &str, headers: &[(&str, &str)]) -> Response {
+        let mut request = Request::builder().uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        router(state.clone()).oneshot(request.body(Body::empty()).unwrap()).await.unwrap()
+    }
+
+    async fn json(response: Response) -> Value {
+        serde_json::from_slice(&to_bytes(response.into_body(), usize::MAX).await.unwrap()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_need_an_api_key() {
+        let state = state().await;
+
+        let response = get(&state, "/clans/c1", &[]).await;
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(json(response).await["error"], "missing or invalid API key");
+        assert_eq!(get(&state, "/clans/c1", &[("x-api-key", "third")]).await.status(), StatusCode::UNAUTHORIZED);
+        // Unknown routes don't reveal anything without a key either
+        assert_eq!(get(&state, "/clans/missing", &[]).await.status(), StatusCode::UNAUTHORIZED);
+
+        assert_eq!(get(&state, "/clans/c1", &[("x-api-key", "first")]).await.status(), StatusCode::OK);
+        assert_eq!(get(&state, "/clans/c1", &[("x-api-key", "second")]).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_etag_answers_not_modified() {
+        let state = state().await;
+
+        let response = get(&state, "/clans/c1", &[("x-api-key", "first")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers()[header::ETAG].to_str().unwrap().to_string();
+        assert_eq!(json(response).await["name"], "Wolves");
+
+        let response = get(&state, "/clans/c1", &[("x-api-key", "first"), ("if-none-match", &etag)]).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert!(to_bytes(response.into_body(), usize::MAX).await.unwrap().is_empty());
+
+        let response = get(&state, "/clans/c2", &[("x-api-key", "first"), ("if-none-match", &etag)]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_missing_resources_and_parameters() {
+        let state = state().await;
+        let key = [("x-api-key", "first")];
+
+        assert_eq!(get(&state, "/clans/missing", &key).await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(get(&state, "/players/missing", &key).await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(get(&state, "/players/missing/sp", &key).await.status(), StatusCode::NOT_FOUND);
+        assert_eq!(get(&state, "/players?name=nobody", &key).await.status(), StatusCode::NOT_FOUND);
+
+        let response = get(&state, "/players?name=%20", &key).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(json(response).await["error"], "the `name` parameter is required");
+    }
+
+    #[tokio::test]
+    async fn test_lists_are_paginated() {
+        let state = state().await;
+
+        let response = get(&state, "/clans?name=wolves&per_page=1&page=2", &[("x-api-key", "first")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = json(response).await;
+        assert_eq!(body["page"], 2);
+        assert_eq!(body["per_page"], 1);
+        assert_eq!(body["total"], 2);
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+
+        let body = json(get(&state, "/clans", &[("x-api-key", "first")]).await).await;
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["per_page"], crate::bot::server::api::DEFAULT_PER_PAGE);
+    }
+}
@@ -0,0 +1,53 @@
+use sqlx::{query, Row, SqlitePool};
+use crate::utils::logger::info;
+
+
+/// The language the guild's admins picked for it, if they ever did.
+pub async fn get_language_code(pool: &SqlitePool, guild_id: &String) -> anyhow::Result<Option<String>> {
+    let q = r#"
+        SELECT language_code FROM guilds WHERE discord_id = $1;
+    "#;
+
+    let row = query(q).bind(guild_id).fetch_optional(pool).await?;
+
+    Ok(row.and_then(|r| r.get("language_code")))
+}
+
+pub async fn set_language_code(pool: &SqlitePool, guild_id: &String, language_code: &str) -> anyhow::Result<()> {
+    let q = r#"
+        INSERT INTO guilds (discord_id, language_code) VALUES ($1, $2)
+        ON CONFLICT(discord_id) DO UPDATE SET language_code = $2;
+    "#;
+
+    query(q).bind(guild_id).bind(language_code).execute(pool).await?;
+
+    info!("Set language for guild {}: `{}`", guild_id, language_code);
+
+    Ok(())
+}
+
+/// Unsets the guild's language, so it falls back to the Discord locale again.
+pub async fn clear_language_code(pool: &SqlitePool, guild_id: &String) -> anyhow::Result<()> {
+    let q = r#"
+        UPDATE guilds SET language_code = NULL WHERE discord_id = $1;
+    "#;
+
+    query(q).bind(guild_id).execute(pool).await?;
+
+    info!("Cleared language for guild {}", guild_id);
+
+    Ok(())
+}
+
+/// Forgets everything stored about the guild, for when the bot leaves it.
+pub async fn delete_guild(pool: &SqlitePool, guild_id: &String) -> anyhow::Result<()> {
+    let q = r#"
+        DELETE FROM guilds WHERE discord_id = $1;
+    "#;
+
+    query(q).bind(guild_id).execute(pool).await?;
+
+    info!("Deleted settings of guild {}", guild_id);
+
+    Ok(())
+}
@@ -1,11 +1,38 @@
-use sqlx::{Row, SqlitePool};
+use std::fmt::Display;
+use chrono::{DateTime, Utc};
+use sqlx::{sqlite::SqliteRow, Row, SqlitePool};
 use uuid::Uuid;
-use crate::utils::scheduler::JobDefinition;
+use crate::utils::scheduler::{JobDefinition, JobOptions, Schedule};
+
+/// A row of the `jobs` table, which may fail to parse if it was written by hand or by a buggy version.
+pub enum StoredJob {
+    Valid(JobDefinition),
+    Corrupt(CorruptJob),
+}
+
+/// A job row that can't be run, kept verbatim so it can be inspected and repaired.
+pub struct CorruptJob {
+    pub id: String,
+    pub name: String,
+    pub row: serde_json::Value,
+    pub error: String,
+}
+
+impl CorruptJob {
+    pub fn from_definition(job: &JobDefinition, error: impl Display) -> Self {
+        Self {
+            id: job.id.to_string(),
+            name: job.name.clone(),
+            row: serde_json::to_value(job).unwrap_or_default(),
+            error: format!("{:#}", error),
+        }
+    }
+}
 
 pub async fn add_job(pool: &SqlitePool, job: &JobDefinition) -> anyhow::Result<()> {
     let q = r#"
-        INSERT INTO jobs (id, name, schedule, created_at, args)
-        VALUES ($1, $2, $3, $4, $5);
+        INSERT INTO jobs (id, name, schedule, created_at, args, args_version, overlap, timeout_secs, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9);
     "#;
     
     sqlx::query(q)
@@ -14,32 +41,142 @@ pub async fn add_job(pool: &SqlitePool, job: &JobDefinition) -> anyhow::Result<(
         .bind(serde_json::to_string(&job.schedule)?)
         .bind(job.created_at)
         .bind(serde_json::to_string(&job.args)?)
+        .bind(job.args_version)
+        .bind(job.options.overlap.as_str())
+        .bind(job.options.timeout.map(|t| t.as_secs() as i64))
+        .bind(job.enabled)
         .execute(pool)
         .await?;
     
     Ok(())
 }
 
-pub async fn get_all_jobs(pool: &SqlitePool) -> anyhow::Result<Vec<JobDefinition>> {
+/// Inserts the job unless a job with the same ID already exists. Returns whether it was inserted.
+pub async fn add_job_if_missing(pool: &SqlitePool, job: &JobDefinition) -> anyhow::Result<bool> {
+    let q = r#"
+        INSERT INTO jobs (id, name, schedule, created_at, args, args_version, overlap, timeout_secs, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        ON CONFLICT(id) DO NOTHING;
+    "#;
+
+    let result = sqlx::query(q)
+        .bind(job.id.to_string())
+        .bind(&job.name)
+        .bind(serde_json::to_string(&job.schedule)?)
+        .bind(job.created_at)
+        .bind(serde_json::to_string(&job.args)?)
+        .bind(job.args_version)
+        .bind(job.options.overlap.as_str())
+        .bind(job.options.timeout.map(|t| t.as_secs() as i64))
+        .bind(job.enabled)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn get_all_jobs(pool: &SqlitePool) -> anyhow::Result<Vec<StoredJob>> {
     let q = r#"
         SELECT * FROM jobs;
     "#;
     
     let rows = sqlx::query(q).fetch_all(pool).await?;
     
-    let jobs: Vec<JobDefinition> = rows.into_iter().map(|row| {
-        JobDefinition {
-            id: Uuid::parse_str(row.get("id")).unwrap(),
-            name: row.get("name"),
-            schedule: serde_json::from_str(row.get("schedule")).unwrap(),
-            created_at: row.get("created_at"),
-            args: serde_json::from_str(row.get("args")).unwrap(),
+    let jobs: Vec<StoredJob> = rows.into_iter().map(|row| {
+        match parse_job(&row) {
+            Ok(job) => StoredJob::Valid(job),
+            Err(error) => StoredJob::Corrupt(CorruptJob {
+                id: row.try_get("id").unwrap_or_default(),
+                name: row.try_get("name").unwrap_or_default(),
+                row: serde_json::json!({
+                    "schedule": row.try_get::<Option<String>, _>("schedule").ok().flatten(),
+                    "created_at": row.try_get::<Option<String>, _>("created_at").ok().flatten(),
+                    "args": row.try_get::<Option<String>, _>("args").ok().flatten(),
+                    "args_version": row.try_get::<Option<i64>, _>("args_version").ok().flatten(),
+                }),
+                error: format!("{:#}", error),
+            }),
         }
     }).collect();
     
     Ok(jobs)
 }
 
+fn parse_job(row: &SqliteRow) -> anyhow::Result<JobDefinition> {
+    Ok(JobDefinition {
+        id: Uuid::parse_str(row.try_get("id")?)?,
+        name: row.try_get("name")?,
+        schedule: serde_json::from_str(row.try_get("schedule")?)?,
+        created_at: row.try_get("created_at")?,
+        args: serde_json::from_str(row.try_get::<Option<&str>, _>("args")?.unwrap_or("null"))?,
+        args_version: row.try_get("args_version")?,
+        options: JobOptions {
+            overlap: row.try_get::<String, _>("overlap")?.parse()?,
+            timeout: row.try_get::<Option<i64>, _>("timeout_secs")?.map(|secs| std::time::Duration::from_secs(secs as u64)),
+        },
+        enabled: row.try_get("enabled")?,
+    })
+}
+
+/// Moves a job out of `jobs` into `jobs_quarantine`, so it is no longer loaded but can still be inspected.
+pub async fn quarantine_job(pool: &SqlitePool, job: &CorruptJob) -> anyhow::Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    let q = r#"
+        INSERT INTO jobs_quarantine (id, name, row, error)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT(id) DO UPDATE SET
+            row = $3,
+            error = $4,
+            quarantined_at = CURRENT_TIMESTAMP;
+    "#;
+
+    sqlx::query(q)
+        .bind(&job.id)
+        .bind(&job.name)
+        .bind(&job.row)
+        .bind(&job.error)
+        .execute(&mut *transaction)
+        .await?;
+
+    sqlx::query("DELETE FROM jobs WHERE id = $1;").bind(&job.id).execute(&mut *transaction).await?;
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn update_job_args(pool: &SqlitePool, job_id: Uuid, args: &serde_json::Value, args_version: u32) -> anyhow::Result<()> {
+    let q = r#"
+        UPDATE jobs SET args = $2, args_version = $3 WHERE id = $1;
+    "#;
+
+    sqlx::query(q)
+        .bind(job_id.to_string())
+        .bind(serde_json::to_string(args)?)
+        .bind(args_version)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_job(pool: &SqlitePool, job_id: Uuid, schedule: &Schedule, args: &serde_json::Value, args_version: u32) -> anyhow::Result<()> {
+    let q = r#"
+        UPDATE jobs SET schedule = $2, args = $3, args_version = $4 WHERE id = $1;
+    "#;
+
+    sqlx::query(q)
+        .bind(job_id.to_string())
+        .bind(serde_json::to_string(schedule)?)
+        .bind(serde_json::to_string(args)?)
+        .bind(args_version)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
 pub async fn delete_job(pool: &SqlitePool, job_id: Uuid) -> anyhow::Result<()> {
     let q = r#"
         DELETE FROM jobs WHERE id = $1;
@@ -48,4 +185,50 @@ pub async fn delete_job(pool: &SqlitePool, job_id: Uuid) -> anyhow::Result<()> {
     sqlx::query(q).bind(job_id.to_string()).execute(pool).await?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+pub async fn set_job_enabled(pool: &SqlitePool, job_id: Uuid, enabled: bool) -> anyhow::Result<()> {
+    let q = r#"
+        UPDATE jobs SET enabled = $2 WHERE id = $1;
+    "#;
+
+    sqlx::query(q).bind(job_id.to_string()).bind(enabled).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Takes the lease if it is free, expired, or already held by `holder_id`, and pushes its expiry to `expires_at`.
+/// Returns whether `holder_id` holds the lease afterwards. The check and the update happen in one statement,
+/// so two instances can't both win.
+pub async fn try_acquire_lease(pool: &SqlitePool, name: &str, holder_id: &str, now: DateTime<Utc>, expires_at: DateTime<Utc>) -> anyhow::Result<bool> {
+    let q = r#"
+        INSERT INTO scheduler_lease (name, holder_id, acquired_at, heartbeat_at, expires_at)
+        VALUES ($1, $2, $3, $3, $4)
+        ON CONFLICT(name) DO UPDATE SET
+            acquired_at = CASE WHEN scheduler_lease.holder_id = $2 THEN scheduler_lease.acquired_at ELSE $3 END,
+            holder_id = $2,
+            heartbeat_at = $3,
+            expires_at = $4
+        WHERE scheduler_lease.holder_id = $2 OR scheduler_lease.expires_at <= $3;
+    "#;
+
+    let result = sqlx::query(q)
+        .bind(name)
+        .bind(holder_id)
+        .bind(now)
+        .bind(expires_at)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn release_lease(pool: &SqlitePool, name: &str, holder_id: &str) -> anyhow::Result<()> {
+    let q = r#"
+        DELETE FROM scheduler_lease WHERE name = $1 AND holder_id = $2;
+    "#;
+
+    sqlx::query(q).bind(name).bind(holder_id).execute(pool).await?;
+
+    Ok(())
+}
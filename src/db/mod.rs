@@ -1,12 +1,15 @@
 use std::{env, path::PathBuf};
-use sqlx::{migrate::MigrateDatabase, query, sqlite::SqlitePoolOptions, Sqlite, SqlitePool};
-use logfather::{info, error};
+use sqlx::{migrate::MigrateDatabase, query, sqlite::SqlitePoolOptions, Row, Sqlite, SqlitePool};
+use crate::utils::logger::{info, error};
 
 pub mod users;
+pub mod guilds;
 pub mod prefixes;
 pub mod wolvesville;
 pub mod lichess;
 pub(crate) mod jobs;
+#[cfg(test)]
+mod tests;
 
 pub async fn get_pool() -> anyhow::Result<SqlitePool> {
     let (db_url, _) = get_db_url()?;
@@ -53,10 +56,86 @@ pub async fn create_db() {
         pool.close().await;
     } else {
         info!("Detected database: {}", filename);
+        let pool = match get_pool().await {
+            Ok(pool) => pool,
+            Err(err) => { error!("Failed to get pool: {}", err); return}
+        };
+        match upgrade_schema(&pool).await {
+            Ok(_) => info!("Schema up to date: {}", filename),
+            Err(err) => { error!("Failed to upgrade schema: {}", err); return}
+        };
+        pool.close().await;
+    }
+}
+
+/// Brings a database created by an older version up to the current schema.
+/// New tables are covered by `initialize_schema`, new columns on existing tables have to be added here.
+async fn upgrade_schema(pool: &SqlitePool) -> anyhow::Result<()> {
+    initialize_schema(pool).await?;
+
+    add_column_if_missing(pool, "jobs", "args_version", "INTEGER NOT NULL DEFAULT 1").await?;
+    add_column_if_missing(pool, "jobs", "overlap", "TEXT NOT NULL DEFAULT 'allow'").await?;
+    add_column_if_missing(pool, "jobs", "timeout_secs", "INTEGER").await?;
+    add_column_if_missing(pool, "jobs", "enabled", "BOOLEAN NOT NULL DEFAULT 1").await?;
+    add_column_if_missing(pool, "wolvesville_players", "last_searched_at", "DATETIME").await?;
+    add_column_if_missing(pool, "wolvesville_players", "sp_sampled_at", "DATETIME").await?;
+
+    // The language is optional now, so the guild language and the Discord locale apply to users who never picked one.
+    // Rows stored before can't be told apart from a deliberate choice and are kept as they are.
+    if column_is_required(pool, "users", "language_code").await? {
+        rebuild_language_table(pool, "users", "").await?;
+    }
+    if column_is_required(pool, "guilds", "language_code").await? {
+        rebuild_language_table(pool, "guilds", "").await?;
+    }
+
+    Ok(())
+}
+
+async fn column_is_required(pool: &SqlitePool, table: &str, column: &str) -> anyhow::Result<bool> {
+    let required: Option<bool> = query("SELECT \"notnull\" FROM pragma_table_info($1) WHERE name = $2;")
+        .bind(table)
+        .bind(column)
+        .fetch_optional(pool)
+        .await?
+        .map(|row| row.try_get(0))
+        .transpose()?;
+
+    Ok(required.unwrap_or(false))
+}
+
+/// Recreates `table` with an optional `language_code`, keeping the rows matched by `filter`.
+async fn rebuild_language_table(pool: &SqlitePool, table: &str, filter: &str) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    query(&format!("CREATE TABLE {}_new (discord_id TEXT PRIMARY KEY, language_code TEXT);", table)).execute(&mut *tx).await?;
+    let kept = query(&format!("INSERT INTO {0}_new (discord_id, language_code) SELECT discord_id, language_code FROM {0} {1};", table, filter))
+        .execute(&mut *tx).await?
+        .rows_affected();
+    query(&format!("DROP TABLE {};", table)).execute(&mut *tx).await?;
+    query(&format!("ALTER TABLE {0}_new RENAME TO {0};", table)).execute(&mut *tx).await?;
+    tx.commit().await?;
+
+    info!("Made {}.language_code optional, kept {} rows", table, kept);
+    Ok(())
+}
+
+async fn add_column_if_missing(pool: &SqlitePool, table: &str, column: &str, definition: &str) -> anyhow::Result<()> {
+    let exists: bool = query("SELECT EXISTS(SELECT 1 FROM pragma_table_info($1) WHERE name = $2);")
+        .bind(table)
+        .bind(column)
+        .fetch_one(pool)
+        .await?
+        .try_get(0)?;
+
+    if !exists {
+        query(&format!("ALTER TABLE {} ADD COLUMN {} {};", table, column, definition)).execute(pool).await?;
+        info!("Added column {}.{}", table, column);
     }
+
+    Ok(())
 }
 
-async fn initialize_schema(pool: &SqlitePool) -> anyhow::Result<()> {
+pub(crate) async fn initialize_schema(pool: &SqlitePool) -> anyhow::Result<()> {
     // NOT FINISHED
     let q = r#"
         CREATE TABLE IF NOT EXISTS prefixes (
@@ -66,7 +145,12 @@ async fn initialize_schema(pool: &SqlitePool) -> anyhow::Result<()> {
 
         CREATE TABLE IF NOT EXISTS users (
             discord_id TEXT PRIMARY KEY,
-            language_code TEXT NOT NULL
+            language_code TEXT
+        );
+
+        CREATE TABLE IF NOT EXISTS guilds (
+            discord_id TEXT PRIMARY KEY,
+            language_code TEXT
         );
 
         CREATE TABLE IF NOT EXISTS wolvesville_clans (
@@ -81,7 +165,9 @@ async fn initialize_schema(pool: &SqlitePool) -> anyhow::Result<()> {
             id TEXT PRIMARY KEY,
             personal_message TEXT,
             json JSON NOT NULL,
-            timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+            timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            last_searched_at DATETIME,
+            sp_sampled_at DATETIME
         );
 
         CREATE TABLE IF NOT EXISTS wolvesville_player_usernames (
@@ -99,13 +185,60 @@ async fn initialize_schema(pool: &SqlitePool) -> anyhow::Result<()> {
             PRIMARY KEY(player_id, timestamp),
             FOREIGN KEY(player_id) REFERENCES wolvesville_players(id) ON DELETE CASCADE
         );
+
+        CREATE TABLE IF NOT EXISTS wolvesville_player_watches (
+            guild_id TEXT NOT NULL,
+            player_id TEXT NOT NULL,
+            channel_id TEXT NOT NULL,
+            sp_threshold INTEGER NOT NULL,
+            language_code TEXT NOT NULL,
+            added_by TEXT NOT NULL,
+            notified_json TEXT,
+            timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY(guild_id, player_id),
+            FOREIGN KEY(player_id) REFERENCES wolvesville_players(id) ON DELETE CASCADE
+        );
+
+        CREATE TABLE IF NOT EXISTS wolvesville_clan_watches (
+            guild_id TEXT NOT NULL,
+            clan_id TEXT NOT NULL,
+            channel_id TEXT NOT NULL,
+            inactive_days INTEGER NOT NULL,
+            language_code TEXT NOT NULL,
+            added_by TEXT NOT NULL,
+            notified_members_json TEXT,
+            checked_at DATETIME,
+            timestamp DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+            PRIMARY KEY(guild_id, clan_id),
+            FOREIGN KEY(clan_id) REFERENCES wolvesville_clans(id) ON DELETE CASCADE
+        );
         
         CREATE TABLE IF NOT EXISTS jobs (
             id TEXT PRIMARY KEY,
             name TEXT NOT NULL,
             created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
             schedule TEXT NOT NULL,
-            args JSON
+            args JSON,
+            args_version INTEGER NOT NULL DEFAULT 1,
+            overlap TEXT NOT NULL DEFAULT 'allow',
+            timeout_secs INTEGER,
+            enabled BOOLEAN NOT NULL DEFAULT 1
+        );
+
+        CREATE TABLE IF NOT EXISTS scheduler_lease (
+            name TEXT PRIMARY KEY,
+            holder_id TEXT NOT NULL,
+            acquired_at DATETIME NOT NULL,
+            heartbeat_at DATETIME NOT NULL,
+            expires_at DATETIME NOT NULL
+        );
+
+        CREATE TABLE IF NOT EXISTS jobs_quarantine (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            row JSON NOT NULL,
+            error TEXT NOT NULL,
+            quarantined_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
         );
         
         CREATE TABLE IF NOT EXISTS lichess_leaderboard_modes (
@@ -1,5 +1,5 @@
 use sqlx::{query, Row, SqlitePool};
-use logfather::info;
+use crate::utils::logger::info;
 
 
 pub async fn get_prefix(pool: &SqlitePool, discord_id: &String) -> anyhow::Result<Option<String>> {
@@ -0,0 +1,50 @@
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use crate::db::{guilds, upgrade_schema, users};
+
+async fn query_all(pool: &SqlitePool, q: &str) -> Vec<(String, Option<String>)> {
+    sqlx::query_as(q).fetch_all(pool).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_upgrade_makes_languages_optional() {
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    // As created by versions that required a language
+    sqlx::query(r#"
+        CREATE TABLE users (discord_id TEXT PRIMARY KEY, language_code TEXT NOT NULL);
+        CREATE TABLE guilds (discord_id TEXT PRIMARY KEY, language_code TEXT NOT NULL);
+        INSERT INTO users (discord_id, language_code) VALUES ('1', 'en'), ('2', 'uk');
+        INSERT INTO guilds (discord_id, language_code) VALUES ('10', 'en'), ('20', 'uk');
+    "#).execute(&pool).await.unwrap();
+
+    upgrade_schema(&pool).await.unwrap();
+    assert_eq!(users::get_language_code(&pool, &"1".to_string()).await.unwrap(), Some("en".to_string()));
+    assert_eq!(users::get_language_code(&pool, &"2".to_string()).await.unwrap(), Some("uk".to_string()));
+    assert_eq!(guilds::get_language_code(&pool, &"10".to_string()).await.unwrap(), Some("en".to_string()));
+
+    // Users can go without a language after the upgrade, which runs only once
+    sqlx::query("INSERT INTO users (discord_id) VALUES ('3');").execute(&pool).await.unwrap();
+    upgrade_schema(&pool).await.unwrap();
+    assert_eq!(query_all(&pool, "SELECT discord_id, language_code FROM users ORDER BY discord_id;").await, vec![
+        ("1".to_string(), Some("en".to_string())),
+        ("2".to_string(), Some("uk".to_string())),
+        ("3".to_string(), None),
+    ]);
+}
+
+#[tokio::test]
+async fn test_clearing_a_guild_language_keeps_the_guild() {
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    upgrade_schema(&pool).await.unwrap();
+    let guild_id = "10".to_string();
+
+    guilds::set_language_code(&pool, &guild_id, "uk").await.unwrap();
+    guilds::clear_language_code(&pool, &guild_id).await.unwrap();
+    assert_eq!(guilds::get_language_code(&pool, &guild_id).await.unwrap(), None);
+    assert_eq!(query_all(&pool, "SELECT discord_id, language_code FROM guilds;").await, vec![(guild_id.clone(), None)]);
+
+    guilds::set_language_code(&pool, &guild_id, "en").await.unwrap();
+    assert_eq!(guilds::get_language_code(&pool, &guild_id).await.unwrap(), Some("en".to_string()));
+    guilds::delete_guild(&pool, &guild_id).await.unwrap();
+    assert!(query_all(&pool, "SELECT discord_id, language_code FROM guilds;").await.is_empty());
+}
@@ -1,4 +1,4 @@
-use logfather::debug;
+use crate::utils::logger::debug;
 use sqlx::{query, Row, SqlitePool};
 use crate::utils::apicallers::wolvesville::models::{WolvesvilleClan, WolvesvilleClanMember};
 
@@ -30,13 +30,18 @@ pub async fn get_wolvesville_clan_info_by_id(pool: &SqlitePool, clan_id: &str) -
     Ok(Some(deserialized_json))
 }
 
+/// Makes `%`, `_` and `\` match themselves in a `LIKE` with `ESCAPE '\'`.
+fn escape_like(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
 pub async fn get_wolvesville_clan_info_by_name(pool: &SqlitePool, clan_name: &str) -> anyhow::Result<Vec<WolvesvilleClan>> {
     let q = r#"
         SELECT wc.* FROM wolvesville_clans wc
-        WHERE wc.name LIKE '%' || $1 || '%';
+        WHERE wc.name LIKE '%' || $1 || '%' ESCAPE '\';
     "#;
 
-    let rows = query(q).bind(clan_name).fetch_all(pool).await?;
+    let rows = query(q).bind(escape_like(clan_name)).fetch_all(pool).await?;
     let mut found_clans: Vec<WolvesvilleClan> = Vec::new();
 
     for row in rows {
@@ -57,6 +62,38 @@ pub async fn get_wolvesville_clan_info_by_name(pool: &SqlitePool, clan_name: &st
     if found_clans.len() != 0 { Ok(found_clans) } else { Err(anyhow::anyhow!("No clans found by name: {}", clan_name)) }
 }
 
+/// A page of the cached clans whose name contains `clan_name`, ordered by name, together with the total number of matches.
+pub async fn search_wolvesville_clans_page(pool: &SqlitePool, clan_name: &str, limit: i64, offset: i64) -> anyhow::Result<(Vec<WolvesvilleClan>, i64)> {
+    let q = r#"
+        SELECT wc.* FROM wolvesville_clans wc
+        WHERE wc.name LIKE '%' || $1 || '%' ESCAPE '\'
+        ORDER BY wc.name ASC, wc.id ASC
+        LIMIT $2 OFFSET $3;
+    "#;
+
+    let count_q = r#"
+        SELECT COUNT(*) FROM wolvesville_clans wc
+        WHERE wc.name LIKE '%' || $1 || '%' ESCAPE '\';
+    "#;
+
+    let pattern = escape_like(clan_name);
+    let rows = query(q).bind(&pattern).bind(limit).bind(offset).fetch_all(pool).await?;
+    let total: i64 = query(count_q).bind(&pattern).fetch_one(pool).await?.try_get(0)?;
+
+    let mut clans = Vec::new();
+    for row in rows {
+        let mut deserialized_json = serde_json::from_value::<WolvesvilleClan>(row.get("json"))
+            .map_err(|err| anyhow::anyhow!("Failed to deserialize clan: {}", err))?;
+
+        deserialized_json.members = row.try_get("members_json").ok().and_then(|members| serde_json::from_value(members).ok());
+        deserialized_json.timestamp = row.get("timestamp");
+
+        clans.push(deserialized_json);
+    }
+
+    Ok((clans, total))
+}
+
 pub async fn upsert_wolvesville_clan(pool: &SqlitePool, mut clan: WolvesvilleClan) -> anyhow::Result<()> {
     let q = r#"
         INSERT INTO wolvesville_clans (id, name, json, members_json)
@@ -64,7 +101,7 @@ pub async fn upsert_wolvesville_clan(pool: &SqlitePool, mut clan: WolvesvilleCla
         ON CONFLICT (id) DO UPDATE SET
             name = $2,
             json = $3,
-            members_json = $4;
+            members_json = CASE WHEN $4 = 'null' THEN members_json ELSE $4 END;
     "#;
 
     let members = serde_json::to_value(clan.members)?;
@@ -97,7 +134,7 @@ pub async fn upsert_multiple_wolvesville_clans(pool: &SqlitePool, clans: &Vec<Wo
         }
     }
 
-    q.push_str(" ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, json = EXCLUDED.json, members_json = EXCLUDED.members_json;");
+    q.push_str(" ON CONFLICT (id) DO UPDATE SET name = EXCLUDED.name, json = EXCLUDED.json, members_json = CASE WHEN EXCLUDED.members_json = 'null' THEN wolvesville_clans.members_json ELSE EXCLUDED.members_json END;");
 
     let mut query = query(q.as_str());
 
@@ -0,0 +1,113 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::{query, Row, SqlitePool};
+use crate::utils::apicallers::wolvesville::models::WolvesvilleClanMember;
+
+/// A guild following the roster of a Wolvesville clan.
+#[derive(Debug, Clone)]
+pub struct ClanWatch {
+    pub guild_id: String,
+    pub clan_id: String,
+    /// Channel the roster changes are posted to.
+    pub channel_id: String,
+    /// Days offline after which a member is reported as inactive.
+    pub inactive_days: i32,
+    pub language_code: String,
+    pub added_by: String,
+    /// The roster as of the last changes posted for this watch, `None` until the first check sets the baseline.
+    pub notified_members: Option<Vec<WolvesvilleClanMember>>,
+    /// When the roster was last compared for this watch, `None` until the first comparison.
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+fn unpack_watch(row: &sqlx::sqlite::SqliteRow) -> ClanWatch {
+    ClanWatch {
+        guild_id: row.get("guild_id"),
+        clan_id: row.get("clan_id"),
+        channel_id: row.get("channel_id"),
+        inactive_days: row.get("inactive_days"),
+        language_code: row.get("language_code"),
+        added_by: row.get("added_by"),
+        notified_members: row.get::<Option<String>, _>("notified_members_json").and_then(|json| serde_json::from_str(&json).ok()),
+        checked_at: row.get::<Option<NaiveDateTime>, _>("checked_at").map(|at| at.and_utc()),
+    }
+}
+
+pub async fn upsert_clan_watch(pool: &SqlitePool, watch: &ClanWatch) -> anyhow::Result<()> {
+    let q = r#"
+        INSERT INTO wolvesville_clan_watches (guild_id, clan_id, channel_id, inactive_days, language_code, added_by)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT(guild_id, clan_id) DO UPDATE SET
+            channel_id = $3,
+            inactive_days = $4,
+            language_code = $5,
+            added_by = $6;
+    "#;
+
+    query(q)
+        .bind(&watch.guild_id)
+        .bind(&watch.clan_id)
+        .bind(&watch.channel_id)
+        .bind(watch.inactive_days)
+        .bind(&watch.language_code)
+        .bind(&watch.added_by)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Stores the roster the watch's channel is up to date with, and when it was compared.
+pub async fn set_clan_watch_notified(pool: &SqlitePool, guild_id: &str, clan_id: &str, members: &[WolvesvilleClanMember], checked_at: DateTime<Utc>) -> anyhow::Result<()> {
+    let q = r#"
+        UPDATE wolvesville_clan_watches SET notified_members_json = $3, checked_at = datetime($4) WHERE guild_id = $1 AND clan_id = $2;
+    "#;
+
+    query(q).bind(guild_id).bind(clan_id).bind(serde_json::to_string(members)?).bind(checked_at.naive_utc()).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Returns whether the guild was watching the clan.
+pub async fn delete_clan_watch(pool: &SqlitePool, guild_id: &str, clan_id: &str) -> anyhow::Result<bool> {
+    let q = r#"
+        DELETE FROM wolvesville_clan_watches WHERE guild_id = $1 AND clan_id = $2;
+    "#;
+
+    let result = query(q).bind(guild_id).bind(clan_id).execute(pool).await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_clan_watches_of_guild(pool: &SqlitePool, guild_id: &str) -> anyhow::Result<()> {
+    let q = r#"
+        DELETE FROM wolvesville_clan_watches WHERE guild_id = $1;
+    "#;
+
+    query(q).bind(guild_id).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Watches of a guild together with the name of each clan, oldest first.
+pub async fn get_clan_watches_of_guild(pool: &SqlitePool, guild_id: &str) -> anyhow::Result<Vec<(ClanWatch, String)>> {
+    let q = r#"
+        SELECT w.*, wc.name AS clan_name FROM wolvesville_clan_watches w
+        JOIN wolvesville_clans wc ON wc.id = w.clan_id
+        WHERE w.guild_id = $1
+        ORDER BY w.timestamp ASC;
+    "#;
+
+    let rows = query(q).bind(guild_id).fetch_all(pool).await?;
+
+    Ok(rows.iter().map(|row| (unpack_watch(row), row.get("clan_name"))).collect())
+}
+
+pub async fn get_all_clan_watches(pool: &SqlitePool) -> anyhow::Result<Vec<ClanWatch>> {
+    let q = r#"
+        SELECT * FROM wolvesville_clan_watches ORDER BY clan_id;
+    "#;
+
+    let rows = query(q).fetch_all(pool).await?;
+
+    Ok(rows.iter().map(unpack_watch).collect())
+}
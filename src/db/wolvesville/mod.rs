@@ -1,2 +1,6 @@
 pub mod player;
-pub mod clan;
\ No newline at end of file
+pub mod clan;
+pub mod watch;
+pub mod clan_watch;
+#[cfg(test)]
+mod tests;
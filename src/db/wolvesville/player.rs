@@ -1,9 +1,10 @@
 use chrono::{DateTime, NaiveDateTime, Utc};
 use sqlx::{query, Row, Sqlite, SqlitePool, Transaction};
-use logfather::{debug, info};
+use crate::utils::logger::{debug, info};
+use serde::Serialize;
 use crate::utils::apicallers::wolvesville::models::WolvesvillePlayer;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SPRecord {
     pub skill: u32,
     pub timestamp: DateTime<Utc>,
@@ -14,10 +15,10 @@ fn pack_player(timestamp: NaiveDateTime, p: &mut WolvesvillePlayer, previous_use
     p.timestamp = Some(DateTime::from_naive_utc_and_offset(timestamp, Utc));
 }
 
-pub async fn _get_player_by_id(pool: &SqlitePool, player_id: &str) -> anyhow::Result<Option<WolvesvillePlayer>> {
+pub async fn get_player_by_id(pool: &SqlitePool, player_id: &str) -> anyhow::Result<Option<WolvesvillePlayer>> {
     let q = r#"
         SELECT * FROM wolvesville_players
-        WHERE player_id = $1;
+        WHERE id = $1;
     "#;
 
     let pq = r#"
@@ -27,20 +28,15 @@ pub async fn _get_player_by_id(pool: &SqlitePool, player_id: &str) -> anyhow::Re
         LIMIT 1 OFFSET 1;
     "#;
 
-    let row = query(q).bind(player_id).fetch_optional(pool).await?;
-    let pu_row = query(pq).bind(player_id).fetch_optional(pool).await?;
-
-    if row.is_none() {
+    let Some(row) = query(q).bind(player_id).fetch_optional(pool).await? else {
         return Ok(None);
-    }
-
-    let row = row.unwrap();
-    let pu_row = pu_row.unwrap();
+    };
+    let pu_row = query(pq).bind(player_id).fetch_optional(pool).await?;
 
     let mut deserialized_json = serde_json::from_value::<WolvesvillePlayer>(row.get("json"))
         .map_err(|err| anyhow::anyhow!("Failed to deserialize player: {}", err))?;
 
-    pack_player(row.get::<NaiveDateTime, _>("timestamp"), &mut deserialized_json, pu_row.get("previous_username"));
+    pack_player(row.get::<NaiveDateTime, _>("timestamp"), &mut deserialized_json, pu_row.map(|r| r.get("previous_username")));
 
     Ok(Some(deserialized_json))
 }
@@ -162,8 +158,8 @@ pub async fn upsert_full_player(pool: &SqlitePool, player: &WolvesvillePlayer) -
     Ok(())
 }
 
-pub async fn get_all_sp_records_of_player_for_last_n_days(pool: &SqlitePool, player_id: &String, days: i64) -> anyhow::Result<Vec<SPRecord>> {
-    let cutoff = Utc::now().naive_utc() - chrono::Duration::days(days);
+pub async fn get_all_sp_records_of_player_for_last_n_days(pool: &SqlitePool, player_id: &String, days: i64, now: DateTime<Utc>) -> anyhow::Result<Vec<SPRecord>> {
+    let cutoff = now.naive_utc() - chrono::Duration::days(days);
 
     let q = r#"
         SELECT skill, timestamp FROM wolvesville_player_ranked_skill
@@ -185,4 +181,115 @@ pub async fn get_all_sp_records_of_player_for_last_n_days(pool: &SqlitePool, pla
     debug!("Got {} records for player {}", records.len(), player_id);
 
     Ok(records)
-}
\ No newline at end of file
+}
+
+/// A username a player had, and when the bot last saw them using it.
+#[derive(Debug, Serialize)]
+pub struct UsernameRecord {
+    pub username: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Every known username of a player, most recent first.
+pub async fn get_username_history(pool: &SqlitePool, player_id: &str) -> anyhow::Result<Vec<UsernameRecord>> {
+    let q = r#"
+        SELECT username, timestamp FROM wolvesville_player_usernames
+        WHERE player_id = $1
+        ORDER BY timestamp DESC;
+    "#;
+
+    let rows = query(q).bind(player_id).fetch_all(pool).await?;
+
+    Ok(rows.iter().map(|row| UsernameRecord {
+        username: row.get("username"),
+        timestamp: row.get("timestamp"),
+    }).collect())
+}
+
+/// A page of the SP records of a player, most recent first, together with the total number of records.
+pub async fn get_sp_records_page(pool: &SqlitePool, player_id: &str, limit: i64, offset: i64) -> anyhow::Result<(Vec<SPRecord>, i64)> {
+    let q = r#"
+        SELECT skill, timestamp FROM wolvesville_player_ranked_skill
+        WHERE player_id = $1
+        ORDER BY timestamp DESC
+        LIMIT $2 OFFSET $3;
+    "#;
+
+    let count_q = r#"
+        SELECT COUNT(*) FROM wolvesville_player_ranked_skill WHERE player_id = $1;
+    "#;
+
+    let rows = query(q).bind(player_id).bind(limit).bind(offset).fetch_all(pool).await?;
+    let total: i64 = query(count_q).bind(player_id).fetch_one(pool).await?.try_get(0)?;
+
+    let records = rows.iter().map(|row| SPRecord {
+        skill: row.try_get("skill").unwrap_or(1500),
+        timestamp: row.get("timestamp"),
+    }).collect();
+
+    Ok((records, total))
+}
+
+/// Remembers that someone looked the player up, which makes them eligible for SP sampling for a while.
+pub async fn mark_player_searched(pool: &SqlitePool, player_id: &str, now: DateTime<Utc>) -> anyhow::Result<()> {
+    let q = r#"
+        UPDATE wolvesville_players SET last_searched_at = datetime($2)
+        WHERE id = $1;
+    "#;
+
+    query(q).bind(player_id).bind(now.naive_utc()).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Players that are watched by a guild or were searched after `searched_since`, and have neither been sampled
+/// nor got a new SP record after `sampled_before`. Watched players come first, then the ones waiting the longest.
+pub async fn get_sp_sample_candidates(pool: &SqlitePool, searched_since: DateTime<Utc>, sampled_before: DateTime<Utc>, limit: usize) -> anyhow::Result<Vec<String>> {
+    let q = r#"
+        SELECT wp.id FROM wolvesville_players wp
+        LEFT JOIN (SELECT DISTINCT player_id FROM wolvesville_player_watches) w ON w.player_id = wp.id
+        WHERE (w.player_id IS NOT NULL OR wp.last_searched_at >= datetime($1))
+            AND (wp.sp_sampled_at IS NULL OR wp.sp_sampled_at < datetime($2))
+            AND NOT EXISTS (
+                SELECT 1 FROM wolvesville_player_ranked_skill wpr
+                WHERE wpr.player_id = wp.id AND wpr.timestamp >= datetime($2)
+            )
+        ORDER BY w.player_id IS NULL, wp.sp_sampled_at IS NOT NULL, wp.sp_sampled_at ASC
+        LIMIT $3;
+    "#;
+
+    let rows = query(q)
+        .bind(searched_since.naive_utc())
+        .bind(sampled_before.naive_utc())
+        .bind(limit as i64)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(|row| row.get("id")).collect())
+}
+
+/// Stores a sampled SP value, if the player has one, and marks the player as sampled either way,
+/// so players with a hidden SP don't take up the sampling budget on every run.
+pub async fn record_sp_sample(pool: &SqlitePool, player_id: &str, skill: Option<i32>, now: DateTime<Utc>) -> anyhow::Result<()> {
+    let mut transaction: Transaction<'_, Sqlite> = pool.begin().await?;
+
+    if let Some(skill) = skill {
+        let sql_wpr = r#"
+            INSERT INTO wolvesville_player_ranked_skill (player_id, skill, timestamp)
+            VALUES ($1, $2, datetime($3))
+            ON CONFLICT(player_id, timestamp) DO NOTHING;
+        "#;
+
+        query(sql_wpr).bind(player_id).bind(skill).bind(now.naive_utc()).execute(&mut *transaction).await?;
+    }
+
+    let sql_wp = r#"
+        UPDATE wolvesville_players SET sp_sampled_at = datetime($2)
+        WHERE id = $1;
+    "#;
+
+    query(sql_wp).bind(player_id).bind(now.naive_utc()).execute(&mut *transaction).await?;
+
+    transaction.commit().await?;
+    Ok(())
+}
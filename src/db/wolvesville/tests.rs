@@ -0,0 +1,155 @@
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use crate::db::initialize_schema;
+use crate::db::wolvesville::clan::search_wolvesville_clans_page;
+use crate::db::wolvesville::clan_watch::{get_all_clan_watches, set_clan_watch_notified, upsert_clan_watch, ClanWatch};
+use crate::db::wolvesville::player::{get_sp_sample_candidates, mark_player_searched, record_sp_sample};
+use crate::utils::apicallers::wolvesville::models::WolvesvilleClanMember;
+
+/// Far from the wall clock, so anything stored with the database's own time would stand out.
+fn now() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2020, 6, 15, 12, 0, 0).unwrap()
+}
+
+async fn setup() -> SqlitePool {
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    initialize_schema(&pool).await.unwrap();
+    pool
+}
+
+async fn insert_player(pool: &SqlitePool, id: &str, last_searched_at: Option<DateTime<Utc>>, sp_sampled_at: Option<DateTime<Utc>>) {
+    sqlx::query("INSERT INTO wolvesville_players (id, json, last_searched_at, sp_sampled_at) VALUES ($1, '{}', datetime($2), datetime($3));")
+        .bind(id)
+        .bind(last_searched_at.map(|at| at.naive_utc()))
+        .bind(sp_sampled_at.map(|at| at.naive_utc()))
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+/// Candidates as the SP sampler asks for them at `now`.
+async fn candidates(pool: &SqlitePool, now: DateTime<Utc>, limit: usize) -> Vec<String> {
+    get_sp_sample_candidates(pool, now - Duration::days(14), now - Duration::hours(3), limit).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_sp_sample_candidates() {
+    let pool = setup().await;
+    let now = now();
+    insert_player(&pool, "watched", None, None).await;
+    sqlx::query("INSERT INTO wolvesville_player_watches (guild_id, player_id, channel_id, sp_threshold, language_code, added_by) VALUES ('g1', 'watched', 'c1', 25, 'en', 'u1'), ('g2', 'watched', 'c2', 25, 'en', 'u2');")
+        .execute(&pool).await.unwrap();
+    insert_player(&pool, "searched", Some(now - Duration::days(2)), None).await;
+    insert_player(&pool, "sampled_long_ago", Some(now - Duration::days(1)), Some(now - Duration::hours(10))).await;
+    insert_player(&pool, "sampled_recently", Some(now - Duration::days(1)), Some(now - Duration::hours(1))).await;
+    insert_player(&pool, "searched_long_ago", Some(now - Duration::days(20)), None).await;
+    insert_player(&pool, "never_searched", None, None).await;
+    // A search stored a fresh SP record, so there's no need to sample it
+    insert_player(&pool, "recent_record", Some(now - Duration::days(1)), None).await;
+    sqlx::query("INSERT INTO wolvesville_player_ranked_skill (player_id, skill, timestamp) VALUES ('recent_record', 1500, datetime($1));")
+        .bind((now - Duration::hours(1)).naive_utc()).execute(&pool).await.unwrap();
+
+    // Watched players first, then the ones never sampled, then the ones waiting the longest
+    assert_eq!(candidates(&pool, now, 10).await, vec!["watched", "searched", "sampled_long_ago"]);
+    // The budget cuts off the end of the queue
+    assert_eq!(candidates(&pool, now, 2).await, vec!["watched", "searched"]);
+}
+
+#[tokio::test]
+async fn test_sp_sample_is_recorded_at_the_given_time() {
+    let pool = setup().await;
+    let now = now();
+    insert_player(&pool, "visible", Some(now - Duration::days(1)), None).await;
+    insert_player(&pool, "hidden", Some(now - Duration::days(1)), None).await;
+
+    record_sp_sample(&pool, "visible", Some(1600), now).await.unwrap();
+    record_sp_sample(&pool, "hidden", None, now).await.unwrap();
+
+    let timestamps: Vec<(String, i32)> = sqlx::query_as("SELECT player_id, skill FROM wolvesville_player_ranked_skill WHERE timestamp = datetime($1);")
+        .bind(now.naive_utc()).fetch_all(&pool).await.unwrap();
+    assert_eq!(timestamps, vec![("visible".to_string(), 1600)]);
+
+    // Both wait until the next run before they are sampled again
+    assert!(candidates(&pool, now, 10).await.is_empty());
+    let mut later = candidates(&pool, now + Duration::hours(4), 10).await;
+    later.sort();
+    assert_eq!(later, vec!["hidden", "visible"]);
+}
+
+#[tokio::test]
+async fn test_search_makes_player_a_candidate() {
+    let pool = setup().await;
+    let now = now();
+    insert_player(&pool, "player", None, None).await;
+    assert!(candidates(&pool, now, 10).await.is_empty());
+
+    mark_player_searched(&pool, "player", now).await.unwrap();
+    assert_eq!(candidates(&pool, now, 10).await, vec!["player"]);
+    // Until the search is too long ago
+    assert!(candidates(&pool, now + Duration::days(15), 10).await.is_empty());
+}
+
+fn clan_member(id: &str) -> WolvesvilleClanMember {
+    serde_json::from_value(serde_json::json!({
+        "playerId": id, "creationTime": "2024-01-01T00:00:00.000Z", "xp": 0, "status": "ACCEPTED", "isCoLeader": false,
+        "username": id, "level": 10, "lastOnline": "2024-01-01T00:00:00.000Z", "profileIconId": "icon",
+        "profileIconColor": "#ffffff", "playerStatus": "DEFAULT",
+    })).unwrap()
+}
+
+#[tokio::test]
+async fn test_clan_watch_keeps_its_own_notified_roster() {
+    let pool = setup().await;
+    let now = now();
+    sqlx::query("INSERT INTO wolvesville_clans (id, name, json) VALUES ('clan', 'Wolves', '{}');").execute(&pool).await.unwrap();
+    let watch = |guild_id: &str| ClanWatch {
+        guild_id: guild_id.to_string(),
+        clan_id: "clan".to_string(),
+        channel_id: "channel".to_string(),
+        inactive_days: 7,
+        language_code: "en".to_string(),
+        added_by: "user".to_string(),
+        notified_members: None,
+        checked_at: None,
+    };
+    upsert_clan_watch(&pool, &watch("g1")).await.unwrap();
+    upsert_clan_watch(&pool, &watch("g2")).await.unwrap();
+
+    set_clan_watch_notified(&pool, "g1", "clan", &[clan_member("alpha"), clan_member("bravo")], now).await.unwrap();
+    // Changing the watch doesn't reset what it already posted
+    upsert_clan_watch(&pool, &ClanWatch { inactive_days: 14, ..watch("g1") }).await.unwrap();
+
+    let watches = get_all_clan_watches(&pool).await.unwrap();
+    let notified = |watch: &ClanWatch| watch.notified_members.as_ref().map(|members| members.iter().map(|member| member.player_id.clone()).collect::<Vec<_>>());
+    assert_eq!(notified(&watches[0]), Some(vec!["alpha".to_string(), "bravo".to_string()]));
+    assert_eq!(watches[0].checked_at, Some(now));
+    assert_eq!(notified(&watches[1]), None);
+    assert_eq!(watches[1].checked_at, None);
+}
+
+#[tokio::test]
+async fn test_clan_search_matches_wildcards_literally() {
+    let pool = setup().await;
+    for (id, name) in [("c1", "100% Wolves"), ("c2", "1000 Wolves"), ("c3", "Night_Owls"), ("c4", "Night Owls"), ("c5", r"Back\slash")] {
+        let json = serde_json::json!({
+            "creationTime": "2020-01-01T00:00:00Z", "icon": "", "iconColor": "", "id": id, "joinType": "PUBLIC",
+            "language": "EN", "leaderId": "p1", "memberCount": 1, "minLevel": 0, "name": name, "questHistoryCount": 0, "xp": 0,
+        });
+        sqlx::query("INSERT INTO wolvesville_clans (id, name, json, members_json) VALUES ($1, $2, $3, 'null');")
+            .bind(id).bind(name).bind(json).execute(&pool).await.unwrap();
+    }
+
+    let search = |name: &'static str| {
+        let pool = pool.clone();
+        async move {
+            let (clans, total) = search_wolvesville_clans_page(&pool, name, 10, 0).await.unwrap();
+            assert_eq!(total as usize, clans.len());
+            clans.into_iter().map(|clan| clan.id).collect::<Vec<_>>()
+        }
+    };
+    assert_eq!(search("0%").await, vec!["c1"]);
+    assert_eq!(search("t_O").await, vec!["c3"]);
+    assert_eq!(search(r"k\s").await, vec!["c5"]);
+    assert_eq!(search("owls").await, vec!["c4", "c3"]);
+}
@@ -0,0 +1,112 @@
+use sqlx::{query, Row, SqlitePool};
+use crate::utils::wolvesville::PlayerSnapshot;
+
+/// A guild following a Wolvesville player.
+#[derive(Debug, Clone)]
+pub struct PlayerWatch {
+    pub guild_id: String,
+    pub player_id: String,
+    /// Channel the changes are posted to.
+    pub channel_id: String,
+    /// Smallest ranked SP change that gets posted.
+    pub sp_threshold: i32,
+    pub language_code: String,
+    pub added_by: String,
+    /// The player as of the last changes posted to the guild, which the next refresh is compared against.
+    /// Missing for watches added before it was stored.
+    pub notified: Option<PlayerSnapshot>,
+}
+
+fn unpack_watch(row: &sqlx::sqlite::SqliteRow) -> PlayerWatch {
+    PlayerWatch {
+        guild_id: row.get("guild_id"),
+        player_id: row.get("player_id"),
+        channel_id: row.get("channel_id"),
+        sp_threshold: row.get("sp_threshold"),
+        language_code: row.get("language_code"),
+        added_by: row.get("added_by"),
+        notified: row.get::<Option<String>, _>("notified_json").and_then(|json| serde_json::from_str(&json).ok()),
+    }
+}
+
+pub async fn upsert_player_watch(pool: &SqlitePool, watch: &PlayerWatch) -> anyhow::Result<()> {
+    let q = r#"
+        INSERT INTO wolvesville_player_watches (guild_id, player_id, channel_id, sp_threshold, language_code, added_by, notified_json)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT(guild_id, player_id) DO UPDATE SET
+            channel_id = $3,
+            sp_threshold = $4,
+            language_code = $5,
+            added_by = $6,
+            notified_json = COALESCE(notified_json, $7);
+    "#;
+    let notified = watch.notified.as_ref().map(serde_json::to_string).transpose()?;
+
+    query(q)
+        .bind(&watch.guild_id)
+        .bind(&watch.player_id)
+        .bind(&watch.channel_id)
+        .bind(watch.sp_threshold)
+        .bind(&watch.language_code)
+        .bind(&watch.added_by)
+        .bind(notified)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn set_notified_snapshot(pool: &SqlitePool, guild_id: &str, player_id: &str, snapshot: &PlayerSnapshot) -> anyhow::Result<()> {
+    let q = r#"
+        UPDATE wolvesville_player_watches SET notified_json = $3 WHERE guild_id = $1 AND player_id = $2;
+    "#;
+
+    query(q).bind(guild_id).bind(player_id).bind(serde_json::to_string(snapshot)?).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Returns whether the guild was watching the player.
+pub async fn delete_player_watch(pool: &SqlitePool, guild_id: &str, player_id: &str) -> anyhow::Result<bool> {
+    let q = r#"
+        DELETE FROM wolvesville_player_watches WHERE guild_id = $1 AND player_id = $2;
+    "#;
+
+    let result = query(q).bind(guild_id).bind(player_id).execute(pool).await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_player_watches_of_guild(pool: &SqlitePool, guild_id: &str) -> anyhow::Result<()> {
+    let q = r#"
+        DELETE FROM wolvesville_player_watches WHERE guild_id = $1;
+    "#;
+
+    query(q).bind(guild_id).execute(pool).await?;
+
+    Ok(())
+}
+
+/// Watches of a guild together with the current username of each player, oldest first.
+pub async fn get_player_watches_of_guild(pool: &SqlitePool, guild_id: &str) -> anyhow::Result<Vec<(PlayerWatch, String)>> {
+    let q = r#"
+        SELECT w.*, json_extract(wp.json, '$.username') AS username FROM wolvesville_player_watches w
+        JOIN wolvesville_players wp ON wp.id = w.player_id
+        WHERE w.guild_id = $1
+        ORDER BY w.timestamp ASC;
+    "#;
+
+    let rows = query(q).bind(guild_id).fetch_all(pool).await?;
+
+    Ok(rows.iter().map(|row| (unpack_watch(row), row.get("username"))).collect())
+}
+
+pub async fn get_all_player_watches(pool: &SqlitePool) -> anyhow::Result<Vec<PlayerWatch>> {
+    let q = r#"
+        SELECT * FROM wolvesville_player_watches ORDER BY player_id;
+    "#;
+
+    let rows = query(q).fetch_all(pool).await?;
+
+    Ok(rows.iter().map(unpack_watch).collect())
+}
@@ -13,7 +13,9 @@ extern crate rust_i18n;
 pub use rust_i18n::t;
 use crate::bot::core::structs::MetricsManager;
 
-i18n!("locale", fallback = "en");
+// No fallback locale, so a key `t!` can't find comes back as is and `language::translation` can tell. Literal keys are
+// checked to exist in every locale by the tests, `language::dynamic_translation` falls back to the default language.
+i18n!("locale");
 
 #[tokio::main]
 async fn main() {
@@ -25,9 +27,9 @@ async fn main() {
 
     let token = env::var("DISCORD_TOKEN").expect("Couldn't find 'DISCORD_TOKEN' in .env file");
 
-    let mut bot = bot::Bot::new(token).await;
+    let metrics_manager = Arc::new(MetricsManager::new());
+    let mut bot = bot::Bot::new(token, metrics_manager.clone()).await;
 
-    let metrics_manager = MetricsManager::new();
-    tokio::spawn(bot::server::run_metrics_manager(Arc::new(metrics_manager), pid));
+    tokio::spawn(bot::server::run_metrics_manager(metrics_manager, bot.health_state(), pid));
     bot.start().await;
 }
@@ -2,6 +2,7 @@ use std::sync::Arc;
 use reqwest::{header::{HeaderMap, HeaderValue, AUTHORIZATION}, Client};
 use crate::bot::core::structs::ApiResult;
 use crate::bot::core::constants::WOLVESVILLE_API_URL;
+use crate::utils::metrics::HTTP_METRICS;
 use models::{WolvesvilleClan, WolvesvillePlayer, WolvesvilleClanMember};
 
 #[cfg(test)]
@@ -19,10 +20,12 @@ pub fn initialize_client() -> Arc<Client> {
 
 pub async fn get_wolvesville_player_by_id(client: &Arc<Client>, player_id: &str) -> ApiResult<WolvesvillePlayer> {
     let url = format!("{}/players/{}", WOLVESVILLE_API_URL, player_id);
+    let mut request = HTTP_METRICS.start("wolvesville", "/players/{id}");
     let response = client
         .get(&url)
         .send()
         .await?;
+    request.set_status(response.status());
     if response.status().as_u16() == 404 { return Ok(None); }
     let json = response.json::<WolvesvillePlayer>().await?;
     Ok(Some(json))
@@ -30,10 +33,12 @@ pub async fn get_wolvesville_player_by_id(client: &Arc<Client>, player_id: &str)
 
 pub async fn get_wolvesville_player_by_username(client: &Arc<Client>, username: &str) -> ApiResult<WolvesvillePlayer> {
     let url = format!("{}/players/search?username={}", WOLVESVILLE_API_URL, username);
+    let mut request = HTTP_METRICS.start("wolvesville", "/players/search");
     let response = client
         .get(&url)
         .send()
         .await?;
+    request.set_status(response.status());
     if response.status().as_u16() == 404 {
         return Ok(None);
     }
@@ -43,10 +48,12 @@ pub async fn get_wolvesville_player_by_username(client: &Arc<Client>, username:
 
 pub async fn get_wolvesville_clan_info_by_id(client: &Arc<Client>, clan_id: &str) -> ApiResult<WolvesvilleClan> {
     let url = format!("{}/clans/{}/info", WOLVESVILLE_API_URL, clan_id);
+    let mut request = HTTP_METRICS.start("wolvesville", "/clans/{id}/info");
     let response = client
         .get(&url)
         .send()
         .await?;
+    request.set_status(response.status());
     if response.status().as_u16() == 404 {
         return Ok(None);
     }
@@ -56,10 +63,12 @@ pub async fn get_wolvesville_clan_info_by_id(client: &Arc<Client>, clan_id: &str
 
 pub async fn get_wolvesville_clan_info_by_name(client: &Arc<Client>, clan_name: &str) -> ApiResult<Vec<WolvesvilleClan>> {
     let url = format!("{}/clans/search?name={}", WOLVESVILLE_API_URL, clan_name);
+    let mut request = HTTP_METRICS.start("wolvesville", "/clans/search");
     let response = client
         .get(&url)
         .send()
         .await?;
+    request.set_status(response.status());
     if response.status().as_u16() == 404 { return Ok(None); }
     let json = response.json::<Vec<WolvesvilleClan>>().await?;
     if json.is_empty() { return Ok(None); }
@@ -68,10 +77,12 @@ pub async fn get_wolvesville_clan_info_by_name(client: &Arc<Client>, clan_name:
 
 pub async fn get_wolvesville_clan_members_by_id(client: &Arc<Client>, clan_id: &str) -> ApiResult<Vec<WolvesvilleClanMember>> {
     let url = format!("{}/clans/{}/members", WOLVESVILLE_API_URL, clan_id);
+    let mut request = HTTP_METRICS.start("wolvesville", "/clans/{id}/members");
     let response = client
         .get(&url)
         .send()
         .await?;
+    request.set_status(response.status());
     if response.status().as_u16() == 404 { return Ok(None); }
     let json = response.json::<Vec<WolvesvilleClanMember>>().await?;
     Ok(Some(json))
@@ -1,12 +1,13 @@
 use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
+use crate::utils::time::Clock;
 
 pub trait Refreshable {
 	fn timestamp(&self) -> Option<DateTime<Utc>>;
 
-	fn is_outdated(&self) -> bool {
+	fn is_outdated(&self, clock: &dyn Clock) -> bool {
 		if let Some(timestamp) = self.timestamp() {
-			let now = Utc::now();
+			let now = clock.now();
 			let diff = now.signed_duration_since(timestamp);
 			diff.num_days() > 30
 		} else {
@@ -0,0 +1,108 @@
+use chrono::{Datelike, NaiveDate, TimeDelta};
+
+/// CLDR plural categories, see the `format` section of the locale files.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PluralCategory {
+    One,
+    Few,
+    Many,
+    Other,
+}
+
+impl PluralCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluralCategory::One => "one",
+            PluralCategory::Few => "few",
+            PluralCategory::Many => "many",
+            PluralCategory::Other => "other",
+        }
+    }
+}
+
+/// Which plural form a whole number takes. Ukrainian has one for 1, 21, 31…, few for 2-4, 22-24…
+/// and many for the rest, everything else is treated like English.
+pub fn plural_category(count: i64, locale: &str) -> PluralCategory {
+    let count = count.unsigned_abs();
+    match locale.split('-').next().unwrap_or(locale) {
+        "uk" => match (count % 10, count % 100) {
+            (1, rem) if rem != 11 => PluralCategory::One,
+            (2..=4, rem) if !(12..=14).contains(&rem) => PluralCategory::Few,
+            _ => PluralCategory::Many,
+        },
+        _ if count == 1 => PluralCategory::One,
+        _ => PluralCategory::Other,
+    }
+}
+
+/// Translates the plural form of `key` that fits `count`, with `%{count}` as a formatted number.
+pub fn plural(key: &str, count: i64, locale: &str) -> String {
+    t!(format!("{}.{}", key, plural_category(count, locale).as_str()), count = format_number(count, locale), locale = locale).to_string()
+}
+
+/// `1234567` is `1,234,567` in English and `1 234 567` in Ukrainian.
+pub fn format_number(number: i64, locale: &str) -> String {
+    let digits = group_thousands(&number.unsigned_abs().to_string(), &t!("format.number.thousands_separator", locale = locale));
+    if number < 0 { format!("-{}", digits) } else { digits }
+}
+
+/// `number` rounded to `decimals` places, with the locale's separators.
+pub fn format_decimal(number: f64, decimals: usize, locale: &str) -> String {
+    let formatted = format!("{:.*}", decimals, number.abs());
+    let (whole, fraction) = formatted.split_once('.').map_or((formatted.as_str(), None), |(whole, fraction)| (whole, Some(fraction)));
+
+    let mut result = group_thousands(whole, &t!("format.number.thousands_separator", locale = locale));
+    if let Some(fraction) = fraction {
+        result.push_str(&t!("format.number.decimal_separator", locale = locale));
+        result.push_str(fraction);
+    }
+    // Rounding may have left nothing but zeroes, which shouldn't read as negative
+    if number < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        result.insert(0, '-');
+    }
+    result
+}
+
+/// A percentage with two decimals, like `12.50%` or `12,50%`.
+pub fn format_percent(percentage: f64, locale: &str) -> String {
+    t!("format.percent", value = format_decimal(percentage, 2, locale), locale = locale).to_string()
+}
+
+/// Spells out a duration down to the second, leaving out the units that are zero, e.g. `1 day, 2 hours`.
+pub fn format_duration(delta: &TimeDelta, locale: &str) -> String {
+    let mut remaining_seconds = delta.num_seconds().abs();
+    let mut parts = Vec::new();
+
+    for (unit, seconds) in [("day", 86400), ("hour", 3600), ("minute", 60)] {
+        let amount = remaining_seconds / seconds;
+        if amount > 0 {
+            parts.push(plural(&format!("format.duration.{}", unit), amount, locale));
+            remaining_seconds %= seconds;
+        }
+    }
+
+    if remaining_seconds > 0 || parts.is_empty() {
+        parts.push(plural("format.duration.second", remaining_seconds, locale));
+    }
+
+    let sign = if delta.num_seconds() < 0 { "-" } else { "" };
+    format!("{}{}", sign, parts.join(&t!("format.duration.separator", locale = locale)))
+}
+
+/// A date written out with the month's name, `August 3, 2018` or `3 серпня 2018 р.`
+pub fn format_date(date: NaiveDate, locale: &str) -> String {
+    let months = t!("format.date.months", locale = locale);
+    let month = months.split(',').nth(date.month0() as usize).map_or(date.month().to_string(), |month| month.trim().to_string());
+    t!("format.date.pattern", day = date.day(), month = month, year = date.year(), locale = locale).to_string()
+}
+
+fn group_thousands(digits: &str, separator: &str) -> String {
+    let mut result = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            result.push_str(separator);
+        }
+        result.push(c);
+    }
+    result
+}
@@ -3,9 +3,16 @@ use reqwest::get;
 use image::{load_from_memory, DynamicImage, ImageBuffer, Pixel, Rgba, RgbaImage};
 use imageproc::drawing::{draw_filled_circle_mut, draw_filled_rect_mut};
 use imageproc::rect::Rect;
+use crate::utils::metrics::HTTP_METRICS;
 
 async fn get_image_by_url(url: &str) -> anyhow::Result<DynamicImage> {
-    let bytes = get(url).await?.bytes().await?;
+    let host = reqwest::Url::parse(url).ok().and_then(|url| url.host_str().map(str::to_string)).unwrap_or_default();
+    let mut request = HTTP_METRICS.start("image", &host);
+    let response = get(url).await?;
+    request.set_status(response.status());
+    let bytes = response.bytes().await?;
+    drop(request);
+
     Ok(load_from_memory(&bytes)?)
 }
 
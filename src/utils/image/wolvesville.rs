@@ -9,6 +9,7 @@ use imageproc::drawing::{draw_text_mut, Canvas};
 use crate::db::wolvesville::player::SPRecord;
 use crate::utils;
 use crate::utils::apicallers::wolvesville::models::Avatar;
+use crate::utils::metrics::RENDER_DURATION;
 
 fn add_level_rank(image: &mut DynamicImage, level: i32) {
     let mut rank_image = open(
@@ -51,6 +52,9 @@ fn add_level_rank(image: &mut DynamicImage, level: i32) {
 }
 
 pub async fn render_wolvesville_avatar(avatar: Avatar, level: Option<i32>) -> anyhow::Result<(String, DynamicImage)> {
+    let mut avatar_image = utils::image::get_image_by_url(avatar.url.as_str()).await?;
+    let _timer = RENDER_DURATION.with_label_values(&["avatar"]).start_timer();
+
     // Import avatar background to maintain aspect ratio
     let overlay_background = open("res/images/wov_small_night_avatar.png")?;
     // Lay avatar background above solid dark blue color
@@ -63,8 +67,6 @@ pub async fn render_wolvesville_avatar(avatar: Avatar, level: Option<i32>) -> an
 
     utils::image::overlay_transparent_image(&mut solid_background, &overlay_background, 0, 0);
 
-    let mut avatar_image = utils::image::get_image_by_url(avatar.url.as_str()).await?;
-
     // Crop avatar if it's too big
     if avatar_image.width() > solid_background.width() {
         // Crop the sides
@@ -98,6 +100,7 @@ pub async fn render_wolvesville_avatar(avatar: Avatar, level: Option<i32>) -> an
 }
 
 pub async fn render_all_wolvesville_avatars(ordered_urls: &Vec<String>, avatar_images: &HashMap<String, DynamicImage>) -> anyhow::Result<DynamicImage> {
+    let _timer = RENDER_DURATION.with_label_values(&["all_avatars"]).start_timer();
     let amount_of_avatars = ordered_urls.len() as u32;
     let amount_of_avatars_on_last_row = amount_of_avatars % 3;  // 0 = 3 avatars
     let amount_of_rows = (amount_of_avatars as f32 / 3.0).ceil() as u32;
@@ -156,6 +159,7 @@ pub fn draw_sp_plot(data: &Vec<SPRecord>, player_name: &String, language: &Strin
     if data.is_empty() {
         return Err(anyhow!("Input data cannot be empty."));
     }
+    let _timer = RENDER_DURATION.with_label_values(&["sp_plot"]).start_timer();
 
     let (timestamps_strings, skill_points_data) = prepare_line_chart_data(data)
         .map_err(|e| anyhow!("Failed to prepare line chart data: {}", e))?;
@@ -1,29 +1,174 @@
+use std::collections::HashSet;
+use std::sync::{LazyLock, Mutex};
+use poise::serenity_prelude as serenity;
 use crate::bot::core::constants::DEFAULT_LANGUAGE;
-use crate::bot::core::structs::Data;
-use crate::db::users::get_language_code;
-use logfather::error;
+use crate::bot::core::structs::{CacheMetrics, Context, Data};
+use crate::db::{guilds, users};
+use crate::utils::logger::{error, warn};
+use crate::utils::metrics::MISSING_TRANSLATIONS;
 
 
-pub async fn get_language(data: &Data, user_id: &String) -> String {
+/// The language to reply in: the user's own choice, then the guild's, then the locale of the Discord client
+/// for slash commands, and the default language when none of them is known.
+pub async fn get_language(ctx: Context<'_>) -> String {
+    let data = ctx.data();
+    let user_language = get_user_language(data, &ctx.author().id.to_string()).await;
+    let guild_language = match (&user_language, ctx.guild_id()) {
+        (None, Some(guild_id)) => get_guild_language(data, &guild_id.to_string()).await,
+        _ => None,
+    };
+
+    resolve_language(user_language.as_deref(), guild_language.as_deref(), ctx.locale())
+}
+
+/// Picks the first of the languages that still has a locale.
+pub fn resolve_language(user_language: Option<&str>, guild_language: Option<&str>, discord_locale: Option<&str>) -> String {
+    user_language.and_then(language_with_code)
+        .or_else(|| guild_language.and_then(language_with_code))
+        .or_else(|| discord_locale.and_then(language_for_discord_locale))
+        .map_or(DEFAULT_LANGUAGE.to_string(), |language| language.code.to_string())
+}
+
+/// The language the user picked, `None` if they haven't.
+pub async fn get_user_language(data: &Data, user_id: &String) -> Option<String> {
+    cached_language(data, user_id, users::get_language_code(&data.db_pool, user_id)).await
+}
+
+/// The language the guild's admins picked, `None` if they haven't.
+pub async fn get_guild_language(data: &Data, guild_id: &String) -> Option<String> {
+    cached_language(data, guild_id, guilds::get_language_code(&data.db_pool, guild_id)).await
+}
+
+/// Users and guilds share the cache like they share the prefix cache, their ids never collide.
+/// Knowing that there's no language set is cached too, so it isn't looked up on every command.
+async fn cached_language(data: &Data, discord_id: &String, lookup: impl Future<Output = anyhow::Result<Option<String>>>) -> Option<String> {
     let mut language_cache = data.language_cache.lock().await;
-    if let Some(language) = language_cache.get(user_id) {
+    if let Some(language) = data.metrics.cache_metrics.record(CacheMetrics::LANGUAGE, language_cache.get(discord_id)) {
         return language.clone();
     }
 
-    match get_language_code(&data.db_pool, user_id).await {
+    match lookup.await {
         Ok(language_code) => {
-            language_cache.put(user_id.clone(), language_code.clone());
-            language_code.clone()
+            language_cache.put(discord_id.clone(), language_code.clone());
+            language_code
         },
         Err(e) => {
-            error!("Failed to get language code: {:?}", e);
-            DEFAULT_LANGUAGE.to_string()
+            error!("Failed to get language code of {}: {:?}", discord_id, e);
+            None
         }
-        
     }
 }
 
-pub async fn set_language(data: &Data, user_id: &String, language_code: &str) {
+/// Updates the cached language of a user or guild, `None` once it was unset.
+pub async fn set_language(data: &Data, discord_id: &str, language_code: Option<&str>) {
     let mut language_cache = data.language_cache.lock().await;
-    language_cache.put(user_id.clone(), language_code.to_string());
-}
\ No newline at end of file
+    language_cache.put(discord_id.to_string(), language_code.map(str::to_string));
+}
+
+/// A language users can pick, described by the `language.*` keys of its locale.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Language {
+    pub code: &'static str,
+    /// What the language calls itself.
+    pub name: String,
+    pub flag: String,
+    pub aliases: Vec<String>,
+}
+
+impl Language {
+    /// Whether `input` is the code, the name or one of the aliases, ignoring case.
+    pub fn matches(&self, input: &str) -> bool {
+        let input = input.trim().to_lowercase();
+        input == self.code.to_lowercase() || input == self.name.to_lowercase() || self.aliases.contains(&input)
+    }
+
+    /// `**English** 🇺🇸`
+    pub fn display(&self) -> String {
+        format!("**{}** {}", self.name, self.flag).trim_end().to_string()
+    }
+}
+
+/// Looks a key up in exactly this locale, without falling back to the default one.
+pub fn translation(locale: &str, key: &str) -> Option<String> {
+    // `t!` hands back the key itself when it can't find it
+    let translated = t!(key, locale = locale);
+    (translated != key).then(|| translated.to_string())
+}
+
+/// Missing translations that were logged already, as `locale:key`.
+static REPORTED_MISSING: LazyLock<Mutex<HashSet<String>>> = LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Translates a key built at runtime, like one ending in a value from the Wolvesville API. When the locale lacks it,
+/// the default language is tried and then `fallback`, instead of showing the key itself. Each missing translation
+/// is counted in `missing_translations_total` by the key up to its last segment, and logged in full the first time.
+pub fn dynamic_translation(key: &str, locale: &str, fallback: &str) -> String {
+    if let Some(translated) = translation(locale, key) {
+        return translated;
+    }
+
+    let key_prefix = key.rsplit_once('.').map_or(key, |(prefix, _)| prefix);
+    MISSING_TRANSLATIONS.with_label_values(&[locale, key_prefix]).inc();
+    let newly_missing = REPORTED_MISSING.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).insert(format!("{}:{}", locale, key));
+    if newly_missing {
+        warn!("Translation `{}` is missing in `{}`", key, locale);
+    }
+
+    translation(DEFAULT_LANGUAGE, key).unwrap_or(fallback.to_string())
+}
+
+static LANGUAGES: LazyLock<Vec<Language>> = LazyLock::new(|| {
+    let mut languages = rust_i18n::available_locales!().into_iter()
+        .filter_map(|code| Some(Language {
+            code,
+            name: translation(code, "language.name")?,
+            flag: translation(code, "language.flag").unwrap_or_default(),
+            aliases: translation(code, "language.aliases").unwrap_or_default()
+                .split(',')
+                .map(|alias| alias.trim().to_lowercase())
+                .filter(|alias| !alias.is_empty())
+                .collect(),
+        }))
+        .collect::<Vec<_>>();
+    languages.sort_by_key(|language| language.code);
+    languages
+});
+
+/// Every language with a locale file that names it, ordered by code.
+pub fn available_languages() -> &'static [Language] {
+    &LANGUAGES
+}
+
+fn language_with_code(code: &str) -> Option<&'static Language> {
+    available_languages().iter().find(|language| language.code == code)
+}
+
+/// Discord locales are like `en-US` or `uk`, a region we have no locale for falls back to the bare language.
+pub fn language_for_discord_locale(locale: &str) -> Option<&'static Language> {
+    let matching = |locale: &str| available_languages().iter().find(|language| language.code.eq_ignore_ascii_case(locale));
+    matching(locale).or_else(|| matching(locale.split('-').next()?))
+}
+
+pub fn find_language(input: &str) -> Option<&'static Language> {
+    available_languages().iter().find(|language| language.matches(input))
+}
+
+/// The language of a code, or the default one for codes no locale exists for anymore.
+pub fn language_of(code: &str) -> &'static Language {
+    find_language(code)
+        .or_else(|| find_language(DEFAULT_LANGUAGE))
+        .expect("The default language has a locale")
+}
+
+/// Suggests the languages whose code, name or alias contains what was typed so far.
+pub async fn autocomplete_language(_ctx: Context<'_>, partial: &str) -> Vec<serenity::AutocompleteChoice> {
+    let partial = partial.trim().to_lowercase();
+    available_languages().iter()
+        .filter(|language| {
+            partial.is_empty()
+                || language.code.to_lowercase().contains(&partial)
+                || language.name.to_lowercase().contains(&partial)
+                || language.aliases.iter().any(|alias| alias.contains(&partial))
+        })
+        .map(|language| serenity::AutocompleteChoice::new(format!("{} {}", language.flag, language.name).trim().to_string(), language.code))
+        .collect()
+}
@@ -0,0 +1,89 @@
+use std::path::PathBuf;
+use anyhow::{anyhow, bail};
+use tracing_appender::rolling::Rotation;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::filter::LevelFilter;
+
+const DEFAULT_PATH: &str = "logs/main.log";
+const DEFAULT_RETENTION: usize = 14;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line.
+    Json,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileConfig {
+    /// Rotated files get the period they cover added before the extension, e.g. `main.2025-01-31.log`.
+    pub path: PathBuf,
+    pub level: LevelFilter,
+    pub format: LogFormat,
+    pub rotation: Rotation,
+    /// How many log files are kept, `0` keeps all of them.
+    pub retention: usize,
+}
+
+/// How the logger is set up.
+///
+/// Read from `LOG_LEVEL` (`EnvFilter` directives like `warn,mif=info,mif::bot::jobs=debug`), and for the log file from
+/// `LOG_FILE` (`off` disables it), `LOG_FILE_LEVEL`, `LOG_FORMAT` (`text` or `json`), `LOG_ROTATE_INTERVAL`
+/// (`never`, `hourly`, `daily` or `weekly`) and `LOG_RETENTION`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggerConfig {
+    pub filter: String,
+    pub file: Option<FileConfig>,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self::from_lookup(|_| None).expect("The default logger configuration is valid")
+    }
+}
+
+impl LoggerConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::from_lookup(|key| std::env::var(key).ok())
+    }
+
+    pub fn from_lookup(lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<Self> {
+        let lookup = |key: &str| lookup(key).map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+        // Debug builds have always logged debug lines to the terminal. Other crates only get to warn by default.
+        let default_level = if cfg!(debug_assertions) { "debug" } else { "info" };
+        let filter = lookup("LOG_LEVEL").unwrap_or(format!("warn,{}={}", env!("CARGO_CRATE_NAME"), default_level));
+        EnvFilter::try_new(&filter).map_err(|e| anyhow!("LOG_LEVEL is invalid: {}", e))?;
+
+        let path = lookup("LOG_FILE").unwrap_or(DEFAULT_PATH.to_string());
+        if matches!(path.to_lowercase().as_str(), "off" | "none" | "false") {
+            return Ok(Self { filter, file: None });
+        }
+
+        let level = match lookup("LOG_FILE_LEVEL") {
+            Some(level) => level.parse().map_err(|e| anyhow!("LOG_FILE_LEVEL is invalid: {}", e))?,
+            None => LevelFilter::INFO,
+        };
+        let format = match lookup("LOG_FORMAT").map(|format| format.to_lowercase()).as_deref() {
+            None | Some("text") => LogFormat::Text,
+            Some("json") => LogFormat::Json,
+            Some(other) => bail!("LOG_FORMAT must be text or json, got `{}`", other),
+        };
+        let rotation = match lookup("LOG_ROTATE_INTERVAL").map(|interval| interval.to_lowercase()).as_deref() {
+            None | Some("daily") => Rotation::DAILY,
+            Some("hourly") => Rotation::HOURLY,
+            Some("weekly") => Rotation::WEEKLY,
+            Some("never") => Rotation::NEVER,
+            Some(other) => bail!("LOG_ROTATE_INTERVAL must be never, hourly, daily or weekly, got `{}`", other),
+        };
+        let retention = match lookup("LOG_RETENTION") {
+            Some(retention) => retention.parse().map_err(|e| anyhow!("LOG_RETENTION `{}` is not a number: {}", retention, e))?,
+            None => DEFAULT_RETENTION,
+        };
+
+        Ok(Self {
+            filter,
+            file: Some(FileConfig { path: PathBuf::from(path), level, format, rotation, retention }),
+        })
+    }
+}
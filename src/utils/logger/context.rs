@@ -0,0 +1,33 @@
+use tracing::field::Empty;
+use tracing::{info_span, Instrument, Span};
+
+/// What a log line was written on behalf of. Filled in for command invocations and attached to every line logged
+/// while the command runs, as long as it is logged from the task handling the event.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogContext {
+    pub command: Option<String>,
+    pub invocation_id: Option<String>,
+    pub guild_id: Option<String>,
+    pub user_id: Option<String>,
+}
+
+/// Runs `future` in its own `event` span, with the context fields still empty.
+pub async fn scope<F: Future>(future: F) -> F::Output {
+    let span = info_span!("event", command = Empty, invocation_id = Empty, guild_id = Empty, user_id = Empty);
+    future.instrument(span).await
+}
+
+/// Records the context on the span of the current scope. Does nothing outside of `scope`.
+pub fn set(context: LogContext) {
+    let span = Span::current();
+    for (name, value) in [
+        ("command", context.command),
+        ("invocation_id", context.invocation_id),
+        ("guild_id", context.guild_id),
+        ("user_id", context.user_id),
+    ] {
+        if let Some(value) = value {
+            span.record(name, value.as_str());
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::any::Any;
+use std::fs;
+use std::io;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const DEFAULT_DIR: &str = "logs/crashes";
+/// How long a report is kept around after it was delivered.
+pub const DELIVERED_RETENTION: Duration = Duration::days(30);
+
+/// Where crash reports are written, `CRASH_REPORT_DIR` or `logs/crashes`.
+pub fn crash_dir() -> PathBuf {
+    std::env::var("CRASH_REPORT_DIR").ok()
+        .filter(|dir| !dir.trim().is_empty())
+        .map_or(PathBuf::from(DEFAULT_DIR), PathBuf::from)
+}
+
+/// Everything known about a panic, written to its own file so it survives the process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub occurred_at: DateTime<Utc>,
+    pub thread: String,
+    pub payload: String,
+    pub location: String,
+    pub backtrace: String,
+    pub recent_log_lines: Vec<String>,
+    /// Set once the report was posted to the developers.
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+impl CrashReport {
+    pub fn from_panic(panic_info: &PanicHookInfo, recent_log_lines: Vec<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().simple().to_string()[..8].to_uppercase(),
+            occurred_at: Utc::now(),
+            thread: std::thread::current().name().unwrap_or("<unnamed>").to_string(),
+            payload: payload_text(panic_info.payload()),
+            location: panic_info.location().map_or("<unknown>".to_string(), |loc| {
+                format!("{}:{}:{}", loc.file(), loc.line(), loc.column())
+            }),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+            recent_log_lines,
+            delivered_at: None,
+        }
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("crash-{}-{}.json", self.occurred_at.format("%Y-%m-%dT%H-%M-%S"), self.id)
+    }
+
+    pub fn write(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(self.file_name());
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(path)
+    }
+
+    /// Rewrites the report at `path` with the delivery time set.
+    pub fn mark_delivered(&mut self, path: &Path, at: DateTime<Utc>) -> io::Result<()> {
+        self.delivered_at = Some(at);
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Panics with a message carry a `&str` when it's a literal and a `String` when it was formatted.
+pub fn payload_text(payload: &(dyn Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|payload| payload.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or("<unknown>".to_string())
+}
+
+/// The reports in `dir`, oldest first. Unreadable files are skipped.
+fn reports(dir: &Path) -> io::Result<Vec<(PathBuf, CrashReport)>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut reports: Vec<(PathBuf, CrashReport)> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .filter_map(|path| {
+            let report = serde_json::from_str::<CrashReport>(&fs::read_to_string(&path).ok()?).ok()?;
+            Some((path, report))
+        })
+        .collect();
+    reports.sort_by_key(|(_, report)| report.occurred_at);
+    Ok(reports)
+}
+
+/// Reports in `dir` that haven't been delivered yet, oldest first. Unreadable files are skipped.
+pub fn undelivered(dir: &Path) -> io::Result<Vec<(PathBuf, CrashReport)>> {
+    Ok(reports(dir)?.into_iter().filter(|(_, report)| report.delivered_at.is_none()).collect())
+}
+
+/// Deletes the reports in `dir` that were delivered more than `retention` before `now`, returning how many.
+pub fn prune_delivered(dir: &Path, now: DateTime<Utc>, retention: Duration) -> io::Result<usize> {
+    let mut pruned = 0;
+    for (path, report) in reports(dir)? {
+        if report.delivered_at.is_some_and(|delivered_at| now - delivered_at > retention) {
+            fs::remove_file(path)?;
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
+}
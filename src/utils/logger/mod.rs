@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+use std::io;
+use std::panic::{self, PanicHookInfo};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock, TryLockError};
+use chrono::Local;
+use tracing_subscriber::fmt::format::Writer;
+use tracing_subscriber::fmt::time::FormatTime;
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Layered, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Layer, Registry};
+
+pub mod config;
+pub mod context;
+pub mod crash;
+#[cfg(test)]
+mod tests;
+
+pub use config::{LogFormat, LoggerConfig};
+pub use context::LogContext;
+
+/// The log macros, imported from here rather than from the logging crate so it can be swapped out in one place.
+#[allow(unused_imports)]
+pub(crate) use tracing::{debug, error, info, warn};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+/// How many of the latest lines go into a crash report.
+const RECENT_LINES: usize = 100;
+
+/// Everything below the filter, which the output layers are added to.
+type Filtered = Layered<reload::Layer<EnvFilter, Registry>, Registry>;
+type BoxedLayer = Box<dyn Layer<Filtered> + Send + Sync>;
+
+/// The filter applied to every output, with the directives it was created from since `EnvFilter` can't be cloned.
+struct FilterHandle {
+    handle: reload::Handle<EnvFilter, Registry>,
+    initial: String,
+}
+
+static FILTER: OnceLock<FilterHandle> = OnceLock::new();
+static RECENT: LazyLock<LineBuffer> = LazyLock::new(|| LineBuffer::new(RECENT_LINES));
+
+/// Timestamps in local time, like `2025-01-31 23:59:59` or `2025-01-31T23:59:59.123+02:00`.
+pub enum LocalTime {
+    Text,
+    Rfc3339,
+}
+
+impl FormatTime for LocalTime {
+    fn format_time(&self, w: &mut Writer<'_>) -> std::fmt::Result {
+        match self {
+            LocalTime::Text => write!(w, "{}", Local::now().format(TIMESTAMP_FORMAT)),
+            LocalTime::Rfc3339 => write!(w, "{}", Local::now().to_rfc3339()),
+        }
+    }
+}
+
+/// Keeps the latest lines written to it in memory, for crash reports.
+#[derive(Clone)]
+pub struct LineBuffer {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+}
+
+impl LineBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self { lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))), capacity }
+    }
+
+    /// The lines kept, oldest first. Empty if they're locked, since a panic while logging
+    /// would otherwise deadlock the panic hook.
+    pub fn lines(&self) -> Vec<String> {
+        match self.lines.try_lock() {
+            Ok(lines) => lines.iter().cloned().collect(),
+            Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner().iter().cloned().collect(),
+            Err(TryLockError::WouldBlock) => Vec::new(),
+        }
+    }
+
+    fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
+
+/// Collects what's written for a single event and adds it to the buffer once the event is done.
+pub struct LineWriter {
+    buffer: LineBuffer,
+    bytes: Vec<u8>,
+}
+
+impl io::Write for LineWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for LineWriter {
+    fn drop(&mut self) {
+        let line = String::from_utf8_lossy(&self.bytes);
+        let line = line.trim_end();
+        if !line.is_empty() {
+            self.buffer.push(line.to_string());
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LineBuffer {
+    type Writer = LineWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LineWriter { buffer: self.clone(), bytes: Vec::new() }
+    }
+}
+
+/// `2025-01-31 23:59:59  INFO event{command=ping}: mif::bot: message`, the span shows the context fields when there are any.
+pub fn text_layer<S, W>(writer: W, colored: bool) -> impl Layer<S> + Send + Sync
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    fmt::layer().with_timer(LocalTime::Text).with_ansi(colored).with_writer(writer)
+}
+
+/// One JSON object per line with the timestamp, level, target, the event's fields and the context under `span`.
+pub fn json_layer<S, W>(writer: W) -> impl Layer<S> + Send + Sync
+where
+    S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    W: for<'w> MakeWriter<'w> + Send + Sync + 'static,
+{
+    fmt::layer().json().flatten_event(true).with_current_span(true).with_span_list(false)
+        .with_timer(LocalTime::Rfc3339).with_writer(writer)
+}
+
+/// Sets up the logger from the environment, see `LoggerConfig`. An invalid configuration falls back to the defaults.
+pub fn install_subscriber() {
+    let config = LoggerConfig::from_env().unwrap_or_else(|e| {
+        eprintln!("Invalid logging configuration, using the defaults: {}", e);
+        LoggerConfig::default()
+    });
+
+    let (filter, handle) = reload::Layer::new(EnvFilter::new(&config.filter));
+    let mut layers: Vec<BoxedLayer> = vec![
+        text_layer(io::stdout, true).boxed(),
+        text_layer(RECENT.clone(), false).boxed(),
+    ];
+
+    if let Some(file_config) = config.file {
+        let directory = file_config.path.parent().filter(|parent| !parent.as_os_str().is_empty())
+            .map_or(std::path::PathBuf::from("."), |parent| parent.to_path_buf());
+        let mut appender = tracing_appender::rolling::Builder::new()
+            .rotation(file_config.rotation.clone())
+            .filename_prefix(file_config.path.file_stem().map_or("log".into(), |stem| stem.to_string_lossy()));
+        if let Some(extension) = file_config.path.extension() {
+            appender = appender.filename_suffix(extension.to_string_lossy());
+        }
+        if file_config.retention > 0 {
+            appender = appender.max_log_files(file_config.retention);
+        }
+
+        // Created up front, the appender would otherwise complain that there is nothing to prune yet
+        let appender = std::fs::create_dir_all(&directory).map_err(anyhow::Error::from)
+            .and_then(|_| Ok(appender.build(&directory)?));
+        match appender {
+            Ok(appender) => layers.push(match file_config.format {
+                LogFormat::Text => text_layer(appender, false).with_filter(file_config.level).boxed(),
+                LogFormat::Json => json_layer(appender).with_filter(file_config.level).boxed(),
+            }),
+            Err(e) => eprintln!("Failed to open the log file {}, logging to the terminal only: {}", file_config.path.display(), e),
+        }
+    }
+
+    if let Err(e) = tracing_subscriber::registry().with(filter).with(layers).try_init() {
+        eprintln!("Failed to install the logger: {}", e);
+        return;
+    }
+    let _ = FILTER.set(FilterHandle { handle, initial: config.filter });
+}
+
+/// The directives currently logged.
+pub fn current_filter() -> String {
+    FILTER.get()
+        .and_then(|filter| filter.handle.with_current(|current| current.to_string()).ok())
+        .unwrap_or(LoggerConfig::default().filter)
+}
+
+/// Changes the levels logged until the next restart, or back to `LOG_LEVEL` when `filter` is `None`.
+pub fn set_filter(filter: Option<EnvFilter>) -> String {
+    let Some(current) = FILTER.get() else { return current_filter() };
+    let filter = filter.unwrap_or_else(|| EnvFilter::new(&current.initial));
+    if let Err(e) = current.handle.reload(filter) {
+        error!("Failed to change the log levels: {}", e);
+    }
+    current_filter()
+}
+
+/// The latest lines logged, oldest first.
+pub fn recent_lines() -> Vec<String> {
+    RECENT.lines()
+}
+
+/// Writes every panic to its own crash report, see `crash::CrashReport`, which is posted to the developers on the next start.
+/// The hook prints to stderr itself rather than logging, a panic while a log line is written would deadlock otherwise.
+pub fn set_up_panic_hook() {
+    panic::set_hook(Box::new(|panic_info: &PanicHookInfo| {
+        let report = crash::CrashReport::from_panic(panic_info, recent_lines());
+
+        eprintln!("\nCRITICAL ERROR: A panic occurred in thread '{}'.", report.thread);
+        eprintln!("Panic payload: \"{}\" at {}", report.payload, report.location);
+        eprintln!("Backtrace:\n{}", report.backtrace);
+        match report.write(&crash::crash_dir()) {
+            Ok(path) => eprintln!("Crash report {} written to {}", report.id, path.display()),
+            Err(e) => eprintln!("Failed to write crash report {}: {}", report.id, e),
+        }
+    }));
+}
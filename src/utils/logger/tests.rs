@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use chrono::{TimeZone, Utc};
+use tracing_appender::rolling::Rotation;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use crate::utils::logger::config::FileConfig;
+use crate::utils::logger::crash::{payload_text, prune_delivered, undelivered, CrashReport};
+use crate::utils::logger::{context, info, json_layer, text_layer, warn, LineBuffer, LogContext, LogFormat, LoggerConfig};
+
+fn config_from(vars: &[(&str, &str)]) -> anyhow::Result<LoggerConfig> {
+    let vars: HashMap<String, String> = vars.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect();
+    LoggerConfig::from_lookup(|key| vars.get(key).cloned())
+}
+
+fn temp_log_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mif-logger-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_logger_config() {
+    let config = config_from(&[("LOG_LEVEL", "error")]).unwrap();
+    assert_eq!(config.filter, "error");
+    assert_eq!(config.file, Some(FileConfig {
+        path: PathBuf::from("logs/main.log"),
+        level: LevelFilter::INFO,
+        format: LogFormat::Text,
+        rotation: Rotation::DAILY,
+        retention: 14,
+    }));
+    // Other crates stay quiet unless asked for
+    assert!(config_from(&[]).unwrap().filter.starts_with("warn,mif="));
+
+    let config = config_from(&[
+        ("LOG_LEVEL", "warn,mif::bot::jobs=debug"),
+        ("LOG_FILE", "/var/log/mif/bot.log"),
+        ("LOG_FILE_LEVEL", "debug"),
+        ("LOG_FORMAT", "JSON"),
+        ("LOG_ROTATE_INTERVAL", "hourly"),
+        ("LOG_RETENTION", "3"),
+    ]).unwrap();
+    assert_eq!(config.filter, "warn,mif::bot::jobs=debug");
+    let file = config.file.unwrap();
+    assert_eq!(file.path, PathBuf::from("/var/log/mif/bot.log"));
+    assert_eq!((file.level, file.format, file.rotation, file.retention), (LevelFilter::DEBUG, LogFormat::Json, Rotation::HOURLY, 3));
+
+    assert_eq!(config_from(&[("LOG_FILE", "off")]).unwrap().file, None);
+    assert!(config_from(&[("LOG_LEVEL", "bot=loud")]).is_err());
+    assert!(config_from(&[("LOG_FILE_LEVEL", "loud")]).is_err());
+    assert!(config_from(&[("LOG_FORMAT", "xml")]).is_err());
+    assert!(config_from(&[("LOG_ROTATE_INTERVAL", "monthly")]).is_err());
+    assert!(config_from(&[("LOG_RETENTION", "-1")]).is_err());
+}
+
+#[tokio::test]
+async fn test_lines_carry_the_command_context() {
+    let text = LineBuffer::new(10);
+    let json = LineBuffer::new(10);
+    let subscriber = tracing_subscriber::registry()
+        .with(text_layer(text.clone(), false))
+        .with(json_layer(json.clone()));
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    info!("before");
+    context::scope(async {
+        warn!("no command yet");
+        context::set(LogContext {
+            command: Some("wolvesville player search".to_string()),
+            invocation_id: Some("42".to_string()),
+            guild_id: None,
+            user_id: Some("7".to_string()),
+        });
+        tokio::task::yield_now().await;
+        info!("a \"quoted\"\nline");
+    }).await;
+
+    let lines = text.lines();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].ends_with("INFO mif::utils::logger::tests: before"));
+    assert!(lines[1].contains("WARN event: mif::utils::logger::tests: no command yet"));
+    assert!(lines[2].contains(r#"INFO event{command="wolvesville player search" invocation_id="42" user_id="7"}: mif::utils::logger::tests: a "quoted""#));
+
+    let lines: Vec<serde_json::Value> = json.lines().iter().map(|line| serde_json::from_str(line).unwrap()).collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].get("span").is_none());
+    assert_eq!(lines[2]["level"], "INFO");
+    assert_eq!(lines[2]["target"], "mif::utils::logger::tests");
+    assert_eq!(lines[2]["message"], "a \"quoted\"\nline");
+    assert_eq!(lines[2]["span"]["command"], "wolvesville player search");
+    assert_eq!(lines[2]["span"]["invocation_id"], "42");
+    assert_eq!(lines[2]["span"]["user_id"], "7");
+    assert!(lines[2]["span"].get("guild_id").is_none());
+    assert!(chrono::DateTime::parse_from_rfc3339(lines[2]["timestamp"].as_str().unwrap()).is_ok());
+}
+
+#[test]
+fn test_line_buffer_keeps_the_latest_lines() {
+    let buffer = LineBuffer::new(2);
+    let subscriber = tracing_subscriber::registry().with(text_layer(buffer.clone(), false));
+    tracing::subscriber::with_default(subscriber, || {
+        for i in 0..3 {
+            info!("line {}", i);
+        }
+    });
+
+    let lines = buffer.lines();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("line 1"));
+    assert!(lines[1].ends_with("line 2"));
+}
+
+#[test]
+fn test_panic_payloads() {
+    let literal: Box<dyn std::any::Any + Send> = Box::new("literal");
+    let formatted: Box<dyn std::any::Any + Send> = Box::new(format!("index {} out of bounds", 3));
+    let other: Box<dyn std::any::Any + Send> = Box::new(42);
+
+    assert_eq!(payload_text(literal.as_ref()), "literal");
+    assert_eq!(payload_text(formatted.as_ref()), "index 3 out of bounds");
+    assert_eq!(payload_text(other.as_ref()), "<unknown>");
+}
+
+#[test]
+fn test_crash_reports_are_delivered_once() {
+    let dir = temp_log_dir();
+    let report = |id: &str, minute: u32| CrashReport {
+        id: id.to_string(),
+        occurred_at: Utc.with_ymd_and_hms(2025, 1, 1, 12, minute, 0).unwrap(),
+        thread: "tokio-runtime-worker".to_string(),
+        payload: "boom".to_string(),
+        location: "src/main.rs:1:1".to_string(),
+        backtrace: String::new(),
+        recent_log_lines: vec!["2025-01-01 12:00:00 [INFO] mif::bot | Connected".to_string()],
+        delivered_at: None,
+    };
+
+    report("NEWER", 5).write(&dir).unwrap();
+    report("OLDER", 1).write(&dir).unwrap();
+    std::fs::write(dir.join("not-a-report.json"), "{").unwrap();
+
+    let pending = undelivered(&dir).unwrap();
+    assert_eq!(pending.iter().map(|(_, report)| report.id.as_str()).collect::<Vec<_>>(), vec!["OLDER", "NEWER"]);
+    assert_eq!(pending[0].1, report("OLDER", 1));
+
+    let (path, mut delivered) = pending.into_iter().next().unwrap();
+    delivered.mark_delivered(&path, Utc::now()).unwrap();
+    let pending = undelivered(&dir).unwrap();
+    assert_eq!(pending.iter().map(|(_, report)| report.id.as_str()).collect::<Vec<_>>(), vec!["NEWER"]);
+
+    assert!(undelivered(&dir.join("missing")).unwrap().is_empty());
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_delivered_crash_reports_are_pruned() {
+    let dir = temp_log_dir();
+    let now = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+    let report = |id: &str, delivered_days_ago: Option<i64>| CrashReport {
+        id: id.to_string(),
+        occurred_at: Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap(),
+        thread: "main".to_string(),
+        payload: "boom".to_string(),
+        location: "src/main.rs:1:1".to_string(),
+        backtrace: String::new(),
+        recent_log_lines: Vec::new(),
+        delivered_at: delivered_days_ago.map(|days| now - chrono::Duration::days(days)),
+    };
+
+    let old = report("OLD", Some(31)).write(&dir).unwrap();
+    let recent = report("RECENT", Some(1)).write(&dir).unwrap();
+    let pending = report("PENDING", None).write(&dir).unwrap();
+
+    assert_eq!(prune_delivered(&dir, now, chrono::Duration::days(30)).unwrap(), 1);
+    assert!(!old.exists());
+    assert!(recent.exists());
+    assert!(pending.exists());
+    assert_eq!(prune_delivered(&dir, now, chrono::Duration::days(30)).unwrap(), 0);
+    assert_eq!(prune_delivered(&dir.join("missing"), now, chrono::Duration::days(30)).unwrap(), 0);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,104 @@
+use std::sync::LazyLock;
+use std::time::Instant;
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry};
+use reqwest::StatusCode;
+
+/// Outbound HTTP requests. These are made by free functions without access to `Data`,
+/// so the metrics are global and `MetricsManager` registers them next to its own.
+pub static HTTP_METRICS: LazyLock<HttpMetrics> = LazyLock::new(HttpMetrics::new);
+
+/// Time spent composing images, labeled by what was rendered. Downloads are not included.
+pub static RENDER_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| HistogramVec::new(
+    HistogramOpts::new("image_render_duration_seconds", "Time spent rendering an image")
+        .buckets(vec![0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0]),
+    &["image"],
+).unwrap());
+
+/// Lookups of translation keys that only exist at runtime and are missing in a locale, see `language::dynamic_translation`.
+/// Labelled by the key without its last segment, which comes from outside and would make the label values unbounded.
+pub static MISSING_TRANSLATIONS: LazyLock<IntCounterVec> = LazyLock::new(|| IntCounterVec::new(
+    Opts::new("missing_translations_total", "Translations that were looked up but don't exist in the locale"),
+    &["locale", "key_prefix"],
+).unwrap());
+
+pub fn register(registry: &Registry) {
+    registry.register(Box::new(HTTP_METRICS.requests.clone())).unwrap();
+    registry.register(Box::new(HTTP_METRICS.duration.clone())).unwrap();
+    registry.register(Box::new(HTTP_METRICS.in_flight.clone())).unwrap();
+    registry.register(Box::new(RENDER_DURATION.clone())).unwrap();
+    registry.register(Box::new(MISSING_TRANSLATIONS.clone())).unwrap();
+}
+
+pub struct HttpMetrics {
+    requests: IntCounterVec,
+    duration: HistogramVec,
+    in_flight: IntGaugeVec,
+}
+
+impl HttpMetrics {
+    fn new() -> Self {
+        Self {
+            requests: IntCounterVec::new(
+                Opts::new("http_client_requests_total", "Outbound HTTP requests by the class of their response status"),
+                &["service", "endpoint", "status"],
+            ).unwrap(),
+            duration: HistogramVec::new(
+                HistogramOpts::new("http_client_request_duration_seconds", "Time from sending an outbound request until its body was read")
+                    .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+                &["service", "endpoint"],
+            ).unwrap(),
+            in_flight: IntGaugeVec::new(
+                Opts::new("http_client_requests_in_flight", "Outbound HTTP requests waiting for a response"),
+                &["service"],
+            ).unwrap(),
+        }
+    }
+
+    /// Starts tracking a request. `endpoint` has to be a template like `/players/{id}` rather than the
+    /// actual path, to keep the number of label values bounded.
+    pub fn start(&'static self, service: &'static str, endpoint: &str) -> TrackedRequest {
+        self.in_flight.with_label_values(&[service]).inc();
+        TrackedRequest {
+            metrics: self,
+            service,
+            endpoint: endpoint.to_string(),
+            status: None,
+            started: Instant::now(),
+        }
+    }
+}
+
+/// A request in flight. It is recorded when dropped, so a request that never got a response,
+/// because of an early return on a transport error, counts with the status `error`.
+pub struct TrackedRequest {
+    metrics: &'static HttpMetrics,
+    service: &'static str,
+    endpoint: String,
+    status: Option<StatusCode>,
+    started: Instant,
+}
+
+impl TrackedRequest {
+    pub fn set_status(&mut self, status: StatusCode) {
+        self.status = Some(status);
+    }
+}
+
+impl Drop for TrackedRequest {
+    fn drop(&mut self) {
+        let status = self.status.map(status_class).unwrap_or("error");
+        self.metrics.in_flight.with_label_values(&[self.service]).dec();
+        self.metrics.requests.with_label_values(&[self.service, &self.endpoint, status]).inc();
+        self.metrics.duration.with_label_values(&[self.service, &self.endpoint]).observe(self.started.elapsed().as_secs_f64());
+    }
+}
+
+pub fn status_class(status: StatusCode) -> &'static str {
+    match status.as_u16() {
+        100..=199 => "1xx",
+        200..=299 => "2xx",
+        300..=399 => "3xx",
+        400..=499 => "4xx",
+        _ => "5xx",
+    }
+}
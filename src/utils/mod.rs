@@ -1,30 +1,31 @@
 pub mod language;
+pub mod format;
 pub mod logger;
 pub mod time;
 pub mod apicallers;
 pub mod scheduler;
 pub mod image;
 pub mod math;
+pub mod wolvesville;
+pub mod metrics;
+#[cfg(test)]
 mod tests;
 
 pub fn get_first_part_of_string(input: &String, delimiter: char) -> String {
     input.split_once(delimiter).map_or(input.clone(), |(first, _)| first.to_string())
 }
 
-pub fn comma_readable_number(number: i64) -> String {
-    let mut result = String::new();
-    let number_string = number.abs().to_string();
-    let mut count = 0;
-    for c in number_string.chars().rev() {
-        if count == 3 {
-            result.push(',');
-            count = 0;
-        }
-        result.push(c);
-        count += 1;
+/// Cuts `text` down to `max_chars` characters, marking the cut with an ellipsis.
+pub fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((index, _)) => format!("{}…", &text[..index]),
+        None => text.to_string(),
     }
-    if number < 0 {
-        result.push('-');
-    }
-    result.chars().rev().collect()
-}
\ No newline at end of file
+}
+
+/// Turns an API constant like `JOIN_BY_REQUEST` into `Join by request`.
+pub fn humanize(value: &str) -> String {
+    let words = value.replace('_', " ").to_lowercase();
+    let mut chars = words.chars();
+    chars.next().map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+}
@@ -0,0 +1,880 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use crate::utils::logger::{error, info, warn};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::SqlitePool;
+use tokio::sync::{Mutex, Semaphore};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+use crate::db::jobs::{get_all_jobs, delete_job, add_job, quarantine_job, update_job_args, CorruptJob, StoredJob};
+use crate::utils::time::{system_clock, Clock};
+
+#[cfg(test)]
+mod tests;
+
+pub type JobFn = Arc<
+    dyn Fn(serde_json::Value) -> Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send>>
+    + Send
+    + Sync,
+>;
+
+/// A kind of job the scheduler can run. Implementors are registered once in `JobRegistry`,
+/// and every stored job with a matching `NAME` is run by them with its own `Args`.
+pub trait Job: Send + Sync + 'static {
+    /// Name stored in the `jobs` table. Must never change once jobs using it have been stored.
+    const NAME: &'static str;
+    /// Version of the `Args` layout. Bump it when `Args` changes and teach `migrate_args` to upgrade the previous version.
+    const ARGS_VERSION: u32 = 1;
+
+    type Args: Serialize + DeserializeOwned + Send + 'static;
+
+    /// Checks arguments before a job is stored. Rejected arguments never reach the `jobs` table.
+    fn validate(_args: &Self::Args) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Upgrades stored arguments from `from_version` to `from_version + 1`.
+    /// Called repeatedly at load time until the arguments reach `ARGS_VERSION`.
+    fn migrate_args(from_version: u32, _args: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        Err(anyhow::anyhow!("No migration for {} arguments from version {}", Self::NAME, from_version))
+    }
+
+    fn run(&self, args: Self::Args) -> impl Future<Output = anyhow::Result<()>> + Send;
+}
+
+/// How many jobs may run at the same time across the whole scheduler. Runs over the limit wait for a free slot.
+pub const DEFAULT_MAX_CONCURRENT_JOBS: usize = 8;
+/// How often the run loop renews the lease and checks for due jobs.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// How long a lease stays valid without a heartbeat. A few check intervals, so a single slow tick doesn't hand it over.
+const DEFAULT_LEASE_DURATION: std::time::Duration = std::time::Duration::from_secs(180);
+/// Every scheduler sharing a database competes for this one lease.
+const LEASE_NAME: &str = "scheduler";
+/// How often the leader reloads the `jobs` table, to pick up jobs added or changed through a standby instance.
+const STORE_RELOAD_INTERVAL: Duration = Duration::minutes(5);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value")]
+pub enum Schedule {
+    Once(DateTime<Utc>),
+    Interval(Duration),
+    Cron(CronSchedule),
+}
+
+/// A cron expression evaluated in an IANA timezone, so "every day at 9:00" stays at 9:00 local time across DST changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredCronSchedule")]
+pub struct CronSchedule {
+    pub expression: String,
+    pub timezone: Tz,
+}
+
+/// Cron schedules used to be stored as a bare expression string. Those rows are still accepted and evaluated in UTC.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCronSchedule {
+    Legacy(String),
+    Zoned { expression: String, timezone: Tz },
+}
+
+impl From<StoredCronSchedule> for CronSchedule {
+    fn from(stored: StoredCronSchedule) -> Self {
+        match stored {
+            StoredCronSchedule::Legacy(expression) => CronSchedule { expression, timezone: Tz::UTC },
+            StoredCronSchedule::Zoned { expression, timezone } => CronSchedule { expression, timezone },
+        }
+    }
+}
+
+impl CronSchedule {
+    pub fn new(expression: &str, timezone: Tz) -> anyhow::Result<Self> {
+        cron::Schedule::from_str(expression)
+            .map_err(|e| anyhow::anyhow!("Invalid cron expression '{}': {}", expression, e))?;
+
+        Ok(Self { expression: expression.to_string(), timezone })
+    }
+
+    /// Finds the next fire time strictly after `after`. The expression is matched against wall-clock time in `timezone`,
+    /// local times skipped by a DST jump never fire, and repeated ones fire once.
+    pub fn next_run(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        cron::Schedule::from_str(&self.expression).ok()?
+            .after(&after.with_timezone(&self.timezone))
+            .next()
+            .map(|t| t.with_timezone(&Utc))
+    }
+}
+
+impl Schedule {
+    pub fn next_run(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Once(t) => (*t > after).then_some(*t),
+            Schedule::Interval(duration) => Some(after + *duration),
+            Schedule::Cron(cron) => cron.next_run(after),
+        }
+    }
+
+    /// When a job that is added or loaded at `now` should run first. Unlike `next_run`, a one-off time that has
+    /// already passed, e.g. a reminder that came due while the bot was down, runs right away instead of never.
+    pub fn first_run(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Once(t) => Some((*t).max(now)),
+            _ => self.next_run(now),
+        }
+    }
+}
+
+/// What to do when a job becomes due while its previous run is still in progress.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapPolicy {
+    /// Start another run alongside the one in progress.
+    #[default]
+    Allow,
+    /// Drop the new run.
+    Skip,
+    /// Run once more after the current run finishes. Further due runs while one is already queued are dropped.
+    Queue,
+}
+
+impl OverlapPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OverlapPolicy::Allow => "allow",
+            OverlapPolicy::Skip => "skip",
+            OverlapPolicy::Queue => "queue",
+        }
+    }
+}
+
+impl FromStr for OverlapPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(OverlapPolicy::Allow),
+            "skip" => Ok(OverlapPolicy::Skip),
+            "queue" => Ok(OverlapPolicy::Queue),
+            other => Err(anyhow::anyhow!("Unknown overlap policy '{}'", other)),
+        }
+    }
+}
+
+/// Per-job execution settings, persisted alongside the job definition.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JobOptions {
+    pub overlap: OverlapPolicy,
+    /// Runs taking longer than this are cancelled. `None` lets a run take as long as it needs.
+    pub timeout: Option<std::time::Duration>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobDefinition {
+    pub id: Uuid,
+    pub name: String,
+    pub schedule: Schedule,
+    pub created_at: DateTime<Utc>,
+    pub args: serde_json::Value,
+    /// The `Job::ARGS_VERSION` that `args` were written with.
+    pub args_version: u32,
+    pub options: JobOptions,
+    /// Paused jobs stay in the schedule but are not run until resumed.
+    pub enabled: bool,
+}
+
+/// How the most recent run of a job ended.
+#[derive(Debug, Clone)]
+pub enum JobOutcome {
+    Succeeded,
+    Failed(String),
+    TimedOut,
+}
+
+/// A snapshot of a scheduled job for display purposes.
+#[derive(Debug, Clone)]
+pub struct JobStatus {
+    pub definition: JobDefinition,
+    pub next_run: DateTime<Utc>,
+    pub last_outcome: Option<(DateTime<Utc>, JobOutcome)>,
+    pub running: bool,
+}
+
+pub struct ScheduledJob {
+    definition: JobDefinition,
+    last_run: Option<DateTime<Utc>>,
+    next_run: DateTime<Utc>,
+    runtime: Arc<JobRuntime>,
+}
+
+impl ScheduledJob {
+    fn new(definition: JobDefinition, next_run: DateTime<Utc>) -> Self {
+        Self {
+            definition,
+            last_run: None,
+            next_run,
+            runtime: Arc::new(JobRuntime::default()),
+        }
+    }
+
+    fn status(&self) -> JobStatus {
+        JobStatus {
+            definition: self.definition.clone(),
+            next_run: self.next_run,
+            last_outcome: self.runtime.last_outcome.lock().unwrap().clone(),
+            running: self.runtime.running.try_lock().is_err(),
+        }
+    }
+}
+
+/// State shared between a scheduled job and its spawned runs: the in-progress and queued runs for its `OverlapPolicy`,
+/// and the outcome of the latest finished run.
+#[derive(Default)]
+struct JobRuntime {
+    running: Arc<Mutex<()>>,
+    queued: AtomicBool,
+    last_outcome: std::sync::Mutex<Option<(DateTime<Utc>, JobOutcome)>>,
+}
+
+/// Everything a spawned task needs to perform one run of a job.
+struct JobRun {
+    id: Uuid,
+    name: String,
+    args: serde_json::Value,
+    timeout: Option<std::time::Duration>,
+    job_fn: JobFn,
+    concurrency: Arc<Semaphore>,
+    runtime: Arc<JobRuntime>,
+    clock: Arc<dyn Clock>,
+}
+
+impl From<anyhow::Result<()>> for JobOutcome {
+    fn from(result: anyhow::Result<()>) -> Self {
+        match result {
+            Ok(()) => JobOutcome::Succeeded,
+            Err(e) => JobOutcome::Failed(format!("{:#}", e)),
+        }
+    }
+}
+
+impl JobRun {
+    async fn execute(self) {
+        let _permit = match self.concurrency.acquire_owned().await {
+            Ok(permit) => permit,
+            Err(_) => {
+                error!("Job concurrency limiter closed, dropping run: id = {}, name = {}", self.id, self.name);
+                return;
+            }
+        };
+
+        info!("Executing job: id = {}, name = {}", self.id, self.name);
+        let future = (self.job_fn)(self.args);
+        let outcome = match self.timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, future).await {
+                Ok(result) => result.into(),
+                Err(_) => JobOutcome::TimedOut,
+            },
+            None => future.await.into(),
+        };
+
+        match &outcome {
+            JobOutcome::Succeeded => {},
+            JobOutcome::Failed(e) => error!("Failed to execute job: id = {}, name = {}: {}", self.id, self.name, e),
+            JobOutcome::TimedOut => error!("Job timed out after {:?} and was cancelled: id = {}, name = {}",
+                self.timeout.unwrap_or_default(), self.id, self.name),
+        }
+
+        *self.runtime.last_outcome.lock().unwrap() = Some((self.clock.now(), outcome));
+    }
+}
+
+/// A registered `Job` with its argument type erased, so jobs of different types can share one registry.
+#[derive(Clone)]
+struct RegisteredJob {
+    run: JobFn,
+    args_version: u32,
+    check_args: fn(&serde_json::Value) -> anyhow::Result<()>,
+    migrate_args: fn(u32, serde_json::Value) -> anyhow::Result<serde_json::Value>,
+}
+
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    jobs: HashMap<String, RegisteredJob>,
+}
+
+struct SchedulerState {
+    jobs: HashMap<Uuid, ScheduledJob>,
+}
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a job under `J::NAME`. Stored arguments are deserialized into `J::Args` right before each run.
+    pub fn register<J: Job>(&mut self, job: J) {
+        let job = Arc::new(job);
+        let run: JobFn = Arc::new(move |value| {
+            let job = job.clone();
+            Box::pin(async move {
+                let args = serde_json::from_value::<J::Args>(value)
+                    .map_err(|e| anyhow::anyhow!("Invalid arguments for job {}: {}", J::NAME, e))?;
+                job.run(args).await
+            })
+        });
+
+        self.jobs.insert(J::NAME.to_string(), RegisteredJob {
+            run,
+            args_version: J::ARGS_VERSION,
+            check_args: |value| J::validate(&serde_json::from_value::<J::Args>(value.clone())?),
+            migrate_args: J::migrate_args,
+        });
+    }
+
+    pub fn get(&self, name: &str) -> Option<JobFn> {
+        self.jobs.get(name).map(|job| job.run.clone())
+    }
+
+    /// Brings stored arguments up to the registered `ARGS_VERSION` and checks that they deserialize.
+    /// Returns the upgraded arguments with their new version, or `None` if they were already current.
+    fn upgrade_args(&self, def: &JobDefinition) -> Result<Option<(serde_json::Value, u32)>, ArgsError> {
+        let Some(job) = self.jobs.get(&def.name) else {
+            return Err(ArgsError::Unregistered);
+        };
+
+        if def.args_version > job.args_version {
+            return Err(ArgsError::TooNew(job.args_version));
+        }
+
+        let mut args = def.args.clone();
+        for version in def.args_version..job.args_version {
+            args = (job.migrate_args)(version, args).map_err(ArgsError::Invalid)?;
+        }
+        (job.check_args)(&args).map_err(ArgsError::Invalid)?;
+
+        Ok((def.args_version != job.args_version).then_some((args, job.args_version)))
+    }
+}
+
+enum ArgsError {
+    /// No job with this name is registered. Likely removed in this version, or added in a newer one.
+    Unregistered,
+    /// Written by a newer version of the bot, which registers the given version.
+    TooNew(u32),
+    /// Could not be migrated or deserialized.
+    Invalid(anyhow::Error),
+}
+
+#[derive(Clone)]
+pub struct Scheduler {
+    pool: Arc<SqlitePool>,
+    state: Arc<Mutex<SchedulerState>>,
+    registry: Arc<JobRegistry>,
+    concurrency: Arc<Semaphore>,
+    lease: Arc<Lease>,
+    clock: Arc<dyn Clock>,
+    /// When the run loop last woke up, in Unix milliseconds. Zero while the loop isn't running.
+    loop_beat: Arc<AtomicI64>,
+    /// When the jobs were last loaded from the store, in Unix milliseconds.
+    loaded_at: Arc<AtomicI64>,
+}
+
+/// Leadership lease in the database. Only the scheduler holding it runs jobs, so two bot processes
+/// sharing a database (e.g. during a deploy) never run the same job twice.
+struct Lease {
+    holder_id: String,
+    duration: std::time::Duration,
+    held: AtomicBool,
+}
+
+impl Scheduler {
+    pub fn new(pool: Arc<SqlitePool>, registry: Arc<JobRegistry>) -> Self {
+        Self {
+            pool,
+            state: Arc::new(Mutex::new(SchedulerState {
+                jobs: HashMap::new(),
+            })),
+            registry,
+            concurrency: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_JOBS)),
+            lease: Arc::new(Lease {
+                holder_id: Uuid::new_v4().to_string(),
+                duration: DEFAULT_LEASE_DURATION,
+                held: AtomicBool::new(false),
+            }),
+            clock: system_clock(),
+            loop_beat: Arc::new(AtomicI64::new(0)),
+            loaded_at: Arc::new(AtomicI64::new(0)),
+        }
+    }
+
+    /// Replaces the clock used for scheduling decisions, the lease and run outcomes.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The clock this scheduler runs on, for other parts of the bot that should share it.
+    pub fn clock(&self) -> Arc<dyn Clock> {
+        self.clock.clone()
+    }
+
+    /// Overrides how long the leadership lease stays valid without a heartbeat.
+    pub fn with_lease_duration(mut self, duration: std::time::Duration) -> Self {
+        self.lease = Arc::new(Lease {
+            holder_id: self.lease.holder_id.clone(),
+            duration,
+            held: AtomicBool::new(false),
+        });
+        self
+    }
+
+    /// Whether this scheduler held the leadership lease at its last heartbeat.
+    pub fn is_leader(&self) -> bool {
+        self.lease.held.load(Ordering::Acquire)
+    }
+
+    /// Whether the run loop is running and woke up recently enough to be considered alive.
+    pub fn is_running(&self) -> bool {
+        let beat = self.loop_beat.load(Ordering::Acquire);
+        let Some(beat) = DateTime::from_timestamp_millis(beat).filter(|_| beat != 0) else {
+            return false;
+        };
+        let stale_after = Duration::from_std(CHECK_INTERVAL * 3).unwrap_or(Duration::minutes(3));
+        self.clock.now() - beat <= stale_after
+    }
+
+    /// Overrides the global cap on concurrently running jobs.
+    pub fn with_max_concurrent_jobs(mut self, max_concurrent_jobs: usize) -> Self {
+        self.concurrency = Arc::new(Semaphore::new(max_concurrent_jobs.max(1)));
+        self
+    }
+
+    /// Load all jobs from the database into memory, replacing the ones already loaded. Called at startup, whenever
+    /// this scheduler takes over the lease and every `STORE_RELOAD_INTERVAL` while it holds it, since other instances
+    /// may have changed the `jobs` table in the meantime.
+    /// Rows that can't be parsed, or whose arguments can't be migrated to the registered version, are moved to
+    /// `jobs_quarantine` instead of failing the whole load.
+    pub async fn load_from_store(&self, ) -> anyhow::Result<()> {
+        info!("Loading jobs from store...");
+        // Locked before reading, so a job added in the meantime is either read here or inserted after the load
+        let mut state = self.state.lock().await;
+        let jobs = get_all_jobs(&self.pool) .await?;
+        let mut previous = std::mem::take(&mut state.jobs);
+        let now = self.clock.now();
+        self.loaded_at.store(now.timestamp_millis(), Ordering::Release);
+
+        for stored in jobs {
+            let mut def = match stored {
+                StoredJob::Valid(def) => def,
+                StoredJob::Corrupt(corrupt) => {
+                    error!("Quarantining corrupt job row: id = {}, name = {}: {}", corrupt.id, corrupt.name, corrupt.error);
+                    if let Err(e) = quarantine_job(&self.pool, &corrupt).await {
+                        error!("Failed to quarantine job {}: {}", corrupt.id, e);
+                    }
+                    continue;
+                }
+            };
+
+            match self.registry.upgrade_args(&def) {
+                Ok(None) => {},
+                Ok(Some((args, version))) => {
+                    info!("Migrated job arguments: id = {}, name = {}, version {} -> {}", def.id, def.name, def.args_version, version);
+                    if let Err(e) = update_job_args(&self.pool, def.id, &args, version).await {
+                        error!("Failed to store migrated arguments for job {}: {}", def.id, e);
+                    }
+                    def.args = args;
+                    def.args_version = version;
+                },
+                Err(ArgsError::Unregistered) => {
+                    warn!("Job name {} not found in registry, it will not run: id = {}", def.name, def.id);
+                },
+                Err(ArgsError::TooNew(version)) => {
+                    warn!("Skipping job with arguments from a newer version: id = {}, name = {}, version {} > {}",
+                        def.id, def.name, def.args_version, version);
+                    continue;
+                },
+                Err(ArgsError::Invalid(e)) => {
+                    error!("Quarantining job with invalid arguments: id = {}, name = {}: {}", def.id, def.name, e);
+                    if let Err(e) = quarantine_job(&self.pool, &CorruptJob::from_definition(&def, &e)).await {
+                        error!("Failed to quarantine job {}: {}", def.id, e);
+                    }
+                    continue;
+                }
+            }
+
+            if let Some(existing) = previous.remove(&def.id) {
+                // Keep the run state and the schedule position of jobs that were already loaded
+                state.jobs.insert(def.id, ScheduledJob { definition: def, ..existing });
+            } else if let Some(next_run) = def.schedule.first_run(now) {
+                info!("Loading job: id = {}, name = {}, next_run = {}",
+                    &def.id, &def.name, &next_run);
+                state.jobs.insert(def.id, ScheduledJob::new(def, next_run));
+            } else {
+                warn!("Skipping job: id = {}, name = {} (no valid next run time)",
+                    def.id, def.name);
+            }
+        }
+        info!("Finished loading {} jobs", state.jobs.len());
+        Ok(())
+    }
+
+    /// The main execution loop.
+    ///
+    /// # Arguments
+    /// * `token` - A cancellation token to gracefully shut down the scheduler.
+    pub async fn run(&self, token: CancellationToken) {
+        info!("Scheduler run loop started: holder_id = {}", self.lease.holder_id);
+        self.loop_beat.store(self.clock.now().timestamp_millis(), Ordering::Release);
+
+        loop {
+            tokio::select! {
+                _ = token.cancelled() => {
+                    info!("Scheduler received cancellation signal. Shutting down.");
+                    break;
+                }
+                _ = tokio::time::sleep(CHECK_INTERVAL) => {
+                    self.loop_beat.store(self.clock.now().timestamp_millis(), Ordering::Release);
+                    if self.heartbeat().await {
+                        self.tick().await;
+                    }
+                }
+            }
+        }
+
+        self.loop_beat.store(0, Ordering::Release);
+        self.release_lease().await;
+    }
+
+    /// Acquires or renews the leadership lease. Returns whether this scheduler is the leader until the next heartbeat.
+    async fn heartbeat(&self) -> bool {
+        let now = self.clock.now();
+        let expires_at = now + Duration::from_std(self.lease.duration).unwrap_or(Duration::minutes(3));
+        let held = match crate::db::jobs::try_acquire_lease(&self.pool, LEASE_NAME, &self.lease.holder_id, now, expires_at).await {
+            Ok(held) => held,
+            Err(e) => {
+                // Without a confirmed lease another instance may be running jobs, so stand down
+                error!("Failed to renew scheduler lease: {}", e);
+                false
+            }
+        };
+
+        let was_held = self.lease.held.swap(held, Ordering::AcqRel);
+        if held && !was_held {
+            info!("Acquired scheduler lease: holder_id = {}", self.lease.holder_id);
+            if let Err(e) = self.load_from_store().await {
+                error!("Failed to reload jobs after acquiring the lease: {}", e);
+            }
+        } else if !held && was_held {
+            warn!("Lost scheduler lease, no longer running jobs: holder_id = {}", self.lease.holder_id);
+        } else if held && now - DateTime::from_timestamp_millis(self.loaded_at.load(Ordering::Acquire)).unwrap_or_default() >= STORE_RELOAD_INTERVAL {
+            // Standby instances write their jobs to the store only, the leader has to load them to run them
+            if let Err(e) = self.load_from_store().await {
+                error!("Failed to reload jobs from store: {}", e);
+            }
+        }
+
+        held
+    }
+
+    /// Gives the lease up on shutdown, so another instance can take over without waiting for it to expire.
+    async fn release_lease(&self) {
+        if !self.lease.held.swap(false, Ordering::AcqRel) {
+            return;
+        }
+
+        match crate::db::jobs::release_lease(&self.pool, LEASE_NAME, &self.lease.holder_id).await {
+            Ok(_) => info!("Released scheduler lease: holder_id = {}", self.lease.holder_id),
+            Err(e) => error!("Failed to release scheduler lease: {}", e),
+        }
+    }
+
+    /// A single check for due jobs.
+    async fn tick(&self) {
+        let now = self.clock.now();
+        let mut state = self.state.lock().await;
+        let mut jobs_to_remove = Vec::new();
+
+        for job in state.jobs.values_mut() {
+            if job.definition.enabled && job.next_run <= now {
+                self.execute_job(job);
+                job.last_run = Some(now);
+
+                if let Some(next_run) = job.definition.schedule.next_run(now) {
+                    job.next_run = next_run;
+                } else {
+                    jobs_to_remove.push(job.definition.id);
+                }
+            }
+        }
+
+        if !jobs_to_remove.is_empty() {
+            for id in jobs_to_remove {
+                info!("Removing job from schedule: id = {}", id);
+                state.jobs.remove(&id);
+                if let Err(e) = delete_job(&self.pool, id).await {
+                    error!("Failed to remove job from db: {}", e);
+                }
+            }
+        }
+    }
+
+    fn execute_job(&self, job: &ScheduledJob) {
+        let Some(job_fn) = self.registry.get(&job.definition.name) else {
+            warn!("Job name {} not found in registry. Skipping execution.",
+                job.definition.name);
+            return;
+        };
+
+        let run = JobRun {
+            id: job.definition.id,
+            name: job.definition.name.clone(),
+            args: job.definition.args.clone(),
+            timeout: job.definition.options.timeout,
+            job_fn,
+            concurrency: self.concurrency.clone(),
+            runtime: job.runtime.clone(),
+            clock: self.clock.clone(),
+        };
+
+        if job.definition.options.overlap == OverlapPolicy::Allow {
+            tokio::spawn(run.execute());
+            return;
+        }
+
+        // The run slot is claimed before spawning so that the next tick sees it as taken
+        match job.runtime.running.clone().try_lock_owned() {
+            Ok(running) => {
+                tokio::spawn(async move {
+                    run.execute().await;
+                    drop(running);
+                });
+            }
+            Err(_) if job.definition.options.overlap == OverlapPolicy::Skip => {
+                warn!("Job is still running, skipping this run: id = {}, name = {}", run.id, run.name);
+            }
+            Err(_) => {
+                if job.runtime.queued.swap(true, Ordering::AcqRel) {
+                    warn!("Job is still running and already has a queued run, skipping this run: id = {}, name = {}", run.id, run.name);
+                    return;
+                }
+
+                info!("Job is still running, queueing this run: id = {}, name = {}", run.id, run.name);
+                let runtime = job.runtime.clone();
+                tokio::spawn(async move {
+                    let running = runtime.running.clone().lock_owned().await;
+                    runtime.queued.store(false, Ordering::Release);
+                    run.execute().await;
+                    drop(running);
+                });
+            }
+        }
+    }
+
+    /// Adds a new job of type `J` to the scheduler. The arguments are validated with `Job::validate` before anything is stored.
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule for the job, which can be a one-time run, an interval, or a cron expression.
+    /// * `args` - The arguments the job will be run with.
+    /// * `options` - Overlap policy and execution timeout for the job.
+    pub async fn add_job<J: Job>(
+        &self,
+        schedule: Schedule,
+        args: &J::Args,
+        options: JobOptions,
+    ) -> anyhow::Result<JobDefinition> {
+        if self.registry.get(J::NAME).is_none() {
+            return Err(anyhow::anyhow!("Job '{}' not found in registry", J::NAME));
+        }
+        J::validate(args)?;
+
+        let now = self.clock.now();
+        let def = JobDefinition {
+            id: Uuid::new_v4(),
+            name: J::NAME.to_string(),
+            schedule,
+            created_at: now,
+            args: serde_json::to_value(args)?,
+            args_version: J::ARGS_VERSION,
+            options,
+            enabled: true,
+        };
+
+        add_job(&self.pool, &def).await?;
+
+        if let Some(next_run) = def.schedule.first_run(now) {
+            let mut state = self.state.lock().await;
+            state.jobs.insert(def.id, ScheduledJob::new(def.clone(), next_run));
+            info!("Added job to schedule: id = {}, name = {}, next_run = {}",
+                def.id, def.name, next_run);
+        }
+
+        Ok(def)
+    }
+
+    /// Makes sure a job of type `J` exists, for jobs the bot runs on its own rather than on request.
+    /// The job's ID is derived from its name, so instances starting at the same time can't both create it.
+    /// An existing job is left as it is, including its schedule and whether it is paused.
+    ///
+    /// # Arguments
+    /// * `schedule` - The schedule to create the job with.
+    /// * `args` - The arguments to create the job with.
+    /// * `options` - Overlap policy and execution timeout for the job.
+    pub async fn ensure_job<J: Job>(
+        &self,
+        schedule: Schedule,
+        args: &J::Args,
+        options: JobOptions,
+    ) -> anyhow::Result<()> {
+        if self.registry.get(J::NAME).is_none() {
+            return Err(anyhow::anyhow!("Job '{}' not found in registry", J::NAME));
+        }
+        J::validate(args)?;
+
+        let id = Uuid::new_v5(&Uuid::NAMESPACE_OID, J::NAME.as_bytes());
+        let mut state = self.state.lock().await;
+        if state.jobs.contains_key(&id) {
+            return Ok(());
+        }
+
+        let now = self.clock.now();
+        let def = JobDefinition {
+            id,
+            name: J::NAME.to_string(),
+            schedule,
+            created_at: now,
+            args: serde_json::to_value(args)?,
+            args_version: J::ARGS_VERSION,
+            options,
+            enabled: true,
+        };
+
+        // Already stored but not loaded means it was quarantined or written by a newer version, so leave it be
+        if !crate::db::jobs::add_job_if_missing(&self.pool, &def).await? {
+            return Ok(());
+        }
+
+        if let Some(next_run) = def.schedule.first_run(now) {
+            info!("Created job: id = {}, name = {}, next_run = {}", def.id, def.name, next_run);
+            state.jobs.insert(def.id, ScheduledJob::new(def, next_run));
+        }
+
+        Ok(())
+    }
+
+    /// Removes a job from the scheduler and the database.
+    ///
+    /// # Arguments
+    /// * `id` - The UUID of the job to remove.
+    pub async fn remove_job(&self, id: Uuid) -> anyhow::Result<()> {
+        delete_job(&self.pool, id)
+            .await?;
+
+        let mut state = self.state.lock().await;
+        if state.jobs.remove(&id).is_some() {
+            info!("Removed job from schedule: id = {}", id);
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Job not found in active schedule"))
+        }
+    }
+
+    /// Replaces the schedule and arguments of a job of type `J`, keeping its ID, options and run state.
+    /// The next run is recomputed from the new schedule.
+    ///
+    /// # Arguments
+    /// * `id` - The UUID of the job to update.
+    /// * `schedule` - The new schedule.
+    /// * `args` - The new arguments, validated with `Job::validate` before anything is stored.
+    pub async fn update_job<J: Job>(&self, id: Uuid, schedule: Schedule, args: &J::Args) -> anyhow::Result<JobStatus> {
+        J::validate(args)?;
+        let args = serde_json::to_value(args)?;
+        let next_run = schedule.next_run(self.clock.now())
+            .ok_or_else(|| anyhow::anyhow!("Schedule has no upcoming run"))?;
+
+        let mut state = self.state.lock().await;
+        let job = state.jobs.get_mut(&id)
+            .filter(|job| job.definition.name == J::NAME)
+            .ok_or_else(|| anyhow::anyhow!("Job not found in active schedule"))?;
+
+        crate::db::jobs::update_job(&self.pool, id, &schedule, &args, J::ARGS_VERSION).await?;
+        job.definition.schedule = schedule;
+        job.definition.args = args;
+        job.definition.args_version = J::ARGS_VERSION;
+        job.next_run = next_run;
+
+        info!("Updated job: id = {}, name = {}, next_run = {}", id, job.definition.name, next_run);
+        Ok(job.status())
+    }
+
+    /// Removes every job matching `filter` from the scheduler and the database. Returns how many were removed.
+    pub async fn remove_jobs_where(&self, filter: impl Fn(&JobDefinition) -> bool) -> anyhow::Result<usize> {
+        let mut state = self.state.lock().await;
+        let ids: Vec<Uuid> = state.jobs.values()
+            .filter(|job| filter(&job.definition))
+            .map(|job| job.definition.id)
+            .collect();
+
+        for id in &ids {
+            delete_job(&self.pool, *id).await?;
+            state.jobs.remove(id);
+            info!("Removed job from schedule: id = {}", id);
+        }
+
+        Ok(ids.len())
+    }
+
+    /// Returns a snapshot of every job in the active schedule, ordered by next run.
+    pub async fn list_jobs(&self) -> Vec<JobStatus> {
+        let state = self.state.lock().await;
+        let mut jobs: Vec<JobStatus> = state.jobs.values().map(ScheduledJob::status).collect();
+        jobs.sort_by_key(|job| job.next_run);
+        jobs
+    }
+
+    /// Pauses or resumes a job. The flag is persisted, so a paused job stays paused across restarts.
+    ///
+    /// # Arguments
+    /// * `id` - The UUID of the job.
+    /// * `enabled` - `false` to pause the job, `true` to resume it.
+    pub async fn set_job_enabled(&self, id: Uuid, enabled: bool) -> anyhow::Result<JobStatus> {
+        let mut state = self.state.lock().await;
+        let job = state.jobs.get_mut(&id)
+            .ok_or_else(|| anyhow::anyhow!("Job not found in active schedule"))?;
+
+        crate::db::jobs::set_job_enabled(&self.pool, id, enabled).await?;
+        job.definition.enabled = enabled;
+
+        if enabled {
+            // Runs missed while paused are not caught up on, except for one-off jobs which run on the next tick
+            let now = self.clock.now();
+            if job.next_run < now {
+                job.next_run = job.definition.schedule.next_run(now).unwrap_or(now);
+            }
+        }
+
+        info!("{} job: id = {}, name = {}", if enabled { "Resumed" } else { "Paused" }, id, job.definition.name);
+        Ok(job.status())
+    }
+
+    /// Runs a job right away without affecting its schedule. Paused jobs can be triggered too.
+    ///
+    /// # Arguments
+    /// * `id` - The UUID of the job to run.
+    pub async fn trigger_job(&self, id: Uuid) -> anyhow::Result<()> {
+        let state = self.state.lock().await;
+        let job = state.jobs.get(&id)
+            .ok_or_else(|| anyhow::anyhow!("Job not found in active schedule"))?;
+
+        if self.registry.get(&job.definition.name).is_none() {
+            return Err(anyhow::anyhow!("Job '{}' not found in registry", job.definition.name));
+        }
+
+        info!("Manually triggering job: id = {}, name = {}", id, job.definition.name);
+        self.execute_job(job);
+        Ok(())
+    }
+}
+
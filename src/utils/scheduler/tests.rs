@@ -0,0 +1,598 @@
+use std::sync::Arc;
+use chrono::{Duration, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{sqlite::SqlitePoolOptions, Row, SqlitePool};
+use tokio::test;
+use crate::db::initialize_schema;
+use crate::utils::scheduler::*;
+use crate::utils::time::{Clock, ManualClock};
+
+#[derive(Serialize, Deserialize)]
+struct CountArgs {
+    count: u32,
+}
+
+struct CountJob;
+
+impl Job for CountJob {
+    const NAME: &'static str = "count";
+    const ARGS_VERSION: u32 = 2;
+
+    type Args = CountArgs;
+
+    fn validate(args: &Self::Args) -> anyhow::Result<()> {
+        if args.count == 0 { Err(anyhow::anyhow!("count must be positive")) } else { Ok(()) }
+    }
+
+    // Version 1 stored the count as `n`
+    fn migrate_args(from_version: u32, args: serde_json::Value) -> anyhow::Result<serde_json::Value> {
+        match from_version {
+            1 => Ok(serde_json::json!({ "count": args["n"].as_u64().ok_or_else(|| anyhow::anyhow!("missing n"))? })),
+            _ => Err(anyhow::anyhow!("unknown version {}", from_version)),
+        }
+    }
+
+    async fn run(&self, _args: Self::Args) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+async fn setup() -> (Arc<SqlitePool>, Scheduler) {
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    initialize_schema(&pool).await.unwrap();
+    let pool = Arc::new(pool);
+
+    let mut registry = JobRegistry::new();
+    registry.register(CountJob);
+
+    (pool.clone(), Scheduler::new(pool, Arc::new(registry)))
+}
+
+async fn insert_raw_job(pool: &SqlitePool, id: &str, schedule: &str, args: &str, args_version: u32) {
+    sqlx::query("INSERT INTO jobs (id, name, schedule, created_at, args, args_version) VALUES ($1, 'count', $2, $3, $4, $5);")
+        .bind(id)
+        .bind(schedule)
+        .bind(Utc::now())
+        .bind(args)
+        .bind(args_version)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+fn interval_schedule() -> String {
+    serde_json::to_string(&Schedule::Interval(Duration::hours(1))).unwrap()
+}
+
+#[test]
+async fn test_add_job_validates_arguments() {
+    let (_, scheduler) = setup().await;
+
+    assert!(scheduler.add_job::<CountJob>(Schedule::Interval(Duration::hours(1)), &CountArgs { count: 0 }, JobOptions::default()).await.is_err());
+    assert!(scheduler.list_jobs().await.is_empty());
+
+    let def = scheduler.add_job::<CountJob>(Schedule::Interval(Duration::hours(1)), &CountArgs { count: 3 }, JobOptions::default()).await.unwrap();
+    assert_eq!(def.args_version, CountJob::ARGS_VERSION);
+    assert_eq!(scheduler.list_jobs().await.len(), 1);
+}
+
+#[test]
+async fn test_load_migrates_old_arguments() {
+    let (pool, scheduler) = setup().await;
+    let id = uuid::Uuid::new_v4().to_string();
+    insert_raw_job(&pool, &id, &interval_schedule(), r#"{"n": 5}"#, 1).await;
+
+    scheduler.load_from_store().await.unwrap();
+
+    let jobs = scheduler.list_jobs().await;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].definition.args, serde_json::json!({ "count": 5 }));
+
+    let row = sqlx::query("SELECT args, args_version FROM jobs WHERE id = $1;").bind(&id).fetch_one(&*pool).await.unwrap();
+    assert_eq!(row.get::<u32, _>("args_version"), 2);
+    assert_eq!(serde_json::from_str::<serde_json::Value>(row.get("args")).unwrap(), serde_json::json!({ "count": 5 }));
+}
+
+#[test]
+async fn test_load_quarantines_corrupt_rows() {
+    let (pool, scheduler) = setup().await;
+    let valid = uuid::Uuid::new_v4().to_string();
+    let bad_schedule = uuid::Uuid::new_v4().to_string();
+    let bad_args = uuid::Uuid::new_v4().to_string();
+    insert_raw_job(&pool, &valid, &interval_schedule(), r#"{"count": 1}"#, 2).await;
+    insert_raw_job(&pool, &bad_schedule, "not a schedule", r#"{"count": 1}"#, 2).await;
+    insert_raw_job(&pool, &bad_args, &interval_schedule(), r#"{"count": "one"}"#, 2).await;
+
+    scheduler.load_from_store().await.unwrap();
+
+    let jobs = scheduler.list_jobs().await;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].definition.id.to_string(), valid);
+
+    let remaining: i64 = sqlx::query("SELECT COUNT(*) FROM jobs;").fetch_one(&*pool).await.unwrap().get(0);
+    assert_eq!(remaining, 1);
+    let quarantined: Vec<String> = sqlx::query("SELECT id FROM jobs_quarantine ORDER BY id;")
+        .fetch_all(&*pool).await.unwrap()
+        .iter().map(|row| row.get("id")).collect();
+    let mut expected = vec![bad_schedule, bad_args];
+    expected.sort();
+    assert_eq!(quarantined, expected);
+}
+
+#[test]
+async fn test_load_skips_arguments_from_newer_version() {
+    let (pool, scheduler) = setup().await;
+    let id = uuid::Uuid::new_v4().to_string();
+    insert_raw_job(&pool, &id, &interval_schedule(), r#"{"count": 1, "extra": true}"#, 3).await;
+
+    scheduler.load_from_store().await.unwrap();
+
+    assert!(scheduler.list_jobs().await.is_empty());
+    let remaining: i64 = sqlx::query("SELECT COUNT(*) FROM jobs;").fetch_one(&*pool).await.unwrap().get(0);
+    assert_eq!(remaining, 1);
+}
+
+struct TickJob(Arc<std::sync::atomic::AtomicUsize>);
+
+impl Job for TickJob {
+    const NAME: &'static str = "tick";
+
+    type Args = ();
+
+    async fn run(&self, _args: Self::Args) -> anyhow::Result<()> {
+        self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// Two schedulers on one SQLite file, as during a deploy where the old and new bot overlap.
+async fn setup_shared(lease: std::time::Duration, clock: Arc<ManualClock>) -> (Scheduler, Scheduler, Arc<std::sync::atomic::AtomicUsize>, std::path::PathBuf) {
+    let path = std::env::temp_dir().join(format!("mif-lease-{}.db", uuid::Uuid::new_v4()));
+    let url = format!("sqlite://{}?mode=rwc", path.display());
+    let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+    let mut schedulers = Vec::new();
+    for _ in 0..2 {
+        let pool = SqlitePoolOptions::new().max_connections(2).connect(&url).await.unwrap();
+        initialize_schema(&pool).await.unwrap();
+        let mut registry = JobRegistry::new();
+        registry.register(TickJob(runs.clone()));
+        schedulers.push(Scheduler::new(Arc::new(pool), Arc::new(registry)).with_lease_duration(lease).with_clock(clock.clone()));
+    }
+
+    let second = schedulers.pop().unwrap();
+    let first = schedulers.pop().unwrap();
+    (first, second, runs, path)
+}
+
+#[test]
+async fn test_only_one_scheduler_holds_the_lease() {
+    let (first, second, _, path) = setup_shared(std::time::Duration::from_secs(60), Arc::new(ManualClock::new(Utc::now()))).await;
+
+    assert!(first.heartbeat().await);
+    assert!(!second.heartbeat().await);
+    // Renewing keeps the lease with its holder
+    assert!(first.heartbeat().await);
+    assert!(!second.heartbeat().await);
+    assert!(first.is_leader());
+    assert!(!second.is_leader());
+
+    // Releasing on shutdown lets the other one take over right away
+    first.release_lease().await;
+    assert!(second.heartbeat().await);
+    assert!(!first.heartbeat().await);
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+async fn test_expired_lease_is_taken_over() {
+    let clock = Arc::new(ManualClock::new(Utc::now()));
+    let (first, second, _, path) = setup_shared(std::time::Duration::from_secs(180), clock.clone()).await;
+
+    assert!(first.heartbeat().await);
+    assert!(!second.heartbeat().await);
+    clock.advance(Duration::seconds(179));
+    assert!(!second.heartbeat().await);
+
+    // The holder stops sending heartbeats, e.g. because it hung or crashed
+    clock.advance(Duration::seconds(2));
+    assert!(second.heartbeat().await);
+    assert!(!first.heartbeat().await);
+    assert!(!first.is_leader());
+
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+async fn test_shared_database_runs_each_job_once() {
+    let clock = Arc::new(ManualClock::new(Utc::now()));
+    let (first, second, runs, path) = setup_shared(std::time::Duration::from_secs(60), clock.clone()).await;
+    first.add_job::<TickJob>(Schedule::Once(clock.now() + Duration::minutes(1)), &(), JobOptions::default()).await.unwrap();
+
+    // The job was added through the first instance, the second one picks it up from the shared table
+    second.load_from_store().await.unwrap();
+    clock.advance(Duration::minutes(1));
+
+    for scheduler in [&first, &second] {
+        if scheduler.heartbeat().await {
+            scheduler.tick().await;
+        }
+    }
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let _ = std::fs::remove_file(path);
+}
+
+/// A scheduler on an in-memory database whose time only moves when the test advances it.
+async fn setup_time_travel() -> (Scheduler, Arc<ManualClock>, Arc<std::sync::atomic::AtomicUsize>) {
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    initialize_schema(&pool).await.unwrap();
+    let runs = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let clock = Arc::new(ManualClock::new(Utc::now()));
+
+    let mut registry = JobRegistry::new();
+    registry.register(TickJob(runs.clone()));
+    let scheduler = Scheduler::new(Arc::new(pool), Arc::new(registry)).with_clock(clock.clone());
+
+    (scheduler, clock, runs)
+}
+
+/// Ticks the scheduler and gives the spawned runs a moment to finish.
+async fn tick_and_settle(scheduler: &Scheduler) {
+    scheduler.tick().await;
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+}
+
+#[test]
+async fn test_interval_job_runs_once_per_interval() {
+    let (scheduler, clock, runs) = setup_time_travel().await;
+    scheduler.add_job::<TickJob>(Schedule::Interval(Duration::hours(1)), &(), JobOptions::default()).await.unwrap();
+
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    clock.advance(Duration::minutes(59));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    clock.advance(Duration::minutes(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // A second tick at the same instant must not run it again
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let jobs = scheduler.list_jobs().await;
+    assert_eq!(jobs[0].next_run, clock.now() + Duration::hours(1));
+    assert!(matches!(jobs[0].last_outcome, Some((at, JobOutcome::Succeeded)) if at == clock.now()));
+}
+
+#[test]
+async fn test_once_job_is_removed_after_running() {
+    let (scheduler, clock, runs) = setup_time_travel().await;
+    scheduler.add_job::<TickJob>(Schedule::Once(clock.now() + Duration::days(2)), &(), JobOptions::default()).await.unwrap();
+
+    clock.advance(Duration::days(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(scheduler.list_jobs().await.len(), 1);
+
+    clock.advance(Duration::days(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(scheduler.list_jobs().await.is_empty());
+
+    // Gone from the store as well, so a restart does not run it again
+    scheduler.load_from_store().await.unwrap();
+    assert!(scheduler.list_jobs().await.is_empty());
+}
+
+#[test]
+async fn test_paused_job_skips_missed_runs_on_resume() {
+    let (scheduler, clock, runs) = setup_time_travel().await;
+    let def = scheduler.add_job::<TickJob>(Schedule::Interval(Duration::hours(1)), &(), JobOptions::default()).await.unwrap();
+    scheduler.set_job_enabled(def.id, false).await.unwrap();
+
+    clock.advance(Duration::hours(5));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    let status = scheduler.set_job_enabled(def.id, true).await.unwrap();
+    assert_eq!(status.next_run, clock.now() + Duration::hours(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    clock.advance(Duration::hours(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+}
+
+#[test]
+async fn test_cron_job_follows_the_clock() {
+    let (scheduler, clock, runs) = setup_time_travel().await;
+    clock.set(Utc.with_ymd_and_hms(2025, 1, 1, 11, 30, 0).unwrap());
+    let schedule = Schedule::Cron(CronSchedule::new("0 0 12 * * *", chrono_tz::UTC).unwrap());
+    scheduler.add_job::<TickJob>(schedule, &(), JobOptions::default()).await.unwrap();
+
+    clock.advance(Duration::minutes(29));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    clock.advance(Duration::minutes(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert_eq!(scheduler.list_jobs().await[0].next_run, Utc.with_ymd_and_hms(2025, 1, 2, 12, 0, 0).unwrap());
+}
+
+#[test]
+async fn test_update_job_replaces_schedule_and_arguments() {
+    let (pool, scheduler) = setup().await;
+    let def = scheduler.add_job::<CountJob>(Schedule::Interval(Duration::hours(1)), &CountArgs { count: 1 }, JobOptions::default()).await.unwrap();
+
+    assert!(scheduler.update_job::<CountJob>(def.id, Schedule::Interval(Duration::hours(2)), &CountArgs { count: 0 }).await.is_err());
+
+    let status = scheduler.update_job::<CountJob>(def.id, Schedule::Interval(Duration::hours(2)), &CountArgs { count: 7 }).await.unwrap();
+    assert_eq!(status.definition.args, serde_json::json!({ "count": 7 }));
+    assert!(status.next_run > Utc::now() + Duration::minutes(119));
+
+    // The change is persisted, so a restart picks it up
+    let reloaded = Scheduler::new(pool.clone(), scheduler.registry.clone());
+    reloaded.load_from_store().await.unwrap();
+    let jobs = reloaded.list_jobs().await;
+    assert_eq!(jobs[0].definition.args, serde_json::json!({ "count": 7 }));
+    assert!(matches!(jobs[0].definition.schedule, Schedule::Interval(every) if every == Duration::hours(2)));
+}
+
+#[test]
+async fn test_remove_jobs_where() {
+    let (pool, scheduler) = setup().await;
+    for count in 1..=4 {
+        scheduler.add_job::<CountJob>(Schedule::Interval(Duration::hours(1)), &CountArgs { count }, JobOptions::default()).await.unwrap();
+    }
+
+    let removed = scheduler.remove_jobs_where(|def| def.args["count"].as_u64().is_some_and(|count| count % 2 == 0)).await.unwrap();
+    assert_eq!(removed, 2);
+
+    let remaining: Vec<u64> = scheduler.list_jobs().await.iter().filter_map(|job| job.definition.args["count"].as_u64()).collect();
+    assert_eq!(remaining.len(), 2);
+    assert!(remaining.iter().all(|count| count % 2 == 1));
+    let stored: i64 = sqlx::query("SELECT COUNT(*) FROM jobs;").fetch_one(&*pool).await.unwrap().get(0);
+    assert_eq!(stored, 2);
+}
+
+#[test]
+async fn test_ensure_job_creates_it_once() {
+    let (pool, scheduler) = setup().await;
+    scheduler.ensure_job::<CountJob>(Schedule::Interval(Duration::hours(1)), &CountArgs { count: 1 }, JobOptions::default()).await.unwrap();
+    scheduler.ensure_job::<CountJob>(Schedule::Interval(Duration::hours(2)), &CountArgs { count: 2 }, JobOptions::default()).await.unwrap();
+
+    let jobs = scheduler.list_jobs().await;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].definition.args, serde_json::json!({ "count": 1 }));
+
+    // Another instance on the same database finds the stored job instead of adding a second one
+    let other = Scheduler::new(pool.clone(), scheduler.registry.clone());
+    other.ensure_job::<CountJob>(Schedule::Interval(Duration::hours(1)), &CountArgs { count: 3 }, JobOptions::default()).await.unwrap();
+    other.load_from_store().await.unwrap();
+    assert_eq!(other.list_jobs().await.len(), 1);
+    let stored: i64 = sqlx::query("SELECT COUNT(*) FROM jobs;").fetch_one(&*pool).await.unwrap().get(0);
+    assert_eq!(stored, 1);
+}
+
+#[test]
+async fn test_is_running_follows_the_run_loop() {
+    let (scheduler, clock, _) = setup_time_travel().await;
+    assert!(!scheduler.is_running());
+
+    let token = tokio_util::sync::CancellationToken::new();
+    let handle = tokio::spawn({
+        let scheduler = scheduler.clone();
+        let token = token.clone();
+        async move { scheduler.run(token).await }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert!(scheduler.is_running());
+
+    // A loop that stopped waking up, e.g. because it is stuck, no longer counts as running
+    clock.advance(Duration::minutes(10));
+    assert!(!scheduler.is_running());
+    clock.advance(Duration::minutes(-10));
+
+    token.cancel();
+    handle.await.unwrap();
+    assert!(!scheduler.is_running());
+}
+
+/// A job that holds its run open until the test hands out a permit, to keep runs overlapping.
+struct GateJob {
+    started: Arc<std::sync::atomic::AtomicUsize>,
+    gate: Arc<tokio::sync::Semaphore>,
+}
+
+impl Job for GateJob {
+    const NAME: &'static str = "gate";
+
+    type Args = ();
+
+    async fn run(&self, _args: Self::Args) -> anyhow::Result<()> {
+        self.started.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        self.gate.acquire().await?.forget();
+        Ok(())
+    }
+}
+
+/// Like `setup_time_travel`, but with `GateJob` and at most `max_concurrent_jobs` runs at a time.
+async fn setup_gated(max_concurrent_jobs: usize) -> (Scheduler, Arc<ManualClock>, Arc<std::sync::atomic::AtomicUsize>, Arc<tokio::sync::Semaphore>) {
+    let pool = SqlitePoolOptions::new().max_connections(1).connect("sqlite::memory:").await.unwrap();
+    initialize_schema(&pool).await.unwrap();
+    let started = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let gate = Arc::new(tokio::sync::Semaphore::new(0));
+    let clock = Arc::new(ManualClock::new(Utc::now()));
+
+    let mut registry = JobRegistry::new();
+    registry.register(GateJob { started: started.clone(), gate: gate.clone() });
+    let scheduler = Scheduler::new(Arc::new(pool), Arc::new(registry))
+        .with_clock(clock.clone())
+        .with_max_concurrent_jobs(max_concurrent_jobs);
+
+    (scheduler, clock, started, gate)
+}
+
+/// Makes the job due twice in a row while its first run is still held open by the gate.
+async fn run_overlapping(overlap: OverlapPolicy) -> (Scheduler, Arc<std::sync::atomic::AtomicUsize>, Arc<tokio::sync::Semaphore>) {
+    let (scheduler, clock, started, gate) = setup_gated(DEFAULT_MAX_CONCURRENT_JOBS).await;
+    let options = JobOptions { overlap, timeout: None };
+    scheduler.add_job::<GateJob>(Schedule::Interval(Duration::minutes(1)), &(), options).await.unwrap();
+
+    for _ in 0..3 {
+        clock.advance(Duration::minutes(1));
+        tick_and_settle(&scheduler).await;
+    }
+
+    (scheduler, started, gate)
+}
+
+#[test]
+async fn test_overlap_allow_runs_alongside() {
+    let (_, started, _) = run_overlapping(OverlapPolicy::Allow).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 3);
+}
+
+#[test]
+async fn test_overlap_skip_drops_due_runs() {
+    let (scheduler, started, gate) = run_overlapping(OverlapPolicy::Skip).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(scheduler.list_jobs().await[0].running);
+
+    // Nothing was kept for later
+    gate.add_permits(3);
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(!scheduler.list_jobs().await[0].running);
+}
+
+#[test]
+async fn test_overlap_queue_runs_once_more_afterwards() {
+    let (scheduler, started, gate) = run_overlapping(OverlapPolicy::Queue).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    // The first run finishing lets the single queued run start, the third due run was dropped
+    gate.add_permits(1);
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    gate.add_permits(2);
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 2);
+    assert!(!scheduler.list_jobs().await[0].running);
+}
+
+#[test]
+async fn test_job_exceeding_timeout_is_cancelled() {
+    let (scheduler, clock, started, _) = setup_gated(DEFAULT_MAX_CONCURRENT_JOBS).await;
+    let options = JobOptions { overlap: OverlapPolicy::Skip, timeout: Some(std::time::Duration::from_millis(50)) };
+    scheduler.add_job::<GateJob>(Schedule::Interval(Duration::minutes(1)), &(), options).await.unwrap();
+
+    clock.advance(Duration::minutes(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(scheduler.list_jobs().await[0].running);
+
+    // The gate never opens, so only the timeout ends the run
+    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    let job = &scheduler.list_jobs().await[0];
+    assert!(!job.running);
+    assert!(matches!(job.last_outcome, Some((_, JobOutcome::TimedOut))));
+}
+
+#[test]
+async fn test_concurrent_runs_are_capped() {
+    let (scheduler, clock, started, gate) = setup_gated(2).await;
+    for _ in 0..3 {
+        scheduler.add_job::<GateJob>(Schedule::Interval(Duration::minutes(1)), &(), JobOptions::default()).await.unwrap();
+    }
+
+    clock.advance(Duration::minutes(1));
+    tick_and_settle(&scheduler).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+    // The third run waits for a free slot rather than being dropped
+    gate.add_permits(1);
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert_eq!(started.load(std::sync::atomic::Ordering::SeqCst), 3);
+}
+
+#[test]
+async fn test_once_job_due_during_downtime_runs_after_restart() {
+    let (scheduler, clock, runs) = setup_time_travel().await;
+    scheduler.add_job::<TickJob>(Schedule::Once(clock.now() + Duration::hours(1)), &(), JobOptions::default()).await.unwrap();
+
+    // The bot is down while the job comes due and starts again an hour later
+    clock.advance(Duration::hours(2));
+    let restarted = Scheduler::new(scheduler.pool.clone(), scheduler.registry.clone()).with_clock(clock.clone());
+    restarted.load_from_store().await.unwrap();
+
+    let jobs = restarted.list_jobs().await;
+    assert_eq!(jobs.len(), 1);
+    assert_eq!(jobs[0].next_run, clock.now());
+
+    tick_and_settle(&restarted).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+    assert!(restarted.list_jobs().await.is_empty());
+    let stored: i64 = sqlx::query("SELECT COUNT(*) FROM jobs;").fetch_one(&*restarted.pool).await.unwrap().get(0);
+    assert_eq!(stored, 0);
+}
+
+#[test]
+async fn test_paused_once_job_due_during_downtime_can_be_resumed_or_removed() {
+    let (scheduler, clock, runs) = setup_time_travel().await;
+    let resumed = scheduler.add_job::<TickJob>(Schedule::Once(clock.now() + Duration::hours(1)), &(), JobOptions::default()).await.unwrap();
+    let removed = scheduler.add_job::<TickJob>(Schedule::Once(clock.now() + Duration::hours(1)), &(), JobOptions::default()).await.unwrap();
+    scheduler.set_job_enabled(resumed.id, false).await.unwrap();
+    scheduler.set_job_enabled(removed.id, false).await.unwrap();
+
+    clock.advance(Duration::hours(2));
+    let restarted = Scheduler::new(scheduler.pool.clone(), scheduler.registry.clone()).with_clock(clock.clone());
+    restarted.load_from_store().await.unwrap();
+
+    // Both are still listed so they can be managed, but neither runs while paused
+    let jobs = restarted.list_jobs().await;
+    assert_eq!(jobs.len(), 2);
+    assert!(jobs.iter().all(|job| !job.definition.enabled));
+    tick_and_settle(&restarted).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    restarted.remove_job(removed.id).await.unwrap();
+    restarted.set_job_enabled(resumed.id, true).await.unwrap();
+    tick_and_settle(&restarted).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    assert!(restarted.list_jobs().await.is_empty());
+    let stored: i64 = sqlx::query("SELECT COUNT(*) FROM jobs;").fetch_one(&*restarted.pool).await.unwrap().get(0);
+    assert_eq!(stored, 0);
+}
+
+#[test]
+async fn test_leader_picks_up_jobs_added_on_standby() {
+    let clock = Arc::new(ManualClock::new(Utc::now()));
+    let (leader, standby, runs, path) = setup_shared(std::time::Duration::from_secs(600), clock.clone()).await;
+    assert!(leader.heartbeat().await);
+    assert!(!standby.heartbeat().await);
+
+    // E.g. a reminder created through the instance that isn't running jobs
+    standby.add_job::<TickJob>(Schedule::Once(clock.now() + Duration::minutes(1)), &(), JobOptions::default()).await.unwrap();
+    clock.advance(Duration::minutes(1));
+    assert!(leader.heartbeat().await);
+    tick_and_settle(&leader).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 0);
+
+    clock.advance(STORE_RELOAD_INTERVAL);
+    assert!(leader.heartbeat().await);
+    tick_and_settle(&leader).await;
+    assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+    let _ = std::fs::remove_file(path);
+}
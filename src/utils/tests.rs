@@ -18,18 +18,45 @@ fn test_calculate_percentage_large_total() {
 }
 
 #[test]
-fn test_pretty_time_delta() {
-    assert_eq!(time::pretty_time_delta(&TimeDelta::seconds(1)), "1 second");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::seconds(2)), "2 seconds");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::seconds(60)), "1 minute");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::seconds(61)), "1 minute, 1 second");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::seconds(62)), "1 minute, 2 seconds");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::seconds(120)), "2 minutes");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::hours(1)), "1 hour");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::hours(2)), "2 hours");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::hours(24)), "1 day");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::hours(25)), "1 day, 1 hour");
-    assert_eq!(time::pretty_time_delta(&TimeDelta::hours(26)), "1 day, 2 hours");
+fn test_format_duration() {
+    let en = |delta: TimeDelta| format::format_duration(&delta, "en");
+    assert_eq!(en(TimeDelta::seconds(0)), "0 seconds");
+    assert_eq!(en(TimeDelta::seconds(1)), "1 second");
+    assert_eq!(en(TimeDelta::seconds(2)), "2 seconds");
+    assert_eq!(en(TimeDelta::seconds(60)), "1 minute");
+    assert_eq!(en(TimeDelta::seconds(61)), "1 minute, 1 second");
+    assert_eq!(en(TimeDelta::seconds(62)), "1 minute, 2 seconds");
+    assert_eq!(en(TimeDelta::seconds(120)), "2 minutes");
+    assert_eq!(en(TimeDelta::hours(1)), "1 hour");
+    assert_eq!(en(TimeDelta::hours(2)), "2 hours");
+    assert_eq!(en(TimeDelta::hours(24)), "1 day");
+    assert_eq!(en(TimeDelta::hours(25)), "1 day, 1 hour");
+    assert_eq!(en(TimeDelta::hours(26)), "1 day, 2 hours");
+    assert_eq!(en(-TimeDelta::minutes(90)), "-1 hour, 30 minutes");
+
+    let uk = |delta: TimeDelta| format::format_duration(&delta, "uk");
+    assert_eq!(uk(TimeDelta::seconds(1)), "1 секунда");
+    assert_eq!(uk(TimeDelta::minutes(2)), "2 хвилини");
+    assert_eq!(uk(TimeDelta::hours(5)), "5 годин");
+    assert_eq!(uk(TimeDelta::days(21) + TimeDelta::hours(11)), "21 день, 11 годин");
+    assert_eq!(uk(TimeDelta::days(1234)), "1\u{a0}234 дні");
+}
+
+#[test]
+fn test_plural_category() {
+    use format::PluralCategory::*;
+
+    assert_eq!(format::plural_category(1, "en"), One);
+    assert_eq!(format::plural_category(0, "en"), Other);
+    assert_eq!(format::plural_category(21, "en"), Other);
+
+    let uk = |count| format::plural_category(count, "uk");
+    assert_eq!([1, 21, 101, -31].map(uk), [One; 4]);
+    assert_eq!([2, 3, 4, 22, 104].map(uk), [Few; 5]);
+    assert_eq!([0, 5, 11, 12, 14, 19, 100, 111].map(uk), [Many; 8]);
+
+    assert_eq!(format::plural("format.duration.day", 3, "en"), "3 days");
+    assert_eq!(format::plural("format.duration.day", 3, "uk"), "3 дні");
 }
 
 #[test]
@@ -55,13 +82,441 @@ fn test_get_first_part_of_string() {
 }
 
 #[test]
-fn test_comma_readable_number() {
-    assert_eq!(comma_readable_number(0), "0");
-    assert_eq!(comma_readable_number(100), "100");
-    assert_eq!(comma_readable_number(1000), "1,000");
-    assert_eq!(comma_readable_number(1000000), "1,000,000");
-    assert_eq!(comma_readable_number(1000000000), "1,000,000,000");
-    assert_eq!(comma_readable_number(-100), "-100");
-    assert_eq!(comma_readable_number(-1000), "-1,000");
-    assert_eq!(comma_readable_number(-0), "0");
-}
\ No newline at end of file
+fn test_format_number() {
+    let en = |number| format::format_number(number, "en");
+    assert_eq!(en(0), "0");
+    assert_eq!(en(100), "100");
+    assert_eq!(en(1000), "1,000");
+    assert_eq!(en(1000000), "1,000,000");
+    assert_eq!(en(1000000000), "1,000,000,000");
+    assert_eq!(en(-100), "-100");
+    assert_eq!(en(-1000), "-1,000");
+    assert_eq!(en(-0), "0");
+    assert_eq!(en(i64::MIN), "-9,223,372,036,854,775,808");
+
+    assert_eq!(format::format_number(1234567, "uk"), "1\u{a0}234\u{a0}567");
+    assert_eq!(format::format_number(-999, "uk"), "-999");
+}
+
+#[test]
+fn test_format_decimal_and_percent() {
+    assert_eq!(format::format_decimal(1234.5678, 2, "en"), "1,234.57");
+    assert_eq!(format::format_decimal(1234.5678, 2, "uk"), "1\u{a0}234,57");
+    assert_eq!(format::format_decimal(-0.001, 2, "en"), "0.00");
+    assert_eq!(format::format_decimal(-2.5, 1, "en"), "-2.5");
+    assert_eq!(format::format_decimal(7.0, 0, "uk"), "7");
+
+    assert_eq!(format::format_percent(math::calculate_percentage(1, 3), "en"), "33.33%");
+    assert_eq!(format::format_percent(math::calculate_percentage(1, 3), "uk"), "33,33%");
+    assert_eq!(format::format_percent(100.0, "uk"), "100,00%");
+}
+
+#[test]
+fn test_format_date() {
+    let date = chrono::NaiveDate::from_ymd_opt(2018, 8, 3).unwrap();
+    assert_eq!(format::format_date(date, "en"), "August 3, 2018");
+    assert_eq!(format::format_date(date, "uk"), "3 серпня 2018 р.");
+
+    let date = chrono::NaiveDate::from_ymd_opt(2025, 12, 31).unwrap();
+    assert_eq!(format::format_date(date, "en"), "December 31, 2025");
+    assert_eq!(format::format_date(date, "uk"), "31 грудня 2025 р.");
+}
+
+#[test]
+fn test_cron_schedule_in_timezone() {
+    use chrono::{TimeZone, Utc};
+    use chrono_tz::Tz;
+    use scheduler::CronSchedule;
+
+    let schedule = CronSchedule::new("0 0 9 * * *", Tz::Europe__Kyiv).unwrap();
+    // Winter: Kyiv is UTC+2
+    let after = Utc.with_ymd_and_hms(2025, 1, 15, 12, 0, 0).unwrap();
+    assert_eq!(schedule.next_run(after), Some(Utc.with_ymd_and_hms(2025, 1, 16, 7, 0, 0).unwrap()));
+    // Summer: Kyiv is UTC+3
+    let after = Utc.with_ymd_and_hms(2025, 7, 15, 12, 0, 0).unwrap();
+    assert_eq!(schedule.next_run(after), Some(Utc.with_ymd_and_hms(2025, 7, 16, 6, 0, 0).unwrap()));
+}
+
+#[test]
+fn test_cron_schedule_across_dst_transitions() {
+    use chrono::{Duration, TimeZone, Utc};
+    use chrono_tz::Tz;
+    use scheduler::CronSchedule;
+
+    // 2025-03-09 02:30 does not exist in New York, so the run is skipped to the next day
+    let schedule = CronSchedule::new("0 30 2 * * *", Tz::America__New_York).unwrap();
+    let after = Utc.with_ymd_and_hms(2025, 3, 8, 12, 0, 0).unwrap();
+    assert_eq!(schedule.next_run(after), Some(Utc.with_ymd_and_hms(2025, 3, 10, 6, 30, 0).unwrap()));
+
+    // 2025-11-02 01:30 happens twice in New York, the run fires on the first occurrence only
+    let schedule = CronSchedule::new("0 30 1 * * *", Tz::America__New_York).unwrap();
+    let after = Utc.with_ymd_and_hms(2025, 11, 1, 12, 0, 0).unwrap();
+    let first = schedule.next_run(after).unwrap();
+    assert_eq!(first, Utc.with_ymd_and_hms(2025, 11, 2, 5, 30, 0).unwrap());
+    assert_eq!(schedule.next_run(first + Duration::seconds(1)), Some(Utc.with_ymd_and_hms(2025, 11, 3, 6, 30, 0).unwrap()));
+}
+
+#[test]
+fn test_legacy_cron_schedule_deserializes_as_utc() {
+    use chrono_tz::Tz;
+    use scheduler::Schedule;
+
+    let legacy: Schedule = serde_json::from_str(r#"{"type":"Cron","value":"0 0 9 * * *"}"#).unwrap();
+    match legacy {
+        Schedule::Cron(cron) => {
+            assert_eq!(cron.expression, "0 0 9 * * *");
+            assert_eq!(cron.timezone, Tz::UTC);
+        }
+        _ => panic!("Expected a cron schedule"),
+    }
+
+    let zoned = serde_json::to_string(&Schedule::Cron(scheduler::CronSchedule::new("0 0 9 * * *", Tz::Europe__Kyiv).unwrap())).unwrap();
+    match serde_json::from_str::<Schedule>(&zoned).unwrap() {
+        Schedule::Cron(cron) => assert_eq!(cron.timezone, Tz::Europe__Kyiv),
+        _ => panic!("Expected a cron schedule"),
+    }
+}
+
+#[test]
+fn test_refreshable_becomes_outdated_after_thirty_one_days() {
+    use apicallers::wolvesville::models::Refreshable;
+    use time::Clock;
+
+    struct CachedEntry(Option<chrono::DateTime<chrono::Utc>>);
+
+    impl Refreshable for CachedEntry {
+        fn timestamp(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+            self.0
+        }
+    }
+
+    let clock = time::ManualClock::new(chrono::Utc::now());
+    let entry = CachedEntry(Some(clock.now()));
+    assert!(!entry.is_outdated(&clock));
+
+    clock.advance(TimeDelta::days(31) - TimeDelta::seconds(1));
+    assert!(!entry.is_outdated(&clock));
+
+    clock.advance(TimeDelta::seconds(1));
+    assert!(entry.is_outdated(&clock));
+
+    // Entries without a timestamp have nothing to compare against
+    assert!(!CachedEntry(None).is_outdated(&clock));
+}
+
+#[test]
+fn test_cooldown_remaining() {
+    use time::Clock;
+
+    let clock = time::ManualClock::new(chrono::Utc::now());
+    let last = clock.now();
+    assert_eq!(time::cooldown_remaining(last, TimeDelta::minutes(30), clock.now()), Some(TimeDelta::minutes(30)));
+
+    clock.advance(TimeDelta::minutes(20));
+    assert_eq!(time::cooldown_remaining(last, TimeDelta::minutes(30), clock.now()), Some(TimeDelta::minutes(10)));
+
+    clock.advance(TimeDelta::minutes(10));
+    assert_eq!(time::cooldown_remaining(last, TimeDelta::minutes(30), clock.now()), None);
+}
+
+#[test]
+fn test_parse_point_in_time_durations() {
+    use chrono::TimeZone;
+    let now = chrono::Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+
+    assert_eq!(time::parse_point_in_time("in 2h30m drink water", now, chrono_tz::UTC), Some((now + TimeDelta::minutes(150), "drink water")));
+    assert_eq!(time::parse_point_in_time("2h 30m drink water", now, chrono_tz::UTC), Some((now + TimeDelta::minutes(150), "drink water")));
+    assert_eq!(time::parse_point_in_time("in 1 day 4 hours check   the clan", now, chrono_tz::UTC), Some((now + TimeDelta::hours(28), "check   the clan")));
+    assert_eq!(time::parse_point_in_time("через 3 години зайти в гру", now, chrono_tz::UTC), Some((now + TimeDelta::hours(3), "зайти в гру")));
+    assert_eq!(time::parse_point_in_time("45хв", now, chrono_tz::UTC), Some((now + TimeDelta::minutes(45), "")));
+    // A number that isn't followed by a unit is part of the text
+    assert_eq!(time::parse_point_in_time("in 10m buy 2 apples", now, chrono_tz::UTC), Some((now + TimeDelta::minutes(10), "buy 2 apples")));
+    assert_eq!(time::parse_point_in_time("in 2x", now, chrono_tz::UTC), None);
+    assert_eq!(time::parse_point_in_time("buy milk", now, chrono_tz::UTC), None);
+    assert_eq!(time::parse_point_in_time("", now, chrono_tz::UTC), None);
+}
+
+#[test]
+fn test_parse_point_in_time_rejects_overflowing_durations() {
+    use chrono::TimeZone;
+    let now = chrono::Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+
+    // Each of these used to panic instead of being rejected
+    assert_eq!(time::parse_point_in_time("in 1000000000d raid", now, chrono_tz::UTC), None);
+    assert_eq!(time::parse_point_in_time("in 1000000000 days raid", now, chrono_tz::UTC), None);
+    assert_eq!(time::parse_point_in_time("in 9000000000000000s raid", now, chrono_tz::UTC), None);
+    assert_eq!(time::parse_point_in_time("in 9000000000000000s 9000000000000000s raid", now, chrono_tz::UTC), None);
+    assert_eq!(time::parse_point_in_time("in 99999999999999999999d raid", now, chrono_tz::UTC), None);
+}
+
+#[test]
+fn test_parse_point_in_time_clock_times() {
+    use chrono::TimeZone;
+    let now = chrono::Utc.with_ymd_and_hms(2025, 3, 10, 12, 0, 0).unwrap();
+
+    assert_eq!(time::parse_point_in_time("tomorrow 18:00 raid", now, chrono_tz::UTC), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 11, 18, 0, 0).unwrap(), "raid")));
+    assert_eq!(time::parse_point_in_time("Завтра 09:30 рейд", now, chrono_tz::UTC), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 11, 9, 30, 0).unwrap(), "рейд")));
+    assert_eq!(time::parse_point_in_time("tomorrow raid", now, chrono_tz::UTC), Some((now + TimeDelta::days(1), "raid")));
+    assert_eq!(time::parse_point_in_time("today 18:00 raid", now, chrono_tz::UTC), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 10, 18, 0, 0).unwrap(), "raid")));
+    assert_eq!(time::parse_point_in_time("today raid", now, chrono_tz::UTC), None);
+    // A bare time that already passed today means tomorrow
+    assert_eq!(time::parse_point_in_time("13:00 raid", now, chrono_tz::UTC), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 10, 13, 0, 0).unwrap(), "raid")));
+    assert_eq!(time::parse_point_in_time("11:00 raid", now, chrono_tz::UTC), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 11, 11, 0, 0).unwrap(), "raid")));
+    assert_eq!(time::parse_point_in_time("2025-04-01 08:15 event", now, chrono_tz::UTC), Some((chrono::Utc.with_ymd_and_hms(2025, 4, 1, 8, 15, 0).unwrap(), "event")));
+    assert_eq!(time::parse_point_in_time("2025-04-01 event", now, chrono_tz::UTC), Some((chrono::Utc.with_ymd_and_hms(2025, 4, 1, 0, 0, 0).unwrap(), "event")));
+}
+
+#[test]
+fn test_parse_point_in_time_in_a_timezone() {
+    use chrono::TimeZone;
+    use chrono_tz::Tz;
+    // Already the 11th in Kyiv, UTC+2 until the clocks go forward on March 30
+    let now = chrono::Utc.with_ymd_and_hms(2025, 3, 10, 23, 0, 0).unwrap();
+    let kyiv = Tz::Europe__Kyiv;
+
+    assert_eq!(time::parse_point_in_time("today 18:00 raid", now, kyiv), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 11, 16, 0, 0).unwrap(), "raid")));
+    assert_eq!(time::parse_point_in_time("tomorrow 09:00 raid", now, kyiv), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 12, 7, 0, 0).unwrap(), "raid")));
+    assert_eq!(time::parse_point_in_time("00:30 raid", now, kyiv), Some((chrono::Utc.with_ymd_and_hms(2025, 3, 11, 22, 30, 0).unwrap(), "raid")));
+    assert_eq!(time::parse_point_in_time("2025-04-01 08:15 event", now, kyiv), Some((chrono::Utc.with_ymd_and_hms(2025, 4, 1, 5, 15, 0).unwrap(), "event")));
+    assert_eq!(time::parse_point_in_time("2025-03-30 03:30 event", now, kyiv), None);
+    // Durations don't depend on the timezone
+    assert_eq!(time::parse_point_in_time("in 2h raid", now, kyiv), Some((now + TimeDelta::hours(2), "raid")));
+}
+
+fn wolvesville_player(username: &str, level: Option<i32>, clan_id: Option<&str>, skill: Option<i32>) -> apicallers::wolvesville::models::WolvesvillePlayer {
+    serde_json::from_value(serde_json::json!({
+        "id": "player-id",
+        "username": username,
+        "level": level,
+        "clanId": clan_id,
+        "rankedSeasonSkill": skill,
+        "profileIconColor": "#ffffff",
+        "profileIconId": "icon",
+        "status": "",
+        "gameStats": {
+            "exitGameAfterDeathCount": 0, "exitGameBySuicideCount": 0, "gamesKilledCount": 0, "gamesSurvivedCount": 0,
+            "soloLoseCount": 0, "soloWinCount": 0, "totalLoseCount": 0, "totalPlayTimeInMinutes": 0, "totalTieCount": 0,
+            "totalWinCount": 0, "villageLoseCount": 0, "villageWinCount": 0, "votingLoseCount": 0, "votingWinCount": 0,
+            "werewolfLoseCount": 0, "werewolfWinCount": 0
+        }
+    })).unwrap()
+}
+
+#[test]
+fn test_diff_players() {
+    use wolvesville::{diff_players, PlayerChange, PlayerSnapshot};
+
+    let old = PlayerSnapshot::from(&wolvesville_player("Old", Some(10), None, Some(1500)));
+    assert!(diff_players(&old, &wolvesville_player("Old", Some(10), None, Some(1540)), 50).is_empty());
+
+    let new = wolvesville_player("New", Some(12), Some("clan"), Some(1550));
+    assert_eq!(diff_players(&old, &new, 50), vec![
+        PlayerChange::Username { from: "Old".to_string(), to: "New".to_string() },
+        PlayerChange::Level { from: 10, to: 12 },
+        PlayerChange::JoinedClan { clan_id: "clan".to_string() },
+        PlayerChange::RankedSkill { from: 1500, to: 1550 },
+    ]);
+
+    let new = PlayerSnapshot::from(&new);
+    assert_eq!(diff_players(&new, &wolvesville_player("New", None, Some("other"), Some(1400)), 100), vec![
+        PlayerChange::SwitchedClan { from: "clan".to_string(), to: "other".to_string() },
+        PlayerChange::RankedSkill { from: 1550, to: 1400 },
+    ]);
+    assert_eq!(diff_players(&new, &wolvesville_player("New", Some(12), None, None), 100), vec![
+        PlayerChange::LeftClan { clan_id: "clan".to_string() },
+    ]);
+}
+
+#[test]
+fn test_player_snapshot_adds_up_small_changes() {
+    use wolvesville::{diff_players, PlayerChange, PlayerSnapshot};
+
+    let mut notified = PlayerSnapshot::from(&wolvesville_player("Name", Some(10), None, Some(1500)));
+    // Three refreshes 20 SP apart, each below the threshold of 50
+    for skill in [1520, 1540] {
+        let player = wolvesville_player("Name", Some(10), None, Some(skill));
+        let changes = diff_players(&notified, &player, 50);
+        assert!(changes.is_empty());
+        notified.update(&player, &changes);
+    }
+    let player = wolvesville_player("Name", Some(10), None, Some(1560));
+    let changes = diff_players(&notified, &player, 50);
+    assert_eq!(changes, vec![PlayerChange::RankedSkill { from: 1500, to: 1560 }]);
+    notified.update(&player, &changes);
+    assert_eq!(notified.ranked_season_skill, Some(1560));
+
+    // A hidden level keeps the last known one, other changes move on
+    let player = wolvesville_player("Renamed", None, Some("clan"), Some(1570));
+    let changes = diff_players(&notified, &player, 50);
+    notified.update(&player, &changes);
+    assert_eq!(notified, PlayerSnapshot { username: "Renamed".to_string(), level: Some(10), clan_id: Some("clan".to_string()), ranked_season_skill: Some(1560) });
+}
+
+fn clan_member(id: &str, username: &str, is_co_leader: bool, last_online: &str) -> apicallers::wolvesville::models::WolvesvilleClanMember {
+    apicallers::wolvesville::models::WolvesvilleClanMember {
+        player_id: id.to_string(),
+        creation_time: "2024-01-01T00:00:00.000Z".to_string(),
+        xp: 0,
+        status: "ACCEPTED".to_string(),
+        is_co_leader,
+        username: username.to_string(),
+        level: 10,
+        last_online: last_online.to_string(),
+        profile_icon_id: "icon".to_string(),
+        profile_icon_color: "#ffffff".to_string(),
+        player_status: "DEFAULT".to_string(),
+        participate_in_clan_quests: None,
+    }
+}
+
+#[test]
+fn test_diff_clan_members() {
+    use chrono::{TimeZone, Utc};
+    use wolvesville::{diff_clan_members, ClanChange};
+
+    let now = Utc.with_ymd_and_hms(2025, 3, 20, 12, 0, 0).unwrap();
+    let previous_check = now - TimeDelta::hours(2);
+    let week = TimeDelta::days(7);
+    let old = vec![
+        clan_member("a", "Alpha", false, "2025-03-19T12:00:00.000Z"),
+        clan_member("b", "Bravo", false, "2025-03-14T12:00:00.000Z"),
+        clan_member("c", "Charlie", true, "2025-03-19T12:00:00.000Z"),
+    ];
+    assert!(diff_clan_members(&old, &old, week, previous_check, now).is_empty());
+
+    let new = vec![
+        clan_member("a", "Alpha", true, "2025-03-19T12:00:00.000Z"),
+        clan_member("b", "Bravo", false, "2025-03-14T12:00:00.000Z"),
+        clan_member("d", "Delta", false, "2025-03-20T11:00:00.000Z"),
+    ];
+    assert_eq!(diff_clan_members(&old, &new, week, previous_check, now), vec![
+        ClanChange::Promoted { username: "Alpha".to_string() },
+        ClanChange::Joined { username: "Delta".to_string() },
+        ClanChange::Left { username: "Charlie".to_string() },
+    ]);
+
+    // Bravo stays offline, going inactive is reported once, at the check that crosses the threshold
+    let bravo = &old[1..2];
+    let crossed = Utc.with_ymd_and_hms(2025, 3, 21, 13, 0, 0).unwrap();
+    assert_eq!(diff_clan_members(bravo, bravo, week, now, crossed), vec![
+        ClanChange::WentInactive { username: "Bravo".to_string(), last_online: Utc.with_ymd_and_hms(2025, 3, 14, 12, 0, 0).unwrap() },
+    ]);
+    assert!(diff_clan_members(bravo, bravo, week, crossed, crossed + TimeDelta::hours(2)).is_empty());
+}
+
+#[test]
+fn test_status_class() {
+    use reqwest::StatusCode;
+    assert_eq!(metrics::status_class(StatusCode::OK), "2xx");
+    assert_eq!(metrics::status_class(StatusCode::NO_CONTENT), "2xx");
+    assert_eq!(metrics::status_class(StatusCode::MOVED_PERMANENTLY), "3xx");
+    assert_eq!(metrics::status_class(StatusCode::NOT_FOUND), "4xx");
+    assert_eq!(metrics::status_class(StatusCode::TOO_MANY_REQUESTS), "4xx");
+    assert_eq!(metrics::status_class(StatusCode::BAD_GATEWAY), "5xx");
+}
+
+#[test]
+fn test_available_languages_come_from_the_locale_files() {
+    let codes: Vec<_> = language::available_languages().iter().map(|language| language.code).collect();
+    assert!(codes.contains(&"en"));
+    assert!(codes.contains(&"uk"));
+    assert!(language::available_languages().iter().all(|language| !language.name.is_empty() && !language.flag.is_empty()));
+}
+
+#[test]
+fn test_find_language() {
+    assert_eq!(language::find_language("uk").map(|language| language.code), Some("uk"));
+    assert_eq!(language::find_language(" UA ").map(|language| language.code), Some("uk"));
+    assert_eq!(language::find_language("Українська").map(|language| language.code), Some("uk"));
+    assert_eq!(language::find_language("English").map(|language| language.code), Some("en"));
+    assert_eq!(language::find_language("eng").map(|language| language.code), Some("en"));
+    assert!(language::find_language("klingon").is_none());
+    assert!(language::find_language("").is_none());
+
+    assert_eq!(language::language_of("uk").name, "Українська");
+    assert_eq!(language::language_of("xx").code, crate::bot::core::constants::DEFAULT_LANGUAGE);
+}
+
+#[test]
+fn test_resolve_language() {
+    // The user's own choice wins, then the guild's, then the Discord locale
+    assert_eq!(language::resolve_language(Some("en"), Some("uk"), Some("uk")), "en");
+    assert_eq!(language::resolve_language(None, Some("uk"), Some("en-US")), "uk");
+    assert_eq!(language::resolve_language(None, None, Some("uk")), "uk");
+    assert_eq!(language::resolve_language(None, None, Some("en-GB")), "en");
+    assert_eq!(language::resolve_language(None, None, None), crate::bot::core::constants::DEFAULT_LANGUAGE);
+
+    // Languages without a locale are skipped
+    assert_eq!(language::resolve_language(Some("xx"), Some("uk"), None), "uk");
+    assert_eq!(language::resolve_language(None, None, Some("pt-BR")), crate::bot::core::constants::DEFAULT_LANGUAGE);
+    assert!(language::language_for_discord_locale("ukrainian").is_none());
+}
+
+/// Every key passed to `t!` as a literal, with the file it's used in.
+fn literal_translation_keys(dir: &std::path::Path, keys: &mut Vec<(String, String)>) {
+    for entry in std::fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()) {
+        if entry.is_dir() {
+            literal_translation_keys(&entry, keys);
+            continue;
+        }
+        if entry.extension().is_none_or(|extension| extension != "rs") {
+            continue;
+        }
+
+        let source = std::fs::read_to_string(&entry).unwrap();
+        // Spelled out in two parts, so this file doesn't find itself
+        let pattern = concat!("t", "!(");
+        for (index, _) in source.match_indices(pattern) {
+            // Not the end of `format!(` or another macro
+            if source[..index].chars().next_back().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                continue;
+            }
+            let Some(rest) = source[index + pattern.len()..].trim_start().strip_prefix('"') else { continue };
+            let key = rest.split('"').next().unwrap();
+            keys.push((entry.display().to_string(), key.to_string()));
+        }
+    }
+}
+
+#[test]
+fn test_every_translation_key_exists_in_every_locale() {
+    let mut keys = Vec::new();
+    literal_translation_keys(&std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut keys);
+    assert!(keys.len() > 100, "Only found {} keys, the scan is broken", keys.len());
+
+    let mut missing = Vec::new();
+    for locale in rust_i18n::available_locales!() {
+        for (file, key) in &keys {
+            if language::translation(locale, key).is_none() {
+                missing.push(format!("`{}` in `{}`, used in {}", key, locale, file));
+            }
+        }
+        // Plural forms are picked at runtime
+        for unit in ["day", "hour", "minute", "second"] {
+            for category in ["one", "few", "many", "other"] {
+                let key = format!("format.duration.{}.{}", unit, category);
+                if language::translation(locale, &key).is_none() {
+                    missing.push(format!("`{}` in `{}`", key, locale));
+                }
+            }
+        }
+    }
+    missing.dedup();
+    assert!(missing.is_empty(), "Missing translations:\n{}", missing.join("\n"));
+}
+
+#[test]
+fn test_dynamic_translation() {
+    assert_eq!(language::dynamic_translation("commands.wov.clan.search.status.PRIVATE", "uk", "Private"), "Закритий");
+
+    // Every missing value under the same key adds to one series
+    let missing = metrics::MISSING_TRANSLATIONS.with_label_values(&["uk", "commands.wov.clan.search.status"]);
+    assert_eq!(language::dynamic_translation("commands.wov.clan.search.status.NEW_JOIN_TYPE", "uk", &humanize("NEW_JOIN_TYPE")), "New join type");
+    assert_eq!(language::dynamic_translation("commands.wov.clan.search.status.NEW_JOIN_TYPE", "uk", "New join type"), "New join type");
+    assert_eq!(language::dynamic_translation("commands.wov.clan.search.status.OTHER_JOIN_TYPE", "uk", "Other join type"), "Other join type");
+    assert_eq!(missing.get(), 3);
+
+    assert_eq!(humanize("JOIN_BY_REQUEST"), "Join by request");
+    assert_eq!(humanize("dnd"), "Dnd");
+    assert_eq!(humanize(""), "");
+}
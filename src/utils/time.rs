@@ -1,4 +1,60 @@
-use chrono::TimeDelta;
+use std::sync::Arc;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Source of the current time. Time-dependent logic asks a `Clock` instead of calling `Utc::now()`,
+/// so tests can move time forward without waiting.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The real wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// A clock that only moves when told to.
+#[cfg(test)]
+pub struct ManualClock {
+    now: std::sync::Mutex<DateTime<Utc>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        Self { now: std::sync::Mutex::new(start) }
+    }
+
+    pub fn advance(&self, by: TimeDelta) {
+        *self.now.lock().unwrap() += by;
+    }
+
+    pub fn set(&self, to: DateTime<Utc>) {
+        *self.now.lock().unwrap() = to;
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Returns how long is left until an action last done at `last` may be done again, or `None` if it already may.
+pub fn cooldown_remaining(last: DateTime<Utc>, cooldown: TimeDelta, now: DateTime<Utc>) -> Option<TimeDelta> {
+    let elapsed = now - last;
+    (elapsed < cooldown).then(|| cooldown - elapsed)
+}
 
 /// Return a discord-compatible timestamp string
 ///
@@ -13,37 +69,122 @@ pub fn get_long_date(timestamp: &i64) -> String {
     format!("<t:{}:D>", timestamp)
 }
 
-pub fn pretty_time_delta(delta: &TimeDelta) -> String {
-    let total_seconds = delta.num_seconds().abs();
-    let mut remaining_seconds = total_seconds;
-    let mut parts = Vec::new();
+/// Parses a point in time from the start of `input` and returns it together with the rest of the input.
+/// Understands durations ("in 2h30m", "1 day 4 hours", "через 3 години"), "today"/"tomorrow" with an optional
+/// `HH:MM` time, a bare `HH:MM` (the next time it comes around), and `YYYY-MM-DD` with an optional time.
+/// Dates and clock times are read in `timezone`, a clock time skipped by a daylight saving change isn't understood.
+pub fn parse_point_in_time(input: &str, now: DateTime<Utc>, timezone: Tz) -> Option<(DateTime<Utc>, &str)> {
+    let tokens = split_tokens(input);
+    let mut index = 0;
+    let word = |i: usize| tokens.get(i).map(|(_, token)| token.to_lowercase());
+    let local_now = now.with_timezone(&timezone).naive_local();
+    let at = |date: NaiveDate, time: NaiveTime| local_to_utc(date.and_time(time), timezone);
 
-    let days = remaining_seconds / 86400; // 86400 seconds in a day
-    if days > 0 {
-        parts.push(format!("{} day{}", days, if days > 1 { "s" } else { "" }));
-        remaining_seconds %= 86400;
+    if matches!(word(index).as_deref(), Some("in" | "через")) {
+        index += 1;
     }
 
-    let hours = remaining_seconds / 3600; // 3600 seconds in an hour
-    if hours > 0 {
-        parts.push(format!("{} hour{}", hours, if hours > 1 { "s" } else { "" }));
-        remaining_seconds %= 3600;
+    let (at, consumed) = if let Some((delta, consumed)) = parse_duration_tokens(&tokens[index..]) {
+        (now.checked_add_signed(delta)?, consumed)
+    } else {
+        match word(index).as_deref() {
+            Some(day @ ("today" | "сьогодні" | "tomorrow" | "завтра")) => {
+                let date = local_now.date() + TimeDelta::days(if matches!(day, "today" | "сьогодні") { 0 } else { 1 });
+                match word(index + 1).and_then(|token| parse_clock_time(&token)) {
+                    Some(time) => (at(date, time)?, 2),
+                    None if matches!(day, "tomorrow" | "завтра") => (at(date, local_now.time())?, 1),
+                    None => return None,
+                }
+            }
+            Some(token) => {
+                if let Some(time) = parse_clock_time(token) {
+                    match at(local_now.date(), time) {
+                        Some(today) if today > now => (today, 1),
+                        _ => (at(local_now.date() + TimeDelta::days(1), time)?, 1),
+                    }
+                } else {
+                    let date = NaiveDate::parse_from_str(token, "%Y-%m-%d").ok()?;
+                    match word(index + 1).and_then(|token| parse_clock_time(&token)) {
+                        Some(time) => (at(date, time)?, 2),
+                        None => (at(date, NaiveTime::MIN)?, 1),
+                    }
+                }
+            }
+            None => return None,
+        }
+    };
+
+    let rest = tokens.get(index + consumed).map_or("", |(offset, _)| &input[*offset..]);
+    Some((at, rest.trim()))
+}
+
+/// The earlier of the two instants when a daylight saving change repeats the local time, `None` when it skips it.
+fn local_to_utc(local: NaiveDateTime, timezone: Tz) -> Option<DateTime<Utc>> {
+    timezone.from_local_datetime(&local).earliest().map(|at| at.with_timezone(&Utc))
+}
+
+/// Splits on whitespace, keeping the byte offset of every token.
+fn split_tokens(input: &str) -> Vec<(usize, &str)> {
+    input.split_whitespace()
+        .map(|token| (token.as_ptr() as usize - input.as_ptr() as usize, token))
+        .collect()
+}
+
+/// Parses as many duration tokens as possible, e.g. `2h30m`, `2h 30m` or `2 hours 30 minutes`.
+/// Returns the total duration and the number of tokens used, or `None` if the first token is not a duration
+/// or the total is too large to represent.
+fn parse_duration_tokens(tokens: &[(usize, &str)]) -> Option<(TimeDelta, usize)> {
+    let mut total = TimeDelta::zero();
+    let mut consumed = 0;
+
+    while let Some((_, token)) = tokens.get(consumed) {
+        let token = token.to_lowercase();
+        if let Some(delta) = parse_compact_duration(&token) {
+            total = total.checked_add(&delta)?;
+            consumed += 1;
+        } else if let (Ok(amount), Some(unit)) = (token.parse::<i64>(), tokens.get(consumed + 1).and_then(|(_, unit)| unit_seconds(&unit.to_lowercase()))) {
+            total = total.checked_add(&TimeDelta::try_seconds(amount.checked_mul(unit)?)?)?;
+            consumed += 2;
+        } else {
+            break;
+        }
     }
 
-    let minutes = remaining_seconds / 60; // 60 seconds in a minute
-    if minutes > 0 {
-        parts.push(format!("{} minute{}", minutes, if minutes > 1 { "s" } else { "" }));
-        remaining_seconds %= 60;
+    (consumed > 0).then_some((total, consumed))
+}
+
+/// Parses a single token made of `<number><unit>` pairs, such as `1d12h` or `45хв`.
+fn parse_compact_duration(token: &str) -> Option<TimeDelta> {
+    let mut total = TimeDelta::zero();
+    let mut rest = token;
+
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+        let unit_len = rest[digits..].find(|c: char| c.is_ascii_digit()).unwrap_or(rest.len() - digits);
+        if digits == 0 || unit_len == 0 {
+            return None;
+        }
+
+        let amount: i64 = rest[..digits].parse().ok()?;
+        let unit = unit_seconds(&rest[digits..digits + unit_len])?;
+        total = total.checked_add(&TimeDelta::try_seconds(amount.checked_mul(unit)?)?)?;
+        rest = &rest[digits + unit_len..];
     }
 
-    if remaining_seconds > 0 || parts.is_empty() {
-        parts.push(format!(
-            "{} second{}",
-            remaining_seconds,
-            if remaining_seconds != 1 { "s" } else { "" }
-        ));
+    (!token.is_empty()).then_some(total)
+}
+
+fn unit_seconds(unit: &str) -> Option<i64> {
+    match unit {
+        "s" | "sec" | "secs" | "second" | "seconds" | "с" | "сек" | "секунда" | "секунди" | "секунд" | "секунду" => Some(1),
+        "m" | "min" | "mins" | "minute" | "minutes" | "хв" | "хвилина" | "хвилини" | "хвилин" | "хвилину" => Some(60),
+        "h" | "hr" | "hrs" | "hour" | "hours" | "г" | "год" | "година" | "години" | "годин" | "годину" => Some(3600),
+        "d" | "day" | "days" | "д" | "дн" | "день" | "дні" | "днів" => Some(86400),
+        "w" | "week" | "weeks" | "т" | "тиж" | "тиждень" | "тижні" | "тижнів" => Some(604800),
+        _ => None,
     }
+}
 
-    let sign = if delta.num_seconds() < 0 { "-" } else { "" };
-    format!("{}{}", sign, parts.join(", "))
-}
\ No newline at end of file
+fn parse_clock_time(token: &str) -> Option<chrono::NaiveTime> {
+    chrono::NaiveTime::parse_from_str(token, "%H:%M").ok()
+}
@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+use chrono::{DateTime, TimeDelta, Utc};
+use serde::{Deserialize, Serialize};
+use crate::utils::apicallers::wolvesville::models::{WolvesvilleClanMember, WolvesvillePlayer};
+
+/// A change between two snapshots of the same player that is worth telling someone about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlayerChange {
+    Username { from: String, to: String },
+    Level { from: i32, to: i32 },
+    JoinedClan { clan_id: String },
+    LeftClan { clan_id: String },
+    SwitchedClan { from: String, to: String },
+    RankedSkill { from: i32, to: i32 },
+}
+
+/// What a player watch reports on, as of the last time it was posted to a guild.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayerSnapshot {
+    pub username: String,
+    pub level: Option<i32>,
+    pub clan_id: Option<String>,
+    pub ranked_season_skill: Option<i32>,
+}
+
+impl From<&WolvesvillePlayer> for PlayerSnapshot {
+    fn from(player: &WolvesvillePlayer) -> Self {
+        Self {
+            username: player.username.clone(),
+            level: player.level,
+            clan_id: player.clan_id.clone(),
+            ranked_season_skill: player.ranked_season_skill,
+        }
+    }
+}
+
+impl PlayerSnapshot {
+    /// Moves the snapshot forward to `new` once `changes` have been posted. A level or ranked SP that was not posted,
+    /// because it is hidden or changed less than the threshold, keeps its old value, so small changes add up
+    /// until they are worth posting.
+    pub fn update(&mut self, new: &WolvesvillePlayer, changes: &[PlayerChange]) {
+        let level_posted = changes.iter().any(|change| matches!(change, PlayerChange::Level { .. }));
+        let skill_posted = changes.iter().any(|change| matches!(change, PlayerChange::RankedSkill { .. }));
+
+        let mut updated = PlayerSnapshot::from(new);
+        if !level_posted && self.level.is_some() {
+            updated.level = self.level;
+        }
+        if !skill_posted && self.ranked_season_skill.is_some() {
+            updated.ranked_season_skill = self.ranked_season_skill;
+        }
+        *self = updated;
+    }
+}
+
+/// Compares the last posted snapshot of a player with a fresh one. Ranked SP changes smaller than `sp_threshold` are ignored.
+pub fn diff_players(old: &PlayerSnapshot, new: &WolvesvillePlayer, sp_threshold: i32) -> Vec<PlayerChange> {
+    let mut changes = Vec::new();
+
+    if old.username != new.username {
+        changes.push(PlayerChange::Username { from: old.username.clone(), to: new.username.clone() });
+    }
+
+    // Levels are hidden for private profiles, which shouldn't read as a change
+    if let (Some(from), Some(to)) = (old.level, new.level) && to > from {
+        changes.push(PlayerChange::Level { from, to });
+    }
+
+    match (&old.clan_id, &new.clan_id) {
+        (None, Some(clan_id)) => changes.push(PlayerChange::JoinedClan { clan_id: clan_id.clone() }),
+        (Some(clan_id), None) => changes.push(PlayerChange::LeftClan { clan_id: clan_id.clone() }),
+        (Some(from), Some(to)) if from != to => changes.push(PlayerChange::SwitchedClan { from: from.clone(), to: to.clone() }),
+        _ => {}
+    }
+
+    if let (Some(from), Some(to)) = (old.ranked_season_skill, new.ranked_season_skill) && (to - from).abs() >= sp_threshold.max(1) {
+        changes.push(PlayerChange::RankedSkill { from, to });
+    }
+
+    changes
+}
+
+/// A change in the roster of a clan, carrying the current username of the member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClanChange {
+    Joined { username: String },
+    Left { username: String },
+    Promoted { username: String },
+    Demoted { username: String },
+    WentInactive { username: String, last_online: DateTime<Utc> },
+}
+
+/// Compares a stored member list of a clan with a fresh one. A member goes inactive once they have been offline
+/// for longer than `inactive_after`, which is reported only at the moment they cross it: inactive `now`, but not yet
+/// at `previous_check`, the last time the clan was compared.
+pub fn diff_clan_members(old: &[WolvesvilleClanMember], new: &[WolvesvilleClanMember], inactive_after: TimeDelta, previous_check: DateTime<Utc>, now: DateTime<Utc>) -> Vec<ClanChange> {
+    let old_by_id: HashMap<&str, &WolvesvilleClanMember> = old.iter().map(|member| (member.player_id.as_str(), member)).collect();
+    let new_by_id: HashMap<&str, &WolvesvilleClanMember> = new.iter().map(|member| (member.player_id.as_str(), member)).collect();
+    let is_inactive = |member: &WolvesvilleClanMember, at: DateTime<Utc>| last_online(member).is_some_and(|last_online| at - last_online > inactive_after);
+
+    let mut changes = Vec::new();
+    for member in new {
+        let username = member.username.clone();
+        let Some(previous) = old_by_id.get(member.player_id.as_str()) else {
+            changes.push(ClanChange::Joined { username });
+            continue;
+        };
+
+        match (previous.is_co_leader, member.is_co_leader) {
+            (false, true) => changes.push(ClanChange::Promoted { username: username.clone() }),
+            (true, false) => changes.push(ClanChange::Demoted { username: username.clone() }),
+            _ => {}
+        }
+
+        if is_inactive(member, now) && !is_inactive(previous, previous_check) && let Some(last_online) = last_online(member) {
+            changes.push(ClanChange::WentInactive { username, last_online });
+        }
+    }
+
+    for member in old {
+        if !new_by_id.contains_key(member.player_id.as_str()) {
+            changes.push(ClanChange::Left { username: member.username.clone() });
+        }
+    }
+
+    changes
+}
+
+fn last_online(member: &WolvesvilleClanMember) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(&member.last_online).ok().map(|time| time.with_timezone(&Utc))
+}